@@ -0,0 +1,90 @@
+use std::f32::consts::FRAC_PI_6;
+use glam::Vec2;
+
+/// What happens to boids crossing one pair of opposite edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AxisBoundary {
+    /// Reappear at the opposite edge
+    Wrap,
+    /// Reflect off the edge
+    Bounce,
+}
+
+/// Applies `mode` along one axis with edges at `-limit` and `limit`.
+pub fn apply_axis(mode: AxisBoundary, position: &mut f32, velocity: &mut f32, limit: f32) {
+    match mode {
+        AxisBoundary::Wrap => {
+            if *position < -limit {
+                *position = limit;
+            } else if *position > limit {
+                *position = -limit;
+            }
+        }
+        AxisBoundary::Bounce => {
+            if *position < -limit {
+                *position = -2. * limit - *position;
+                *velocity = velocity.abs();
+            } else if *position > limit {
+                *position = 2. * limit - *position;
+                *velocity = -velocity.abs();
+            }
+        }
+    }
+}
+
+/// Inradius, the centre to edge distance, of the largest flat-topped
+/// hexagon fitting in a rectangle of `half_extents` around the origin.
+pub fn hex_inradius(half_extents: Vec2) -> f32 {
+    // the hexagon's width is 4/sqrt(3) times its inradius
+    half_extents.y.min(half_extents.x * 3f32.sqrt() / 2.)
+}
+
+/// Maps `position` into the hexagon around the origin by subtracting the
+/// nearest point of the hexagonal lattice the world tiles.
+pub fn wrap_hexagonal(position: Vec2, inradius: f32) -> Vec2 {
+    // neighbouring hexagons sit across an edge, at twice the inradius
+    let a = Vec2::from_angle(FRAC_PI_6) * 2. * inradius;
+    let b = Vec2::Y * 2. * inradius;
+    // coordinates in the lattice basis
+    let determinant = a.x * b.y - a.y * b.x;
+    let i = (position.x * b.y - position.y * b.x) / determinant;
+    let j = (a.x * position.y - a.y * position.x) / determinant;
+    // with a 60 degree basis the nearest lattice point is a corner of the
+    // enclosing cell
+    let (i, j) = (i.floor(), j.floor());
+    let nearest = [(0., 0.), (1., 0.), (0., 1.), (1., 1.)]
+        .into_iter()
+        .map(|(di, dj)| a * (i + di) + b * (j + dj))
+        .min_by(|p, q| position.distance_squared(*p).total_cmp(&position.distance_squared(*q)))
+        .unwrap_or(Vec2::ZERO);
+    position - nearest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bouncing_reflects_position_and_velocity() {
+        let (mut position, mut velocity) = (12., 3.);
+        apply_axis(AxisBoundary::Bounce, &mut position, &mut velocity, 10.);
+        assert_eq!((position, velocity), (8., -3.));
+        let (mut position, mut velocity) = (-12., -3.);
+        apply_axis(AxisBoundary::Wrap, &mut position, &mut velocity, 10.);
+        assert_eq!((position, velocity), (10., -3.));
+    }
+
+    #[test]
+    fn hexagonal_wrapping_lands_inside_the_hexagon() {
+        let inradius = 100.;
+        for &position in &[Vec2::new(0., 150.), Vec2::new(180., 20.), Vec2::new(-130., -90.), Vec2::new(30., -40.)] {
+            let wrapped = wrap_hexagonal(position, inradius);
+            // inside every pair of opposite edges
+            for corner in 0..3 {
+                let normal = Vec2::from_angle(FRAC_PI_6 + corner as f32 * 2. * FRAC_PI_6);
+                assert!(wrapped.dot(normal).abs() <= inradius + 1e-3, "{position} wrapped to {wrapped}");
+            }
+        }
+        assert_eq!(wrap_hexagonal(Vec2::new(30., -40.), inradius), Vec2::new(30., -40.));
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    mem::size_of,
+};
+use glam::{IVec2, Vec2};
+
+/// Spatial hash of boids, or anything else identified by a `K`, so
+/// neighbour lookups only visit nearby cells instead of every boid.
+/// Meant to be cleared and refilled every tick.
+pub struct SpatialGrid<K> {
+    cell_size: f32,
+    /// Boids and their positions by cell
+    cells: HashMap<IVec2, Vec<(K, Vec2)>>,
+    /// Lowest and highest occupied cell, bounding nearest-K searches
+    extent: Option<(IVec2, IVec2)>,
+}
+
+/// A boid found by [`SpatialGrid::nearest`], ordered by distance so the
+/// farthest one kept sits at the top of the heap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Candidate<K> {
+    distance_squared: f32,
+    key: K,
+    position: Vec2,
+}
+
+impl<K: PartialEq> Eq for Candidate<K> {}
+
+impl<K: Ord> Ord for Candidate<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // ties broken by key so the result doesn't depend on cell order
+        self.distance_squared
+            .total_cmp(&other.distance_squared)
+            .then(self.key.cmp(&other.key))
+    }
+}
+
+impl<K: Ord> PartialOrd for Candidate<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Copy + Ord> SpatialGrid<K> {
+    /// An empty grid of square cells `cell_size` wide. Lookups are
+    /// quickest with cells as wide as the usual query radius.
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            extent: None,
+        }
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn insert(&mut self, key: K, position: Vec2) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((key, position));
+        self.extent = Some(match self.extent {
+            Some((min, max)) => (min.min(cell), max.max(cell)),
+            None => (cell, cell),
+        });
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Lowest and highest occupied cell.
+    pub fn extent(&self) -> Option<(IVec2, IVec2)> {
+        self.extent
+    }
+
+    /// Occupied cells and the boids in them.
+    pub fn cells(&self) -> impl Iterator<Item = (IVec2, &[(K, Vec2)])> {
+        self.cells
+            .iter()
+            .filter(|(_, boids)| !boids.is_empty())
+            .map(|(&cell, boids)| (cell, boids.as_slice()))
+    }
+
+    /// Boids in the cells overlapping the square of half size `radius`
+    /// around `position`, so callers still filter by distance.
+    pub fn neighbours(&self, position: Vec2, radius: f32) -> impl Iterator<Item = (K, Vec2)> + '_ {
+        let min = self.cell(position - radius);
+        let max = self.cell(position + radius);
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    /// The `k` boids nearest to `position` within `radius`, nearest first,
+    /// with their distances. Boids right at `position`, like the one asking,
+    /// are left out.
+    ///
+    /// Visits cells in square rings around the one holding `position`,
+    /// keeping the best `k` so far in a max-heap, and stops once the next
+    /// ring can't hold anything closer than the farthest one kept.
+    pub fn nearest(&self, position: Vec2, k: usize, radius: f32) -> Vec<(K, Vec2, f32)> {
+        if k == 0 || !position.is_finite() || radius.is_nan() || radius <= 0. {
+            return Vec::new();
+        }
+        let Some((first, last)) = self.extent else {
+            return Vec::new();
+        };
+        let radius_squared = radius * radius;
+        let center = self.cell(position);
+        // cells outside this box are empty or entirely outside the radius
+        let min = self.cell(position - radius).max(first);
+        let max = self.cell(position + radius).min(last);
+        if min.cmpgt(max).any() {
+            return Vec::new();
+        }
+        let last_ring = (center - min).abs().max((max - center).abs()).max_element();
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        for ring in 0..=last_ring {
+            for cell in ring_cells(center, ring, min, max) {
+                let Some(boids) = self.cells.get(&cell) else {
+                    continue;
+                };
+                for &(key, pos) in boids {
+                    let distance_squared = position.distance_squared(pos);
+                    if distance_squared == 0. || distance_squared >= radius_squared {
+                        continue;
+                    }
+                    let candidate = Candidate { distance_squared, key, position: pos };
+                    if heap.len() < k {
+                        heap.push(candidate);
+                    } else if heap.peek().is_some_and(|farthest| candidate < *farthest) {
+                        heap.pop();
+                        heap.push(candidate);
+                    }
+                }
+            }
+            // everything in the next ring is at least a whole ring of cells away
+            let reach = ring as f32 * self.cell_size;
+            if heap.len() == k && heap.peek().is_some_and(|farthest| farthest.distance_squared <= reach * reach) {
+                break;
+            }
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|candidate| (candidate.key, candidate.position, candidate.distance_squared.sqrt()))
+            .collect()
+    }
+
+    /// Empties the grid for refilling, keeping the allocations of cells
+    /// that were in use and dropping the rest.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| !cell.is_empty());
+        self.extent = None;
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    /// Switches to cells `cell_size` wide, emptying the grid.
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.cell_size = cell_size;
+        self.cells.clear();
+        self.extent = None;
+    }
+
+    /// Approximate heap memory held by the grid.
+    pub fn heap_bytes(&self) -> usize {
+        let table = self.cells.capacity() * size_of::<(IVec2, Vec<(K, Vec2)>)>();
+        let entries: usize = self.cells
+            .values()
+            .map(|cell| cell.capacity() * size_of::<(K, Vec2)>())
+            .sum();
+        table + entries
+    }
+}
+
+/// Cells at chessboard distance `ring` from `center`, clipped to the box
+/// from `min` to `max`.
+fn ring_cells(center: IVec2, ring: i32, min: IVec2, max: IVec2) -> impl Iterator<Item = IVec2> {
+    let low = (center - ring).max(min);
+    let high = (center + ring).min(max);
+    (low.y..=high.y)
+        .flat_map(move |y| {
+            // rows on the top and bottom edge are whole, the rest only have their ends
+            let (xs, step) = if y == center.y - ring || y == center.y + ring {
+                (low.x..=high.x, 1)
+            } else {
+                (center.x - ring..=center.x + ring, 2 * ring as usize)
+            };
+            xs.step_by(step)
+                .filter(move |&x| x >= low.x && x <= high.x)
+                .map(move |x| IVec2::new(x, y))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use super::*;
+
+    fn scattered(count: u32, extent: f32, cell_size: f32, seed: u64) -> (SpatialGrid<u32>, Vec<(u32, Vec2)>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut grid = SpatialGrid::new(cell_size);
+        let boids: Vec<(u32, Vec2)> = (0..count)
+            .map(|index| {
+                let position = Vec2::new(rng.gen_range(-extent..extent), rng.gen_range(-extent..extent));
+                (index, position)
+            })
+            .collect();
+        for &(key, position) in &boids {
+            grid.insert(key, position);
+        }
+        (grid, boids)
+    }
+
+    fn brute_force(boids: &[(u32, Vec2)], position: Vec2, k: usize, radius: f32) -> Vec<(u32, Vec2, f32)> {
+        let mut found: Vec<Candidate<u32>> = boids
+            .iter()
+            .map(|&(key, pos)| Candidate { distance_squared: position.distance_squared(pos), key, position: pos })
+            .filter(|candidate| candidate.distance_squared > 0. && candidate.distance_squared < radius * radius)
+            .collect();
+        found.sort();
+        found.truncate(k);
+        found.into_iter()
+            .map(|candidate| (candidate.key, candidate.position, candidate.distance_squared.sqrt()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let (grid, boids) = scattered(500, 400., 50., 7);
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..200 {
+            let position = Vec2::new(rng.gen_range(-450f32..450.), rng.gen_range(-450f32..450.));
+            let k = rng.gen_range(1..20);
+            let radius = rng.gen_range(10f32..300.);
+            assert_eq!(grid.nearest(position, k, radius), brute_force(&boids, position, k, radius));
+        }
+    }
+
+    #[test]
+    fn queries_from_a_boid_leave_it_out() {
+        let (grid, boids) = scattered(300, 200., 30., 3);
+        for &(key, position) in boids.iter().take(50) {
+            let nearest = grid.nearest(position, 7, 120.);
+            assert!(nearest.iter().all(|&(other, _, _)| other != key));
+            assert_eq!(nearest, brute_force(&boids, position, 7, 120.));
+        }
+    }
+
+    #[test]
+    fn sparse_and_unbounded_queries() {
+        // few boids spread over many cells, so the search has to walk far out
+        let (grid, boids) = scattered(20, 2000., 10., 5);
+        for &radius in &[5., 100., 5000., f32::INFINITY] {
+            for k in [1, 3, 20, 50] {
+                assert_eq!(grid.nearest(Vec2::ZERO, k, radius), brute_force(&boids, Vec2::ZERO, k, radius));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_results() {
+        let (grid, _) = scattered(100, 100., 20., 1);
+        assert!(grid.nearest(Vec2::ZERO, 0, 50.).is_empty());
+        assert!(grid.nearest(Vec2::ZERO, 5, 0.).is_empty());
+        assert!(SpatialGrid::<u32>::new(50.).nearest(Vec2::ZERO, 5, 50.).is_empty());
+    }
+}
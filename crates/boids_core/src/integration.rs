@@ -0,0 +1,167 @@
+use glam::Vec2;
+
+/// Kinematic state advanced by an [`Integrator`] each tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct State {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Numerical scheme used to advance boid velocity and position.
+///
+/// Accelerations are in world units per second squared; the acceleration
+/// closure passed to [`Integrator::step`] may be sampled more than once per
+/// step by the higher order schemes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Integrator {
+    /// Update velocity first, then move with the new velocity.
+    #[default]
+    SemiImplicitEuler,
+    /// Velocity Verlet, averaging the acceleration at both ends of the step.
+    Verlet,
+    /// Second order Runge-Kutta (midpoint method).
+    Rk2,
+}
+
+/// How drag grows with speed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DragModel {
+    Linear,
+    Quadratic,
+}
+
+/// Air resistance balanced by a constant thrust, so a boid left alone
+/// settles at `cruise_speed`: slower boids are pushed forward, faster ones
+/// held back, instead of being pinned at their max speed by the clamp.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drag {
+    pub model: DragModel,
+    pub coefficient: f32,
+    pub cruise_speed: f32,
+}
+
+impl Drag {
+    pub fn linear(coefficient: f32, cruise_speed: f32) -> Self {
+        Drag { model: DragModel::Linear, coefficient, cruise_speed }
+    }
+
+    pub fn quadratic(coefficient: f32, cruise_speed: f32) -> Self {
+        Drag { model: DragModel::Quadratic, coefficient, cruise_speed }
+    }
+
+    /// Net acceleration of drag and thrust at `velocity`, along the direction of travel.
+    pub fn acceleration(&self, velocity: Vec2) -> Vec2 {
+        let speed = velocity.length();
+        if speed == 0. {
+            return Vec2::ZERO;
+        }
+        let excess = match self.model {
+            DragModel::Linear => speed - self.cruise_speed,
+            DragModel::Quadratic => speed * speed - self.cruise_speed * self.cruise_speed,
+        };
+        velocity * (-self.coefficient * excess / speed)
+    }
+}
+
+impl Integrator {
+    pub fn step<F>(self, state: State, dt: f32, acceleration: F) -> State
+    where
+        F: Fn(Vec2, Vec2) -> Vec2,
+    {
+        let State { position, velocity } = state;
+        match self {
+            Integrator::SemiImplicitEuler => {
+                let velocity = velocity + acceleration(position, velocity) * dt;
+                State {
+                    position: position + velocity * dt,
+                    velocity,
+                }
+            }
+            Integrator::Verlet => {
+                let a0 = acceleration(position, velocity);
+                let position = position + velocity * dt + a0 * (0.5 * dt * dt);
+                // velocity dependent terms are sampled at the half step estimate
+                let a1 = acceleration(position, velocity + a0 * (0.5 * dt));
+                State {
+                    position,
+                    velocity: velocity + (a0 + a1) * (0.5 * dt),
+                }
+            }
+            Integrator::Rk2 => {
+                let a0 = acceleration(position, velocity);
+                let mid_position = position + velocity * (0.5 * dt);
+                let mid_velocity = velocity + a0 * (0.5 * dt);
+                let a_mid = acceleration(mid_position, mid_velocity);
+                State {
+                    position: position + mid_velocity * dt,
+                    velocity: velocity + a_mid * dt,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use super::*;
+
+    const DT: f32 = 0.05;
+
+    // unit mass on a circular orbit around a unit point mass
+    fn gravity(position: Vec2, _velocity: Vec2) -> Vec2 {
+        let r = position.length();
+        -position / (r * r * r)
+    }
+
+    fn energy(state: State) -> f32 {
+        0.5 * state.velocity.length_squared() - 1. / state.position.length()
+    }
+
+    /// Returns the (max, final) relative energy drift after `orbits` revolutions.
+    fn drift(integrator: Integrator, orbits: f32) -> (f32, f32) {
+        let mut state = State {
+            position: Vec2::X,
+            velocity: Vec2::Y,
+        };
+        let e0 = energy(state);
+        let mut max = 0f32;
+        for _ in 0..(TAU * orbits / DT) as usize {
+            state = integrator.step(state, DT, gravity);
+            max = max.max(((energy(state) - e0) / e0).abs());
+        }
+        (max, ((energy(state) - e0) / e0).abs())
+    }
+
+    #[test]
+    fn all_integrators_stay_close_to_the_initial_energy() {
+        for integrator in [Integrator::SemiImplicitEuler, Integrator::Verlet, Integrator::Rk2] {
+            let (max, _) = drift(integrator, 20.);
+            assert!(max < 1e-2, "{integrator:?} drifted by {max}");
+        }
+    }
+
+    #[test]
+    fn verlet_drifts_less_than_euler() {
+        let (euler, _) = drift(Integrator::SemiImplicitEuler, 20.);
+        let (verlet, _) = drift(Integrator::Verlet, 20.);
+        assert!(verlet < euler, "verlet {verlet} >= euler {euler}");
+    }
+
+    #[test]
+    fn symplectic_integrators_do_not_accumulate_drift() {
+        let (_, euler) = drift(Integrator::SemiImplicitEuler, 20.);
+        let (_, verlet) = drift(Integrator::Verlet, 20.);
+        let (_, rk2) = drift(Integrator::Rk2, 20.);
+        assert!(euler < 1e-4, "euler drifted by {euler}");
+        assert!(verlet < 1e-4, "verlet drifted by {verlet}");
+        assert!(rk2 > 1e-3, "rk2 drifted by only {rk2}");
+    }
+
+    #[test]
+    fn rk2_drift_grows_with_time() {
+        let (_, short) = drift(Integrator::Rk2, 10.);
+        let (_, long) = drift(Integrator::Rk2, 20.);
+        assert!(long > short * 1.5, "short {short}, long {long}");
+    }
+}
@@ -0,0 +1,13 @@
+//! The flocking algorithms behind the boids simulation, without any game
+//! engine: steering rules, the spatial index neighbours are found in,
+//! integrators and the geometry of the world's edges. The Bevy plugin is
+//! built on top of these, and they can be reused and tested without an ECS
+//! world.
+
+pub mod bounds;
+pub mod grid;
+pub mod integration;
+pub mod sensing;
+pub mod steering;
+
+pub use glam::{IVec2, Vec2};
@@ -0,0 +1,99 @@
+use glam::Vec2;
+
+use crate::steering;
+
+/// Distance along the ray from `origin` in unit `direction` to where it
+/// enters the circle around `center`, `None` if it misses it or starts
+/// inside it.
+pub fn ray_circle(origin: Vec2, direction: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = center - origin;
+    let along = offset.dot(direction);
+    let miss_squared = offset.length_squared() - along * along;
+    let radius_squared = radius * radius;
+    if along < 0. || miss_squared > radius_squared || offset.length_squared() < radius_squared {
+        return None;
+    }
+    Some(along - (radius_squared - miss_squared).sqrt())
+}
+
+/// Distance along the ray from `origin` in unit `direction` to the nearest
+/// wall it reaches, with walls at `-half_extents` and `half_extents` on the
+/// axes `walls` says are walled off. `None` if it reaches none.
+pub fn ray_walls(origin: Vec2, direction: Vec2, half_extents: Vec2, walls: (bool, bool)) -> Option<f32> {
+    let axis = |walled: bool, origin: f32, direction: f32, half_extent: f32| {
+        (walled && direction != 0.).then(|| ((half_extent * direction.signum() - origin) / direction).max(0.))
+    };
+    let x = axis(walls.0, origin.x, direction.x, half_extents.x);
+    let y = axis(walls.1, origin.y, direction.y, half_extents.y);
+    match (x, y) {
+        (Some(x), Some(y)) => Some(x.min(y)),
+        (hit, None) | (None, hit) => hit,
+    }
+}
+
+/// A ray cast ahead of a boid to feel for walls and obstacles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Whisker {
+    /// Unit direction in world space
+    pub direction: Vec2,
+    pub length: f32,
+    /// How far along the whisker it touched something, if it did
+    pub hit: Option<f32>,
+}
+
+/// Steering from whiskers: each touching something turns the boid away
+/// from its direction, harder the closer the touch. A side whisker alone
+/// turns the boid until it runs along the wall, so boids follow walls and
+/// corridors instead of being pushed off them. Nothing if no whisker
+/// touches anything.
+pub fn follow_walls(velocity: Vec2, whiskers: &[Whisker], max_speed: f32, max_force: f32) -> Vec2 {
+    let mut turn = Vec2::ZERO;
+    let mut urgency = 0f32;
+    for whisker in whiskers {
+        let Some(hit) = whisker.hit else {
+            continue;
+        };
+        let closeness = 1. - (hit / whisker.length).clamp(0., 1.);
+        turn -= whisker.direction * closeness;
+        urgency = urgency.max(closeness);
+    }
+    if urgency == 0. {
+        return Vec2::ZERO;
+    }
+    steering::head(velocity, velocity.normalize_or_zero() + turn * 2., max_speed, max_force) * urgency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rays_hit_the_near_side_of_circles() {
+        assert_eq!(ray_circle(Vec2::ZERO, Vec2::X, Vec2::new(10., 0.), 2.), Some(8.));
+        assert_eq!(ray_circle(Vec2::ZERO, Vec2::X, Vec2::new(-10., 0.), 2.), None);
+        assert_eq!(ray_circle(Vec2::ZERO, Vec2::X, Vec2::new(10., 3.), 2.), None);
+        assert_eq!(ray_circle(Vec2::ZERO, Vec2::X, Vec2::new(1., 0.), 2.), None);
+    }
+
+    #[test]
+    fn rays_only_hit_walled_axes() {
+        let half_extents = Vec2::new(10., 5.);
+        assert_eq!(ray_walls(Vec2::ZERO, Vec2::X, half_extents, (true, true)), Some(10.));
+        assert_eq!(ray_walls(Vec2::ZERO, -Vec2::Y, half_extents, (true, true)), Some(5.));
+        assert_eq!(ray_walls(Vec2::ZERO, Vec2::X, half_extents, (false, true)), None);
+        let diagonal = Vec2::new(1., 1.).normalize();
+        let hit = ray_walls(Vec2::ZERO, diagonal, half_extents, (true, true)).unwrap();
+        assert!((hit - 5. * 2f32.sqrt()).abs() < 1e-4, "{hit}");
+    }
+
+    #[test]
+    fn a_side_whisker_turns_the_boid_along_the_wall() {
+        let velocity = Vec2::new(10., 0.);
+        // a wall close on the left, slanting in front of the boid
+        let left = Whisker { direction: Vec2::new(1., 1.).normalize(), length: 10., hit: Some(3.) };
+        let force = follow_walls(velocity, &[left], 10., 100.);
+        assert!(force.y < 0., "{force}");
+        let clear = Whisker { hit: None, ..left };
+        assert_eq!(follow_walls(velocity, &[clear], 10., 100.), Vec2::ZERO);
+    }
+}
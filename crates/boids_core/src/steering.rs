@@ -0,0 +1,203 @@
+use glam::Vec2;
+
+/// Force turning something at `position` moving at `velocity` towards
+/// `target` at `max_speed`, at most `max_force`.
+pub fn seek(position: Vec2, velocity: Vec2, target: Vec2, max_speed: f32, max_force: f32) -> Vec2 {
+    // nothing when already there
+    head(velocity, target - position, max_speed, max_force)
+}
+
+/// Force turning something at `position` moving at `velocity` away from
+/// `threat` at `max_speed`, at most `max_force`.
+pub fn flee(position: Vec2, velocity: Vec2, threat: Vec2, max_speed: f32, max_force: f32) -> Vec2 {
+    head(velocity, position - threat, max_speed, max_force)
+}
+
+/// Force turning something moving at `velocity` to move along `direction`
+/// at `max_speed`, at most `max_force`, or nothing if `direction` is zero.
+pub fn head(velocity: Vec2, direction: Vec2, max_speed: f32, max_force: f32) -> Vec2 {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+    (direction * max_speed - velocity).clamp_length_max(max_force)
+}
+
+/// Reynolds' separation: steering away from neighbours, given as their
+/// positions and distances, each pushing harder the closer it is.
+pub fn separation(
+    position: Vec2,
+    velocity: Vec2,
+    neighbours: impl IntoIterator<Item = (Vec2, f32)>,
+    max_speed: f32,
+    max_force: f32,
+) -> Vec2 {
+    let mut away = Vec2::ZERO;
+    let mut count = 0;
+    for (neighbour, distance) in neighbours {
+        away += (position - neighbour).normalize_or_zero() / distance;
+        count += 1;
+    }
+    if count > 0 {
+        away /= count as f32;
+    }
+    head(velocity, away, max_speed, max_force)
+}
+
+/// Separation by predicted closest approach: steering away from where
+/// neighbours, given as their positions and velocities, will pass closest
+/// within the next `horizon` seconds, each pushing harder the closer and
+/// the sooner that is. Neighbours that won't come within `radius` are left
+/// alone, so boids flying side by side don't push each other apart while
+/// ones on a collision course start turning early.
+pub fn predictive_separation(
+    position: Vec2,
+    velocity: Vec2,
+    neighbours: impl IntoIterator<Item = (Vec2, Vec2)>,
+    radius: f32,
+    horizon: f32,
+    max_speed: f32,
+    max_force: f32,
+) -> Vec2 {
+    let mut away = Vec2::ZERO;
+    let mut count = 0;
+    for (neighbour, neighbour_velocity) in neighbours {
+        let offset = position - neighbour;
+        let closing = velocity - neighbour_velocity;
+        // drifting apart or keeping pace, the closest they get is now
+        let speed_squared = closing.length_squared();
+        let time = if speed_squared > 0. {
+            (-offset.dot(closing) / speed_squared).clamp(0., horizon.max(0.))
+        } else {
+            0.
+        };
+        let closest = offset + closing * time;
+        let distance = closest.length();
+        if distance >= radius {
+            continue;
+        }
+        // head on, sidestep instead of just braking
+        let direction = closest.try_normalize().unwrap_or_else(|| closing.perp().normalize_or_zero());
+        away += direction * (1. - distance / radius) / (1. + time);
+        count += 1;
+    }
+    if count > 0 {
+        away /= count as f32;
+    }
+    head(velocity, away, max_speed, max_force)
+}
+
+/// Reynolds' alignment: steering towards the mean heading of neighbours,
+/// given as their velocities and weights. Nothing without neighbours, and
+/// slowing down if their velocities cancel out.
+pub fn alignment(
+    velocity: Vec2,
+    neighbours: impl IntoIterator<Item = (Vec2, f32)>,
+    max_speed: f32,
+    max_force: f32,
+) -> Vec2 {
+    let Some(mean) = weighted_mean(neighbours) else {
+        return Vec2::ZERO;
+    };
+    (mean.normalize_or_zero() * max_speed - velocity).clamp_length_max(max_force)
+}
+
+/// One relaxation step of the XY spin model: `heading` turns towards its
+/// neighbours' headings by `coupling` times the mean sine of the angle to
+/// each, per second, over `dt` seconds. Angles are in radians and nothing
+/// changes without neighbours.
+pub fn align_spin(heading: f32, neighbours: impl IntoIterator<Item = f32>, coupling: f32, dt: f32) -> f32 {
+    let (sum, count) = neighbours
+        .into_iter()
+        .fold((0., 0), |(sum, count), other| (sum + (other - heading).sin(), count + 1));
+    if count == 0 {
+        return heading;
+    }
+    heading + coupling * sum / count as f32 * dt
+}
+
+/// Reynolds' cohesion: steering towards the centre of neighbours, given as
+/// their positions and weights. Nothing without neighbours.
+pub fn cohesion(
+    position: Vec2,
+    velocity: Vec2,
+    neighbours: impl IntoIterator<Item = (Vec2, f32)>,
+    max_speed: f32,
+    max_force: f32,
+) -> Vec2 {
+    weighted_mean(neighbours).map_or(Vec2::ZERO, |centre| seek(position, velocity, centre, max_speed, max_force))
+}
+
+/// Mean of the values weighted by their weights, `None` if the weights
+/// add up to zero.
+fn weighted_mean(values: impl IntoIterator<Item = (Vec2, f32)>) -> Option<Vec2> {
+    let (sum, total) = values
+        .into_iter()
+        .fold((Vec2::ZERO, 0.), |(sum, total), (value, weight)| (sum + value * weight, total + weight));
+    (total > 0.).then(|| sum / total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forces_are_clamped_to_max_force() {
+        let force = seek(Vec2::ZERO, Vec2::ZERO, Vec2::new(100., 0.), 50., 10.);
+        assert_eq!(force, Vec2::new(10., 0.));
+        assert_eq!(flee(Vec2::ZERO, Vec2::ZERO, Vec2::new(100., 0.), 50., 10.), -force);
+    }
+
+    #[test]
+    fn separation_pushes_hardest_from_the_closest() {
+        // a close neighbour on the right outweighs a far one on the left
+        let neighbours = [(Vec2::new(5., 0.), 5.), (Vec2::new(-40., 0.), 40.)];
+        let force = separation(Vec2::ZERO, Vec2::ZERO, neighbours, 50., 100.);
+        assert!(force.x < 0. && force.y.abs() < 1e-6, "{force}");
+    }
+
+    #[test]
+    fn predictive_separation_ignores_boids_passing_wide() {
+        // flying alongside at the same speed, never closer than now
+        let beside = [(Vec2::new(0., 30.), Vec2::new(10., 0.))];
+        assert_eq!(predictive_separation(Vec2::ZERO, Vec2::new(10., 0.), beside, 25., 2., 10., 100.), Vec2::ZERO);
+        // crossing ahead, passing 40 away
+        let crossing = [(Vec2::new(40., -40.), Vec2::new(0., 10.))];
+        assert_eq!(predictive_separation(Vec2::ZERO, Vec2::ZERO, crossing, 25., 10., 10., 100.), Vec2::ZERO);
+    }
+
+    #[test]
+    fn predictive_separation_turns_away_from_a_collision_course() {
+        // a neighbour 100 ahead and a little to the left flying straight at
+        // the boid, well outside the radius now but about to hit it
+        let velocity = Vec2::new(10., 0.);
+        let oncoming = [(Vec2::new(100., 2.), Vec2::new(-10., 0.))];
+        let force = predictive_separation(Vec2::ZERO, velocity, oncoming, 25., 10., 10., 100.);
+        assert!(force.y < 0., "{force}");
+        // nothing this far out within a short horizon
+        assert_eq!(predictive_separation(Vec2::ZERO, velocity, oncoming, 25., 1., 10., 100.), Vec2::ZERO);
+        // exactly head on, it sidesteps
+        let head_on = [(Vec2::new(100., 0.), Vec2::new(-10., 0.))];
+        let force = predictive_separation(Vec2::ZERO, velocity, head_on, 25., 10., 10., 100.);
+        assert!(force.y.abs() > 0., "{force}");
+    }
+
+    #[test]
+    fn spins_turn_the_short_way_towards_their_neighbours() {
+        // a neighbour at 2π - 0.3 is just clockwise, not most of a turn away
+        let turned = align_spin(0.2, [std::f32::consts::TAU - 0.3], 1., 0.1);
+        assert!(turned < 0.2, "{turned}");
+        // opposite neighbours pull equally and cancel out
+        assert!((align_spin(0., [1., -1.], 1., 0.1)).abs() < 1e-6);
+        assert_eq!(align_spin(0.5, [], 1., 0.1), 0.5);
+    }
+
+    #[test]
+    fn alignment_and_cohesion_ignore_unweighted_neighbours() {
+        let velocity = Vec2::new(0., 10.);
+        assert_eq!(alignment(velocity, [(Vec2::X, 0.)], 10., 100.), Vec2::ZERO);
+        assert_eq!(cohesion(Vec2::ZERO, velocity, [(Vec2::X, 0.)], 10., 100.), Vec2::ZERO);
+        // a heading matched already needs no steering
+        assert_eq!(alignment(velocity, [(Vec2::new(0., 3.), 1.), (Vec2::X, 0.)], 10., 100.), Vec2::ZERO);
+    }
+}
@@ -1,213 +1,414 @@
 use std::{
-    ops::{AddAssign, Div, Mul, MulAssign, Sub},
-    f32::consts::{PI, TAU}
+    collections::HashSet,
+    ops::{AddAssign, Mul, Sub},
+    f32::consts::TAU,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
 use bevy::{
     app::{App, Plugin},
+    log::error,
     prelude::{
         Component,
         Vec2,
         Commands,
         Bundle,
         Query,
+        Added,
         With,
+        Without,
         Resource,
         Entity,
         Res,
         ResMut,
         Startup,
+        PreUpdate,
         Update,
+        FixedUpdate,
+        Fixed,
+        Last,
         IntoSystemConfigs,
-        Transform,
-        Vec3,
-        Color,
-        Mesh,
-        Assets,
-        Handle,
-        Triangle2d,
-        Camera2dBundle,
-        Window,
-        Quat,
-        Time
+        Local,
+        not,
+        resource_changed,
+        resource_exists,
+        Time,
+        Timer,
+        TimerMode,
     },
-    sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle},
+    ecs::{archetype::Archetypes, component::Components, system::SystemParam},
+    utils::Parallel,
 };
-use rand::prelude::{StdRng};
+#[cfg(feature = "render")]
+use bevy::{prelude::PostUpdate, transform::TransformSystem};
+use boids_core::steering;
 use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::Deserialize;
+
+use crate::strict::soft_assert;
+use crate::tools::ToolsPlugin;
+
+use attractor::CursorAttractor;
+use behaviors::SteeringBehaviors;
+use clusters::ClusterHistogram;
+use config::{Config, ConfigFile};
+use corridors::Corridors;
+use density::Crowding;
+use despawning::Despawning;
+use disturbance::{Panic, ScheduledDisturbances};
+use flash::EventFlashes;
+use flocks::Flocks;
+use flow::FlowField;
+use foraging::{FoodDesc, FoodLayout, Forager};
+use grid::{GridQueryCount, SpatialGrid};
+use inspector::SteeringForces;
+use keyframes::KeyframeTracks;
+use integration::{DragForce, Integration, State};
+use obstacles::{Erosion, Obstacle, ObstacleDesc, ObstacleHit, ObstacleLayout, ObstaclesChanged};
+use orientation::{AngularVelocity, Heading};
+use predators::{InitialPredators, PredatorConfig};
+use prefabs::{PrefabSlot, PrefabTool};
+use roosting::{Dormant, RoostDesc, RoostLayout};
+use schedules::SpawnSchedules;
+use settings::SettingsProblems;
+use signals::{Inbox, Signal, SignalKind};
+use snapshot::SnapshotFile;
+use spawning::{SpawnFade, Spawning};
+use species::SpeciesFile;
+use springs::SpringCohesion;
+use tags::{TagRegion, TagRegions, TagTool};
+use teaching::TeachingMode;
+use temperature::TemperatureField;
+use timestep::{PreviousPosition, TimeScale};
+use wander::WanderAngle;
+use whiskers::ShowWhiskers;
+
+pub use behaviors::{Alignment, Cohesion, Neighbour, PredictiveSeparation, Separation, Steering, SteeringBehavior};
+pub use bounds::{BoidExited, BoundaryMode, Edge, WorldBounds};
+pub use corridors::Corridor;
+#[cfg(feature = "debug")]
+pub use debug::{DebugLayers, DebugOverlayPlugin};
+pub use density::BoidShading;
+pub use disturbance::Disturbance;
+#[cfg(feature = "editor")]
+pub use editor::SpeciesEditorPlugin;
+pub use emitter::EdgeEmitter;
+pub use flocks::{FlockDesc, FlockId};
+pub use forces::{ApplyForce, ForceFilter};
+#[cfg(feature = "render")]
+pub use gpu::GpuBoidsPlugin;
+pub use grid::GridStats;
+pub use integration::{Drag, Integrator};
+pub use keyframes::{KeyframeTrack, Parameter};
+#[cfg(feature = "editor")]
+pub use panel::SettingsPanelPlugin;
+pub use perception::PerceptionMetric;
+pub use predators::Predator;
+pub use prefabs::{Prefab, PrefabBoid, StampPrefab};
+#[cfg(feature = "render")]
+pub use render::{BoidAppearance, BoidMaterial, BoidMesh, DrawOrder};
+pub use schedules::{SpawnSchedule, Spawnee};
+pub use settings::{BoidsSettings, SettingsProblem};
+pub use snapshot::{BoidsSnapshot, RngState, SnapshotBoid, SnapshotPredator};
+pub use spins::SpinLattice;
+pub use species::{BodyRadius, ForceMixing, Mass, Species, SpeciesDesc, SpeciesTable};
+pub use stats::FlockStats;
+pub use squads::{Squad, Squads};
+pub use tags::{TagStats, Tags};
+pub use timestep::BoidsSimulation;
+#[cfg(feature = "render")]
+pub use trails::TrailPlugin;
+pub use wander::Wander;
+pub use whiskers::Whiskers;
+
+mod attractor;
+mod behaviors;
+mod bounds;
+#[cfg(feature = "render")]
+mod camera;
+mod clusters;
+mod config;
+#[cfg(feature = "render")]
+mod continuum;
+mod corridors;
+#[cfg(feature = "debug")]
+mod debug;
+mod density;
+mod despawning;
+mod disturbance;
+#[cfg(feature = "editor")]
+mod editor;
+mod emitter;
+mod field;
+mod flash;
+mod flocks;
+mod flow;
+mod foraging;
+mod forces;
+#[cfg(feature = "render")]
+mod gpu;
+mod grid;
+mod inspector;
+mod integration;
+mod keyframes;
+#[cfg(feature = "render")]
+mod labels;
+#[cfg(feature = "render")]
+mod lod;
+mod measure;
+mod obstacles;
+mod orientation;
+#[cfg(feature = "render")]
+mod palette;
+#[cfg(feature = "editor")]
+mod panel;
+mod perception;
+mod predators;
+mod prefabs;
+#[cfg(feature = "render")]
+mod render;
+mod roosting;
+mod settings;
+#[cfg(feature = "render")]
+mod rose;
+mod schedules;
+mod signals;
+mod snapshot;
+mod spawning;
+mod species;
+mod spins;
+mod springs;
+mod squads;
+mod stats;
+mod tags;
+mod teaching;
+mod temperature;
+mod timestep;
+#[cfg(feature = "render")]
+mod trails;
+mod wander;
+mod whiskers;
 
 const DEFAULT_MAX_BOID_COUNT: u32 = 600;
+// simulation ticks per second
+const DEFAULT_TICK_RATE: f64 = 60.;
 
 const R: f32 = 5.;
 
-const MAX_FORCE: f32 = 5.0;
+// spawned boids keep this far from the world edges and obstacles
+const SPAWN_MARGIN: f32 = 4. * R;
+// positions tried before falling back to the requested one
+const SPAWN_ATTEMPTS: u32 = 8;
+// largest angle between an edge spawned boid's heading and the edge normal
+const EDGE_SPAWN_SPREAD: f32 = TAU / 12.;
+// seconds a removed boid takes to shrink away
+const DESPAWN_SECONDS: f32 = 0.3;
+
+// steering acceleration in units/s², 5 units/frame at 60 fps
+const MAX_FORCE: f32 = 300.0;
 const MAX_SPEED: f32 = 300.0;
+// predators are a little faster than boids but turn more slowly
+const PREDATOR_MAX_FORCE: f32 = 200.0;
+const PREDATOR_MAX_SPEED: f32 = 330.0;
+
+// where the species editor saves to without `--species`
+const SPECIES_FILE: &str = "assets/species.txt";
+// where the prefab tool saves to without `--prefab`
+const PREFAB_FILE: &str = "assets/prefab.txt";
+// where F9 saves a snapshot to without `--snapshot`
+const SNAPSHOT_FILE: &str = "assets/snapshot.ron";
+
+// keeps force / mass finite for misconfigured species
+const MIN_MASS: f32 = 0.01;
 
 const DESIRED_SEPARATION: f32 = 50.;
 const NEIGHBOUR_RADIUS: f32 = 100.;
+// degrees, boids don't see the neighbours right behind them
+const FIELD_OF_VIEW: f32 = 270.;
+// clearance from an obstacle's surface at which boids start steering away
+const AVOID_DISTANCE: f32 = 40.;
+// boids brake for others closer than this inside their forward cone
+const QUEUE_DISTANCE: f32 = 25.;
+const QUEUE_CONE_COS: f32 = 0.85;
+// boids flee predators closer than this
+const EVASION_RADIUS: f32 = 150.;
+// boids avoiding the edges steer away from them once closer than this
+const EDGE_MARGIN: f32 = 100.;
+// reach of the attract tool
+const CURSOR_RADIUS: f32 = 250.;
 
 const SEPARATION_MULTIPLIER: f32 = 1.2;
 const ALIGN_MULTIPLIER: f32 = 1.0;
 const COHESION_MULTIPLIER: f32 = 1.0;
+const AVOID_MULTIPLIER: f32 = 2.0;
+const EDGE_AVOIDANCE_MULTIPLIER: f32 = 1.5;
+const EVASION_MULTIPLIER: f32 = 2.5;
+const CURSOR_STRENGTH: f32 = 1.5;
+const QUEUE_MULTIPLIER: f32 = 1.5;
+const ALARM_MULTIPLIER: f32 = 1.5;
+const RECRUIT_MULTIPLIER: f32 = 0.8;
+const PANIC_MULTIPLIER: f32 = 1.0;
+
+/// World position of a boid or obstacle.
+#[derive(Component)]
+pub struct Position(pub Vec2);
 
 #[derive(Component)]
-struct Position(Vec2);
+pub struct Velocity(pub Vec2);
 
+/// Steering force accumulated over a frame, applied and reset when the boid
+/// moves. Systems running before the move can add their own forces to it.
 #[derive(Component)]
-struct Velocity(Vec2);
+pub struct Acceleration(pub Vec2);
 
+/// Multiplier on a boid's max speed set by the environment it is in.
 #[derive(Component)]
-struct Acceleration(Vec2);
+struct SpeedScale(f32);
 
+impl Default for SpeedScale {
+    fn default() -> Self {
+        SpeedScale(1.)
+    }
+}
+
+/// A boid and its steering limits, kept in sync with [`BoidsSettings`] and
+/// its species.
 #[derive(Component)]
-struct Boid {
+pub struct Boid {
     max_force: f32,
     max_speed: f32,
 }
 
-impl Default for Boid {
-    fn default() -> Self {
+impl Boid {
+    fn new(settings: &BoidsSettings, species: &SpeciesDesc) -> Self {
         Boid {
-            max_force: MAX_FORCE,
-            max_speed: MAX_SPEED,
+            max_force: settings.max_force,
+            max_speed: settings.max_speed * species.speed,
         }
     }
 }
 
 impl Boid {
     fn seek(&self, target: Vec2, position: &Position, velocity: &Velocity) -> Vec2 {
-        target
-            .sub(position.0)
-            .normalize()
-            .mul(self.max_speed)
-            .sub(velocity.0)
-            .clamp_length_max(self.max_force)
+        steering::seek(position.0, velocity.0, target, self.max_speed, self.max_force)
     }
 
-    fn separate(
-        &self,
-        position: &Position,
-        velocity: &Velocity,
-        boids: &Res<Boids>,
-        positions: &Query<&Position>
-    ) -> Vec3 {
-        let mut steer = Vec3::ZERO;
-        let mut count = 0;
-        for &boid in &boids.0 {
-            if let Ok(pos) = positions.get(boid) {
-                let dist = position.0.distance(pos.0);
-                if dist > 0f32 && dist < DESIRED_SEPARATION {
-                    let diff = position.0
-                        .sub(pos.0)
-                        .normalize()
-                        .div(dist);
-                    steer.add_assign(Vec3::from((diff, 0.)));
-                    count += 1;
-                }
-            }
-        }
-        if count > 0 {
-            steer = steer.div(count as f32);
-        }
-        if steer.length() > 0f32 {
-            steer = steer
-                .normalize()
-                .mul(self.max_speed)
-                .sub(Vec3::from((velocity.0, 0.)));
-            steer = steer.clamp_length_max(self.max_force);
-        }
-        steer
+    fn flee(&self, threat: Vec2, position: &Position, velocity: &Velocity) -> Vec2 {
+        steering::flee(position.0, velocity.0, threat, self.max_speed, self.max_force)
     }
 
-    fn align(
+    fn queue(
         &self,
         position: &Position,
         velocity: &Velocity,
-        boids: &Res<Boids>,
-        positions: &Query<&Position>,
-        velocities: &Query<&Velocity>
+        grid: &SpatialGrid
     ) -> Vec2 {
-        let mut sum = Vec2::ZERO;
-        let mut count = 0;
-        for &boid in &boids.0 {
-            if let (
-                Ok(pos),
-                Ok(vel)
-            ) = (
-                positions.get(boid),
-                velocities.get(boid)
-            ) {
-                let dist = position.0.distance(pos.0);
-                if dist > 0f32 && dist < NEIGHBOUR_RADIUS {
-                    sum.add_assign(vel.0);
-                    count += 1;
-                }
+        let heading = velocity.0.normalize_or_zero();
+        let mut closest = QUEUE_DISTANCE;
+        for (_, pos) in grid.neighbours(position.0, QUEUE_DISTANCE) {
+            let offset = pos.sub(position.0);
+            let dist = offset.length();
+            // only boids directly ahead count
+            if dist > 0f32 && dist < closest && offset.dot(heading) > dist * QUEUE_CONE_COS {
+                closest = dist;
             }
         }
-        if count > 0 {
-            sum.div(count as f32)
-                .normalize()
-                .mul(self.max_speed)
-                .sub(velocity.0)
-                .clamp_length_max(self.max_force)
+        if closest < QUEUE_DISTANCE {
+            // brake harder the closer the boid ahead is
+            heading.mul(-self.max_force * (1. - closest / QUEUE_DISTANCE))
         } else {
-            Vec2::new(0., 0.)
+            Vec2::ZERO
         }
     }
 
-    fn cohesion(
+    fn avoid(
         &self,
         position: &Position,
         velocity: &Velocity,
-        boids: &Res<Boids>,
-        positions: &Query<&Position>
+        obstacles: &Query<(&Position, &Obstacle), Without<Boid>>
     ) -> Vec2 {
-        let mut sum = Vec2::ZERO;
-        let mut count = 0;
-        for &boid in &boids.0 {
-            if let Ok(pos) = positions.get(boid) {
-                let dist = position.0.distance(pos.0);
-                if dist > 0f32 && dist < NEIGHBOUR_RADIUS {
-                    sum.add_assign(pos.0);
-                    count += 1;
-                }
+        let mut steer = Vec2::ZERO;
+        for (center, obstacle) in obstacles.iter() {
+            let offset = position.0.sub(center.0);
+            let clearance = offset.length() - obstacle.radius;
+            if clearance < AVOID_DISTANCE {
+                // closer obstacles push harder
+                let weight = 1. - clearance.max(0.) / AVOID_DISTANCE;
+                steer.add_assign(offset.normalize_or_zero().mul(weight));
             }
         }
-        if count > 0 {
-            self.seek(sum.div(count as f32), position, velocity)
+        if steer.length() > 0f32 {
+            steer
+                .normalize()
+                .mul(self.max_speed)
+                .sub(velocity.0)
+                .clamp_length_max(self.max_force)
         } else {
-            Vec2::new(0., 0.)
+            steer
         }
     }
 }
 
+/// Components of a boid. Boids spawned from outside the plugin join the
+/// flock on the next frame as the first species.
 #[derive(Bundle)]
-pub struct BoidBundle<T: Bundle> {
+pub struct BoidBundle {
     marker: Boid,
     position: Position,
     velocity: Velocity,
     acceleration: Acceleration,
-    mesh: T,
+    speed_scale: SpeedScale,
+    heading: Heading,
+    angular_velocity: AngularVelocity,
+    species: Species,
+    mass: Mass,
+    radius: BodyRadius,
+    crowding: Crowding,
+    wander: WanderAngle,
+    inbox: Inbox,
+    forager: Forager,
+    previous_position: PreviousPosition,
 }
 
-// list of spawned boids that is updated runtime
-#[derive(Resource)]
-struct Boids(Vec<Entity>);
-
-impl Default for Boids {
-    fn default() -> Self {
-        Boids(Vec::new())
+impl BoidBundle {
+    pub fn new(position: Vec2, velocity: Vec2, settings: &BoidsSettings) -> Self {
+        BoidBundle {
+            marker: Boid::new(settings, &SpeciesDesc::default()),
+            position: Position(position),
+            velocity: Velocity(velocity),
+            acceleration: Acceleration(Vec2::ZERO),
+            speed_scale: SpeedScale::default(),
+            heading: Heading(velocity.y.atan2(velocity.x)),
+            angular_velocity: AngularVelocity::default(),
+            species: Species(0),
+            mass: Mass::default(),
+            radius: BodyRadius(R),
+            crowding: Crowding::default(),
+            wander: WanderAngle::default(),
+            inbox: Inbox::default(),
+            forager: Forager::default(),
+            previous_position: PreviousPosition(position),
+        }
     }
 }
 
+// list of spawned boids that is updated runtime
+#[derive(Resource, Default)]
+struct Boids(Vec<Entity>);
+
 #[derive(Resource)]
 struct RandomGenerator {
-    rng: StdRng,
+    // the generator behind `StdRng`, named so snapshots can save its state
+    rng: ChaCha12Rng,
 }
 
 impl RandomGenerator {
-    fn new(seed: [u8; 32]) -> Self {
+    fn new(seed: u64) -> Self {
         RandomGenerator {
-            rng: StdRng::from_seed(seed),
+            rng: ChaCha12Rng::seed_from_u64(seed),
         }
     }
 
@@ -217,165 +418,1349 @@ impl RandomGenerator {
 }
 
 #[derive(Resource)]
-struct BoidMesh(Mesh2dHandle);
+struct MaxBoidCount(u32);
 
-#[derive(Resource)]
-struct BoidMaterial(Handle<ColorMaterial>);
+/// Approximate memory used by boids, for display in the HUD, and how well
+/// the boid list the neighbour lookups are built from matches the world.
+#[derive(Resource, Default)]
+pub struct BoidMemoryUsage {
+    pub boids: usize,
+    /// Component storage of every archetype containing boids
+    pub component_bytes: usize,
+    /// Neighbour lookup structures
+    pub index_bytes: usize,
+    /// Boids in the flock missing from the boid list. Boids spawned by
+    /// other plugins are only listed on the next tick.
+    pub unindexed: usize,
+    /// Entries in the boid list that are not a boid in the flock, or list
+    /// one twice
+    pub stale_entries: usize,
+    /// Entries in the boid list
+    pub listed: usize,
+    /// Perched boids, taken out of the boid list while they sleep
+    pub perched: usize,
+    /// Boids the spawner counts as still in the flock, which should be the
+    /// listed and perched boids less any spawned by other plugins
+    pub counted: usize,
+}
 
+/// Whether boids slow down behind others instead of piling into them.
 #[derive(Resource)]
-struct MaxBoidCount(u32);
+struct Queueing(bool);
 
-#[derive(Resource)]
-struct BoidCount(u32);
+/// Where boids spawned while the flock fills up appear.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum SpawnArea {
+    /// At the origin, flying in a random direction
+    #[default]
+    Centre,
+    /// Just outside a random edge of the world, flying inwards
+    Edges,
+    /// At a random point in the world, flying in a random direction
+    Anywhere,
+}
 
-impl Default for BoidCount {
+/// How fast the flock fills up to its max size.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum SpawnStrategy {
+    /// The whole flock on the first frame
+    AllAtOnce,
+    /// This many boids every frame
+    PerFrame(u32),
+    /// This many boids per second, independent of the frame rate
+    PerSecond(f32),
+}
+
+impl Default for SpawnStrategy {
     fn default() -> Self {
-        BoidCount(0)
+        SpawnStrategy::PerFrame(1)
+    }
+}
+
+/// Boids spawned at startup as `(position, velocity)` pairs.
+#[derive(Resource, Default)]
+struct InitialBoids(Vec<(Vec2, Vec2)>);
+
+/// Boids spawned by [`BoidSpawner`] that are still in the flock, which
+/// the spawner fills up to the [`MaxBoidCount`].
+#[derive(Resource, Default)]
+struct BoidCount(u32);
+
+/// Spawns boids and keeps the boid list and count in sync.
+#[derive(SystemParam)]
+struct BoidSpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    boids: ResMut<'w, Boids>,
+    boid_count: ResMut<'w, BoidCount>,
+    squads: Res<'w, Squads>,
+    flocks: Res<'w, Flocks>,
+    species: Res<'w, SpeciesTable>,
+    bounds: Res<'w, WorldBounds>,
+    mode: Res<'w, BoundaryMode>,
+    obstacles: Query<'w, 's, (&'static Position, &'static Obstacle), Without<Boid>>,
+    rng: ResMut<'w, RandomGenerator>,
+    fade: Res<'w, SpawnFade>,
+    settings: Res<'w, BoidsSettings>,
+}
+
+impl BoidSpawner<'_, '_> {
+    /// Obstacle whose margin `position` lies in, as its centre and radius.
+    fn blocking_obstacle(&self, position: Vec2) -> Option<(Vec2, f32)> {
+        self.obstacles
+            .iter()
+            .find(|(pos, obstacle)| pos.0.distance(position) < obstacle.radius + SPAWN_MARGIN)
+            .map(|(pos, obstacle)| (pos.0, obstacle.radius))
+    }
+
+    /// Nearest free spot to `desired`, kept `margin` inside the world bounds.
+    /// A spot inside an obstacle is retried at a random point just outside
+    /// it, up to [`SPAWN_ATTEMPTS`] times before settling for `desired`
+    /// fitted into the bounds and leaving the boid to steer out.
+    fn place(&mut self, desired: Vec2, margin: f32) -> Vec2 {
+        let fallback = self.mode.fit(&self.bounds, desired, margin);
+        let mut candidate = fallback;
+        for _ in 0..SPAWN_ATTEMPTS {
+            let Some((centre, radius)) = self.blocking_obstacle(candidate) else {
+                return candidate;
+            };
+            let direction = Vec2::from_angle(self.rng.random_f32(0.0..TAU));
+            let outside = centre + direction * (radius + SPAWN_MARGIN * 1.5);
+            candidate = self.mode.fit(&self.bounds, outside, margin);
+        }
+        fallback
+    }
+
+    /// Random spot in `area` for something of `radius`, the direction it
+    /// flies off in and how far inside the world bounds to keep it.
+    fn spot_in(&mut self, area: SpawnArea, radius: f32) -> (Vec2, Vec2, f32) {
+        match area {
+            SpawnArea::Centre => (Vec2::ZERO, Vec2::from_angle(self.rng.random_f32(0.0..TAU)), SPAWN_MARGIN),
+            SpawnArea::Edges => {
+                let edges = [Edge::Left, Edge::Right, Edge::Bottom, Edge::Top];
+                let edge = edges[(self.rng.random_f32(0.0..4.0) as usize).min(3)];
+                let t = self.rng.random_f32(-1.0..1.0);
+                let spread = self.rng.random_f32(-EDGE_SPAWN_SPREAD..EDGE_SPAWN_SPREAD);
+                // a negative margin lets the boid start off screen, right at the edge
+                (edge.point(&self.bounds, t, radius), Vec2::from_angle(spread).rotate(edge.inward()), -radius)
+            }
+            SpawnArea::Anywhere => {
+                let position = Vec2::new(
+                    self.rng.random_f32(-1.0..1.0) * self.bounds.half_extents.x,
+                    self.rng.random_f32(-1.0..1.0) * self.bounds.half_extents.y,
+                );
+                (position, Vec2::from_angle(self.rng.random_f32(0.0..TAU)), SPAWN_MARGIN)
+            }
+        }
+    }
+
+    /// Spawns a boid in `area` at half its max speed, of `species` or of
+    /// the next species in turn.
+    fn spawn_in(&mut self, area: SpawnArea, species: Option<Species>) -> Entity {
+        let species = self.species_or_next(species);
+        let (position, heading, margin) = self.spot_in(area, self.body_radius(species));
+        let position = self.place(position, margin);
+        let velocity = heading * self.settings.max_speed / 2.;
+        self.insert_species(position, velocity, species)
+    }
+
+    fn spawn(&mut self, position: Vec2, velocity: Vec2) -> Entity {
+        let position = self.place(position, SPAWN_MARGIN);
+        self.insert(position, velocity)
+    }
+
+    /// Spawns a boid just outside `edge`, `t` in `[-1, 1]` running along it,
+    /// of `species` or of the next species in turn.
+    fn spawn_at_edge(&mut self, edge: Edge, t: f32, velocity: Vec2, species: Option<Species>) -> Entity {
+        let species = self.species_or_next(species);
+        let radius = self.body_radius(species);
+        let position = edge.point(&self.bounds, t, radius);
+        // a negative margin lets the boid start off screen, right at the edge
+        let position = self.place(position, -radius);
+        self.insert_species(position, velocity, species)
+    }
+
+    /// Spawns a boid of `species`, wrapped around to one in the table if
+    /// it has fewer species than that.
+    fn spawn_species(&mut self, position: Vec2, velocity: Vec2, species: Species) -> Entity {
+        let position = self.place(position, SPAWN_MARGIN);
+        let species = self.species.assign(species.0);
+        self.insert_species(position, velocity, species)
+    }
+
+    /// `species` wrapped around to one in the table, or the next species
+    /// in turn.
+    fn species_or_next(&self, species: Option<Species>) -> Species {
+        match (species, self.flocks.assign(self.boid_count.0)) {
+            (Some(species), _) => self.species.assign(species.0),
+            (None, Some(flock)) => self.species.assign(flock.0),
+            (None, None) => self.species.assign(self.boid_count.0),
+        }
+    }
+
+    fn body_radius(&self, species: Species) -> f32 {
+        R * self.species.get(species).size
+    }
+
+    fn insert(&mut self, position: Vec2, velocity: Vec2) -> Entity {
+        let species = self.species_or_next(None);
+        self.insert_species(position, velocity, species)
+    }
+
+    fn insert_species(&mut self, position: Vec2, velocity: Vec2, species: Species) -> Entity {
+        let boid = BoidBundle {
+            marker: Boid::new(&self.settings, self.species.get(species)),
+            species,
+            mass: Mass(self.species.get(species).mass),
+            radius: BodyRadius(self.body_radius(species)),
+            ..BoidBundle::new(position, velocity, &self.settings)
+        };
+        let delay = if self.fade.stagger > 0. { self.rng.random_f32(0.0..self.fade.stagger) } else { 0. };
+        let mut entity = self.commands.spawn(boid);
+        if let Some(squad) = self.squads.assign(self.boid_count.0) {
+            entity.insert(squad);
+        }
+        if !self.flocks.0.is_empty() {
+            entity.insert(FlockId(species.0));
+        }
+        if self.fade.duration + self.fade.stagger > 0. {
+            entity.insert(Spawning::new(&self.fade, delay));
+        }
+        let boid_id = entity.id();
+        self.boids.0.push(boid_id);
+        self.boid_count.0 += 1;
+        boid_id
+    }
+
+    /// Takes the boid out of the flock right away and despawns it once it
+    /// has shrunk away.
+    fn despawn(&mut self, entity: Entity) {
+        let timer = Timer::new(Duration::from_secs_f32(DESPAWN_SECONDS), TimerMode::Once);
+        self.commands.entity(entity).try_insert(Despawning(timer));
+        let live = self.boids.0.len();
+        self.boids.0.retain(|&boid| boid != entity);
+        if self.boids.0.len() < live {
+            self.boid_count.0 = self.boid_count.0.saturating_sub(1);
+        }
     }
 }
 
 pub struct BoidsPlugin {
-    max_boid_count: u32
+    max_boid_count: u32,
+    seed: u64,
+    integrator: Integrator,
+    squad_count: u32,
+    obstacles: Vec<ObstacleDesc>,
+    temperature_seed: Option<u64>,
+    flow_field: Option<PathBuf>,
+    #[cfg_attr(not(feature = "render"), allow(dead_code))]
+    palette_image: Option<PathBuf>,
+    species_file: Option<PathBuf>,
+    prefab_file: Option<PathBuf>,
+    snapshot_file: Option<PathBuf>,
+    config_file: Option<PathBuf>,
+    initial_boids: Vec<(Vec2, Vec2)>,
+    spawn_area: SpawnArea,
+    spawn_strategy: SpawnStrategy,
+    spawn_fade: SpawnFade,
+    settings: BoidsSettings,
+    world_bounds: WorldBounds,
+    boundary_mode: BoundaryMode,
+    perception: PerceptionMetric,
+    emitter: Option<EdgeEmitter>,
+    queueing: bool,
+    corridors: Vec<Corridor>,
+    species: Vec<SpeciesDesc>,
+    flocks: Vec<u32>,
+    springs: Option<SpringCohesion>,
+    behaviors: Vec<(Arc<dyn SteeringBehavior>, f32)>,
+    predictive_separation: Option<PredictiveSeparation>,
+    food: Vec<FoodDesc>,
+    roosts: Vec<RoostDesc>,
+    disturbances: Vec<(f32, Disturbance)>,
+    teaching: bool,
+    event_flashes: bool,
+    grid_query_count: bool,
+    shading: BoidShading,
+    #[cfg(feature = "render")]
+    appearance: BoidAppearance,
+    keyframes: Vec<KeyframeTrack>,
+    drag: Option<Drag>,
+    wander: Option<Wander>,
+    whiskers: Option<Whiskers>,
+    spins: Option<SpinLattice>,
+    predators: Option<PredatorConfig>,
+    initial_predators: Vec<(Vec2, Vec2)>,
+    tag_regions: Vec<TagRegion>,
+    schedules: Vec<SpawnSchedule>,
+    tick_rate: f64,
+    time_scale: f32,
+}
+
+impl Default for BoidsPlugin {
+    fn default() -> Self {
+        BoidsPlugin::new(DEFAULT_MAX_BOID_COUNT)
+    }
 }
 
 impl BoidsPlugin {
-    pub(crate) fn new(max_boid_count: u32) -> Self {
+    /// A flock that grows to `max_boid_count` boids, with every optional
+    /// behaviour off.
+    pub fn new(max_boid_count: u32) -> Self {
         BoidsPlugin {
-            max_boid_count
+            max_boid_count,
+            seed: 0,
+            integrator: Integrator::default(),
+            squad_count: 0,
+            obstacles: Vec::new(),
+            temperature_seed: None,
+            flow_field: None,
+            palette_image: None,
+            species_file: None,
+            prefab_file: None,
+            snapshot_file: None,
+            config_file: None,
+            initial_boids: Vec::new(),
+            spawn_area: SpawnArea::default(),
+            spawn_strategy: SpawnStrategy::default(),
+            spawn_fade: SpawnFade::default(),
+            settings: BoidsSettings::default(),
+            world_bounds: WorldBounds::default(),
+            boundary_mode: BoundaryMode::default(),
+            perception: PerceptionMetric::default(),
+            emitter: None,
+            queueing: false,
+            corridors: Vec::new(),
+            species: Vec::new(),
+            flocks: Vec::new(),
+            springs: None,
+            behaviors: Vec::new(),
+            predictive_separation: None,
+            food: Vec::new(),
+            roosts: Vec::new(),
+            disturbances: Vec::new(),
+            teaching: false,
+            event_flashes: false,
+            grid_query_count: false,
+            shading: BoidShading::default(),
+            #[cfg(feature = "render")]
+            appearance: BoidAppearance::default(),
+            keyframes: Vec::new(),
+            drag: None,
+            wander: None,
+            whiskers: None,
+            spins: None,
+            predators: None,
+            initial_predators: Vec::new(),
+            tag_regions: Vec::new(),
+            schedules: Vec::new(),
+            tick_rate: DEFAULT_TICK_RATE,
+            time_scale: 1.,
         }
     }
 
-    pub(crate) fn default() -> Self {
-        BoidsPlugin {
-            max_boid_count: DEFAULT_MAX_BOID_COUNT,
+    /// A default flock set up from the config file at `path`, so it can be
+    /// tuned without rebuilding. Anything the file leaves out keeps its
+    /// default, and the defaults are used as they are if the file can't be
+    /// read. Saving the file while the simulation runs applies the changes
+    /// to it.
+    ///
+    /// The file is a RON struct, every field of which can be left out:
+    /// `max_boid_count`, `seed`, `tick_rate`, `time_scale`, `world_size`,
+    /// e.g. `(1600, 900)` for a world of that size instead of the window's,
+    /// `spawn_area`, a [`SpawnArea`], `spawn_strategy`, a [`SpawnStrategy`]
+    /// such as `PerSecond(50)`, `settings`, a map of [`BoidsSettings`] fields
+    /// by name, e.g. `{"cohesion": 1.5}`, `species`, a list of species in
+    /// the species file format, e.g. `["speed=1.2 color=0.9,0.4,0.2"]`, and
+    /// `boid_count`, a [`Parameter::BoidCount`] track of `(seconds, count)`
+    /// keys, e.g. `[(0, 100), (120, 5000)]` to ramp from 100 to 5000 boids
+    /// over two minutes. A tick rate that isn't positive or a negative time
+    /// scale fails the whole file.
+    pub fn from_config(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let mut plugin = BoidsPlugin::default();
+        match Config::load(path) {
+            Ok(config) => plugin.apply_config(&config),
+            Err(err) => error!("failed to load config from {}: {err}", path.display()),
+        }
+        plugin.config_file = Some(path.to_path_buf());
+        plugin
+    }
+
+    /// Runs the simulation at `hz` fixed ticks per second, independent of the
+    /// frame rate. Frames between ticks interpolate boid positions.
+    pub fn with_tick_rate(mut self, hz: f64) -> Self {
+        self.tick_rate = hz;
+        self
+    }
+
+    /// Runs the simulation `scale` times faster than real time.
+    pub fn with_time_scale(mut self, scale: f32) -> Self {
+        self.time_scale = scale;
+        self
+    }
+
+    /// Seeds every random choice the simulation makes, so runs with the same
+    /// seed and settings play out the same way.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn settings(&self) -> BoidsSettings {
+        self.settings
+    }
+
+    pub fn with_integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    /// Lets boids coast toward a cruise speed under drag instead of only
+    /// being clamped at their max speed.
+    pub fn with_drag(mut self, drag: Drag) -> Self {
+        self.drag = Some(drag);
+        self
+    }
+
+    /// Lets boids without neighbours meander, steered by `strength` times
+    /// a [`Wander`] force, instead of flying in a straight line. Wander
+    /// ranks below every other force for species that prioritize forces.
+    pub fn with_wander(mut self, strength: f32) -> Self {
+        self.wander = Some(Wander::new(strength));
+        self
+    }
+
+    /// Lets boids feel ahead with [`Whiskers`] for walls and obstacles,
+    /// steered along them by `strength` times the wall following force.
+    /// Whiskers rank below the flocking rules and above other behaviours
+    /// for species that prioritize forces.
+    pub fn with_whiskers(mut self, strength: f32) -> Self {
+        self.whiskers = Some(Whiskers::new(strength));
+        self
+    }
+
+    /// Freezes boids on a lattice filling the world the plugin starts with,
+    /// turning on the spot as a [`SpinLattice`] instead of flocking. Takes
+    /// the place of the boid count and any initial boids.
+    pub fn with_spin_lattice(mut self, spins: SpinLattice) -> Self {
+        self.spins = Some(spins);
+        self
+    }
+
+    /// Starting flocking parameters, which can be changed at runtime
+    /// through the [`BoidsSettings`] resource.
+    pub fn with_settings(mut self, settings: BoidsSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Splits the flock into `count` squads that can be given separate goals.
+    pub fn with_squads(mut self, count: u32) -> Self {
+        self.squad_count = count;
+        self
+    }
+
+    pub fn with_obstacle(mut self, position: Vec2, radius: f32) -> Self {
+        self.obstacles.push(ObstacleDesc { position, radius, health: None });
+        self
+    }
+
+    /// Adds an obstacle that erodes as boids collide with it.
+    pub fn with_soft_obstacle(mut self, position: Vec2, radius: f32, health: f32) -> Self {
+        self.obstacles.push(ObstacleDesc { position, radius, health: Some(health) });
+        self
+    }
+
+    /// Enables a temperature field generated from `seed` that scales boid
+    /// speed and heading noise.
+    pub fn with_temperature(mut self, seed: u64) -> Self {
+        self.temperature_seed = Some(seed);
+        self
+    }
+
+    /// Enables a flow field boids steer along, loaded from `path` if it
+    /// exists. It can be painted with the flow brush and saved back there.
+    pub fn with_flow_field(mut self, path: impl Into<PathBuf>) -> Self {
+        self.flow_field = Some(path.into());
+        self
+    }
+
+    /// Colours each boid from the picture at `path`, stretched over the
+    /// world, at the spot it spawns. Boids keep their colour for life, so
+    /// the flock carries the picture's palette around and scrambles it as
+    /// it mixes. Only drawn with the render feature.
+    pub fn with_palette_image(mut self, path: impl Into<PathBuf>) -> Self {
+        self.palette_image = Some(path.into());
+        self
+    }
+
+    /// Spawns the given `(position, velocity)` pairs at startup instead of
+    /// growing the flock one boid per frame from the origin.
+    pub fn with_initial_boids(mut self, boids: Vec<(Vec2, Vec2)>) -> Self {
+        self.max_boid_count = boids.len() as u32;
+        self.initial_boids = boids;
+        self
+    }
+
+    /// Fixes the world to `size` across, centred on the origin, instead of
+    /// the size of the window, so resizing the window no longer changes
+    /// where boids wrap or bounce.
+    pub fn with_world_size(mut self, size: Vec2) -> Self {
+        self.world_bounds = WorldBounds::fixed(size);
+        self
+    }
+
+    pub fn with_boundary_mode(mut self, mode: BoundaryMode) -> Self {
+        self.boundary_mode = mode;
+        self
+    }
+
+    /// Shape of the region boids flock with, a circle unless set, which
+    /// can be changed at runtime through the [`PerceptionMetric`] resource.
+    pub fn with_perception(mut self, metric: PerceptionMetric) -> Self {
+        self.perception = metric;
+        self
+    }
+
+    /// Where boids appear while the flock fills up to its max size.
+    pub fn with_spawn_area(mut self, area: SpawnArea) -> Self {
+        self.spawn_area = area;
+        self
+    }
+
+    /// How fast the flock fills up to its max size, one boid per frame
+    /// unless set.
+    pub fn with_spawn_strategy(mut self, strategy: SpawnStrategy) -> Self {
+        self.spawn_strategy = strategy;
+        self
+    }
+
+    /// New boids grow in over `duration` seconds after a random delay of up
+    /// to `stagger` seconds, and only push their neighbours around once grown.
+    pub fn with_spawn_fade(mut self, duration: f32, stagger: f32) -> Self {
+        self.spawn_fade = SpawnFade { duration, stagger };
+        self
+    }
+
+    /// Wind tunnel mode: emits `rate` boids per second from `edge` instead of
+    /// spawning at the origin, and despawns boids leaving the world.
+    pub fn with_edge_emitter(mut self, edge: Edge, rate: f32) -> Self {
+        self.emitter = Some(EdgeEmitter::new(edge, rate));
+        self.boundary_mode = BoundaryMode::Despawn { respawn: None };
+        self
+    }
+
+    /// Makes boids brake for boids directly ahead, so they funnel through
+    /// narrow gaps instead of piling up.
+    pub fn with_queueing(mut self, queueing: bool) -> Self {
+        self.queueing = queueing;
+        self
+    }
+
+    /// Adds a lane that biases the heading of boids inside it.
+    pub fn with_corridor(mut self, corridor: Corridor) -> Self {
+        self.corridors.push(corridor);
+        self
+    }
+
+    /// Loads the species table from `path` if it exists, replacing the
+    /// species added with [`Self::with_species`]. The species editor saves
+    /// its changes there.
+    pub fn with_species_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.species_file = Some(path.into());
+        self
+    }
+
+    /// Loads the prefab the prefab tool stamps from `path` if it exists.
+    /// Selecting boids with the tool saves them there as the new prefab.
+    pub fn with_prefab_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.prefab_file = Some(path.into());
+        self
+    }
+
+    /// File F9 saves a [`BoidsSnapshot`] of the flock to and F10 loads it
+    /// back from, `assets/snapshot.ron` by default.
+    pub fn with_snapshot_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot_file = Some(path.into());
+        self
+    }
+
+    /// Adds a species. Spawned boids cycle through the species in the order
+    /// they were added; without any, every boid uses the defaults.
+    pub fn with_species(mut self, species: SpeciesDesc) -> Self {
+        self.species.push(species);
+        self
+    }
+
+    /// Splits the boids into independent flocks, replacing the species
+    /// added with [`Self::with_species`] with one per flock. Boids only
+    /// align and cohere with their own flock but keep apart from all, and
+    /// the flock grows to the flocks' total size.
+    pub fn with_flocks(mut self, flocks: Vec<FlockDesc>) -> Self {
+        self.max_boid_count = flocks.iter().map(|flock| flock.count).sum();
+        self.flocks = flocks.iter().map(|flock| flock.count).collect();
+        self.species = flocks.into_iter().map(|flock| flock.species).collect();
+        self
+    }
+
+    /// Experimental: replaces cohesion with springs to the `neighbours`
+    /// nearest boids, relaxed at `rest_length`.
+    pub fn with_spring_cohesion(mut self, neighbours: usize, rest_length: f32) -> Self {
+        self.springs = Some(SpringCohesion::new(neighbours, rest_length));
+        self
+    }
+
+    /// Replaces separation with [`PredictiveSeparation`], keeping boids
+    /// apart by where they will pass closest within `horizon` seconds
+    /// rather than where they are, for fewer near misses in fast, dense
+    /// flocks.
+    pub fn with_predictive_separation(mut self, horizon: f32) -> Self {
+        self.predictive_separation = Some(PredictiveSeparation { horizon });
+        self
+    }
+
+    /// Steers every boid by `behavior` as well, its force multiplied by
+    /// `weight`. Behaviours rank below separation, alignment and cohesion,
+    /// in the order they were added, for species that prioritize forces.
+    pub fn with_steering_behavior(mut self, behavior: impl SteeringBehavior, weight: f32) -> Self {
+        self.behaviors.push((Arc::new(behavior), weight));
+        self
+    }
+
+    /// Adds a food source. Boids flying over it recruit their neighbours to it.
+    pub fn with_food_source(mut self, position: Vec2, radius: f32) -> Self {
+        self.food.push(FoodDesc { position, radius });
+        self
+    }
+
+    /// Adds a roost. Boids flying over it perch and sleep for a while, out
+    /// of the flocking simulation, until their timer runs out or an alarm
+    /// wakes them.
+    pub fn with_roost(mut self, position: Vec2, radius: f32) -> Self {
+        self.roosts.push(RoostDesc { position, radius });
+        self
+    }
+
+    /// Presentation mode: starts with separation, alignment and cohesion
+    /// off and switches them on one at a time with N, with a caption naming
+    /// the active rules.
+    pub fn with_teaching_mode(mut self, teaching: bool) -> Self {
+        self.teaching = teaching;
+        self
+    }
+
+    /// Lights boids up for a moment when they spawn, get caught, receive a
+    /// signal, or perch, wake up or panic, to make those events visible.
+    pub fn with_event_flashes(mut self, flashes: bool) -> Self {
+        self.event_flashes = flashes;
+        self
+    }
+
+    /// Counts the neighbour lookups flocking makes each frame into
+    /// [`GridStats::flocking_lookups`], off by default to keep the count out of the
+    /// steering loop.
+    pub fn with_grid_query_count(mut self, count: bool) -> Self {
+        self.grid_query_count = count;
+        self
+    }
+
+    /// Shades boids by how crowded they are, their heading or their speed
+    /// from the start, instead of in their own colour. U still cycles it.
+    pub fn with_shading(mut self, shading: BoidShading) -> Self {
+        self.shading = shading;
+        self
+    }
+
+    /// Draws boids with a custom mesh, a sprite, at another scale or in
+    /// another order instead of as the built-in triangles.
+    #[cfg(feature = "render")]
+    pub fn with_appearance(mut self, appearance: BoidAppearance) -> Self {
+        self.appearance = appearance;
+        self
+    }
+
+    /// Animates a rule weight or the size of the flock over time.
+    pub fn with_keyframes(mut self, track: KeyframeTrack) -> Self {
+        self.keyframes.push(track);
+        self
+    }
+
+    /// Adds `count` predators that chase the nearest boid. With
+    /// `catch_prey` the boids they reach are despawned.
+    pub fn with_predators(mut self, count: u32, catch_prey: bool) -> Self {
+        let boundary = self.predators.and_then(|config| config.boundary);
+        self.predators = Some(PredatorConfig { count, catch_prey, boundary });
+        self
+    }
+
+    /// Spawns predators at the given `(position, velocity)` pairs at
+    /// startup, on top of any spawned at random. With `catch_prey` the
+    /// boids they reach are despawned.
+    pub fn with_initial_predators(mut self, predators: Vec<(Vec2, Vec2)>, catch_prey: bool) -> Self {
+        let config = self.predators.get_or_insert(PredatorConfig { count: 0, catch_prey, boundary: None });
+        config.catch_prey = catch_prey;
+        self.initial_predators = predators;
+        self
+    }
+
+    /// Keeps predators inside the world by `mode` rather than the plugin's
+    /// [`BoundaryMode`], e.g. bouncing off the edges of a wrapping world.
+    /// Despawning modes bounce predators, which are never despawned.
+    pub fn with_predator_boundary_mode(mut self, mode: BoundaryMode) -> Self {
+        let config = self.predators.get_or_insert(PredatorConfig { count: 0, catch_prey: true, boundary: None });
+        config.boundary = Some(mode);
+        self
+    }
+
+    /// Spawns boids of a species, or predators, by `schedule`. Once given
+    /// any schedule the flock no longer fills up to its max boid count by
+    /// itself. Scheduled predators catch their prey unless set otherwise
+    /// with [`Self::with_predators`].
+    pub fn with_spawn_schedule(mut self, schedule: SpawnSchedule) -> Self {
+        if schedule.spawnee == Spawnee::Predators && self.predators.is_none() {
+            self.predators = Some(PredatorConfig { count: 0, catch_prey: true, boundary: None });
         }
+        self.schedules.push(schedule);
+        self
+    }
+
+    /// Tags boids spawned within `radius` of `position` with `tag`, to
+    /// follow them through [`TagStats`].
+    pub fn with_tag_region(mut self, tag: &str, position: Vec2, radius: f32) -> Self {
+        self.tag_regions.push(TagRegion { tag: tag.to_string(), position, radius });
+        self
+    }
+
+    /// Fires `disturbance` once `seconds` after startup.
+    pub fn with_disturbance_at(mut self, seconds: f32, disturbance: Disturbance) -> Self {
+        self.disturbances.push((seconds, disturbance));
+        self
+    }
+
+    fn species_table(&self) -> SpeciesTable {
+        if let Some(path) = &self.species_file {
+            match SpeciesTable::load(path) {
+                Ok(table) => return table,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => error!("failed to load species from {}: {err}", path.display()),
+            }
+        }
+        SpeciesTable(if self.species.is_empty() {
+            vec![SpeciesDesc::default()]
+        } else {
+            self.species.clone()
+        })
     }
 }
 
 impl Plugin for BoidsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Boids>()
+        if !app.is_plugin_added::<ToolsPlugin>() {
+            app.add_plugins(ToolsPlugin);
+        }
+        if let Some(seed) = self.temperature_seed {
+            app.insert_resource(TemperatureField::from_noise(seed));
+        }
+        if let Some(config) = self.predators {
+            app.insert_resource(config)
+                .insert_resource(InitialPredators(self.initial_predators.clone()))
+                .add_systems(Startup, predators::spawn_predators)
+                .add_systems(FixedUpdate, (predators::hunt, predators::catch_prey)
+                    .chain()
+                    .after(update_boid)
+                    .before(bounds::apply_boundaries));
+        }
+        if let Some(path) = &self.flow_field {
+            app.insert_resource(FlowField::load_or_empty(path.clone()));
+        }
+        #[cfg(feature = "render")]
+        if let Some(path) = &self.palette_image {
+            match palette::PaletteImage::load(path) {
+                Ok(image) => {
+                    app.insert_resource(image);
+                }
+                Err(err) => error!("failed to load palette image {}: {err}", path.display()),
+            }
+        }
+        if let Some(emitter) = self.emitter {
+            app.insert_resource(emitter);
+        }
+        if !self.schedules.is_empty() {
+            app.insert_resource(SpawnSchedules::new(&self.schedules))
+                .add_systems(Startup, schedules::spawn_scheduled_at_startup.after(spawn_initial_boids))
+                .add_systems(Update, schedules::run_spawn_schedules);
+        }
+        if self.grid_query_count {
+            app.init_resource::<GridQueryCount>();
+        }
+        if self.event_flashes {
+            app.insert_resource(EventFlashes)
+                .add_systems(FixedUpdate, flash::flash_on_events.after(orientation::update_heading))
+                .add_systems(Update, flash::fade_flashes);
+            #[cfg(feature = "render")]
+            app.init_resource::<flash::FlashMaterial>()
+                .add_systems(PostUpdate, flash::show_flashes
+                    .after(density::shade_boids)
+                    .before(lod::update_lod));
+        }
+        let mut behaviors = SteeringBehaviors::default();
+        if let Some(springs) = self.springs {
+            app.insert_resource(springs);
+            behaviors.cohesion = Arc::new(springs);
+        }
+        if let Some(separation) = self.predictive_separation {
+            behaviors.separation = Arc::new(separation);
+        }
+        if let Some(whiskers) = self.whiskers {
+            // feeling for walls outranks behaviours registered by hand
+            behaviors.custom.push((Arc::new(whiskers), 1.));
+            app.insert_resource(whiskers)
+                .init_resource::<ShowWhiskers>()
+                .add_systems(Update, whiskers::whisker_input);
+            #[cfg(feature = "render")]
+            app.add_systems(Update, whiskers::draw_whiskers);
+        }
+        behaviors.custom.extend(self.behaviors.iter().cloned());
+        if let Some(wander) = self.wander {
+            // solitary meandering gives way to everything else
+            behaviors.custom.push((Arc::new(wander), 1.));
+            app.insert_resource(wander)
+                .add_systems(FixedUpdate, wander::drift_wander.before(flock));
+        }
+        app.insert_resource(behaviors);
+        if let Some(path) = &self.config_file {
+            app.insert_resource(ConfigFile::new(path.clone()))
+                .add_systems(Update, config::reload_config);
+        }
+        if let Some(drag) = self.drag {
+            app.insert_resource(DragForce(drag));
+        }
+        let mut initial_boids = None;
+        if let Some(spins) = self.spins {
+            app.insert_resource(spins);
+            initial_boids = Some(spins.lattice(self.world_bounds.half_extents));
+        }
+        let mut settings = self.settings;
+        if self.teaching {
+            let teaching = TeachingMode::new(&settings);
+            teaching.apply(&mut settings);
+            app.insert_resource(teaching)
+                .add_systems(Update, teaching::teaching_input);
+            #[cfg(feature = "render")]
+            app.add_systems(Startup, teaching::setup_caption)
+                .add_systems(Update, teaching::update_caption.after(teaching::teaching_input));
+        }
+        if !self.keyframes.is_empty() {
+            app.insert_resource(KeyframeTracks(self.keyframes.clone()))
+                .add_systems(FixedUpdate, keyframes::animate_parameters.before(flock))
+                .add_systems(Update, keyframes::follow_boid_count);
+        }
+        app.insert_resource(settings)
+            .init_resource::<SettingsProblems>()
+            .add_event::<SettingsProblem>()
+            .add_systems(FixedUpdate, (
+                settings::validate_settings.run_if(resource_changed::<BoidsSettings>),
+                settings::apply_settings,
+            ).chain().before(flock))
+            .insert_resource(Time::<Fixed>::from_hz(self.tick_rate))
+            .insert_resource(TimeScale(self.time_scale))
+            .add_systems(Startup, (timestep::apply_time_scale, timestep::limit_catch_up))
+            .add_systems(Update, timestep::time_input)
+            .insert_resource(RandomGenerator::new(self.seed))
+            .init_resource::<Boids>()
+            .init_resource::<SpatialGrid>()
             .init_resource::<BoidCount>()
-            .insert_resource(MaxBoidCount(self.max_boid_count))
-            .add_systems(Startup, (setup).chain())
-            .add_systems(Update, spawn)
-            .add_systems(Update, (flock, update_boid).chain());
-    }
-}
+            .insert_resource(MaxBoidCount(initial_boids.as_ref().map_or(self.max_boid_count, |boids| boids.len() as u32)))
+            .insert_resource(InitialBoids(initial_boids.unwrap_or_else(|| self.initial_boids.clone())))
+            .insert_resource(Integration(self.integrator))
+            .insert_resource(Squads::new(self.squad_count))
+            .insert_resource(Flocks(self.flocks.clone()))
+            .insert_resource(ObstacleLayout(self.obstacles.clone()))
+            .insert_resource(FoodLayout(self.food.clone()))
+            .insert_resource(RoostLayout(self.roosts.clone()))
+            .insert_resource(ScheduledDisturbances(self.disturbances.clone()))
+            .init_resource::<Erosion>()
+            .init_resource::<BoidMemoryUsage>()
+            .init_resource::<GridStats>()
+            .init_resource::<FlockStats>()
+            .init_resource::<TagStats>()
+            .init_resource::<ClusterHistogram>()
+            .insert_resource(self.world_bounds)
+            .insert_resource(self.boundary_mode)
+            .insert_resource(self.perception)
+            .insert_resource(self.spawn_area)
+            .insert_resource(self.spawn_strategy)
+            .insert_resource(self.spawn_fade)
+            .insert_resource(Queueing(self.queueing))
+            .insert_resource(Corridors(self.corridors.clone()))
+            .insert_resource(self.species_table())
+            .insert_resource(SpeciesFile(self.species_file.clone().unwrap_or_else(|| SPECIES_FILE.into())))
+            .add_event::<BoidExited>()
+            .add_event::<ObstacleHit>()
+            .add_event::<ObstaclesChanged>()
+            .add_event::<Signal>()
+            .add_event::<Disturbance>()
+            .add_event::<ApplyForce>()
+            .add_event::<StampPrefab>()
+            .init_resource::<forces::ActiveForces>()
+            .add_systems(FixedUpdate, forces::apply_forces.after(flock).before(update_boid))
+            .add_systems(Startup, (obstacles::spawn_obstacles, foraging::spawn_food, roosting::spawn_roosts, spawn_initial_boids).chain())
+            .add_systems(Update, (
+                spawn.run_if(not(resource_exists::<EdgeEmitter>)).run_if(not(resource_exists::<SpawnSchedules>)),
+                emitter::emit_boids.run_if(resource_exists::<EdgeEmitter>),
+            ))
+            .add_systems(PreUpdate, bounds::sync_bounds_to_window)
+            .add_systems(Update, bounds::boundary_input)
+            .insert_resource(self.shading)
+            .add_systems(Update, density::shading_input)
+            .add_systems(Last, (measure_memory, grid::measure_grid, stats::update_stats))
+            .add_systems(Update, (spawning::finish_spawning, despawning::finish_despawning))
+            .add_systems(Update, (spawning::population_input, spawning::spawn_brush))
+            .add_systems(Update, (squads::select_squad, squads::set_squad_goal).chain())
+            .add_systems(Update, (clusters::sample_clusters, clusters::cluster_chart_input).chain())
+            .add_systems(Update, (inspector::select_boids, inspector::record_history))
+            .add_systems(Update, disturbance::disturb_on_click)
+            .init_resource::<measure::Ruler>()
+            .init_resource::<CursorAttractor>()
+            .add_systems(Update, attractor::attract_input)
+            .add_systems(Update, measure::measure_input)
+            .insert_resource(PrefabSlot::load_or_empty(self.prefab_file.clone().unwrap_or_else(|| PREFAB_FILE.into())))
+            .init_resource::<PrefabTool>()
+            .insert_resource(SnapshotFile(self.snapshot_file.clone().unwrap_or_else(|| SNAPSHOT_FILE.into())))
+            .add_systems(Update, snapshot::snapshot_input)
+            .add_systems(Update, (prefabs::prefab_input, prefabs::stamp_prefabs).chain())
+            .insert_resource(TagRegions(self.tag_regions.clone()))
+            .init_resource::<TagTool>()
+            .add_systems(Update, tags::tag_input)
+            .add_systems(Update, temperature::paint_temperature.run_if(resource_exists::<TemperatureField>))
+            .add_systems(Update, flow::paint_flow.run_if(resource_exists::<FlowField>))
+            .add_systems(Update, springs::spring_input.run_if(resource_exists::<SpringCohesion>))
+            .add_systems(FixedUpdate, (
+                timestep::remember_positions,
+                adopt_boids,
+                tags::tag_spawned,
+                signals::deliver_signals,
+                disturbance::fire_scheduled_disturbances,
+                (disturbance::calm_down, roosting::wake, disturbance::panic_on_disturbance).chain(),
+                roosting::perch,
+                foraging::forage,
+                grid::rebuild_grid,
+                flock.run_if(not(resource_exists::<SpinLattice>)),
+                spins::turn_spins.run_if(resource_exists::<SpinLattice>),
+                temperature::apply_temperature.run_if(resource_exists::<TemperatureField>),
+                update_boid.run_if(not(resource_exists::<SpinLattice>)),
+                bounds::apply_boundaries,
+                obstacles::collide_with_obstacles,
+                (obstacles::erode_obstacles, signals::alarm_on_collision),
+                orientation::update_heading,
+            ).chain());
 
-fn setup(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    commands.spawn(Camera2dBundle::default());
-
-    let seed = [0u8;32];
-    commands.insert_resource(RandomGenerator::new(seed));
-    
-    commands.insert_resource(BoidMesh(Mesh2dHandle(meshes.add(Triangle2d::new(
-        Vec2::Y * 6.,
-        Vec2::new(-3., -3.),
-        Vec2::new(3., -3.)
-    )))));
-
-    commands.insert_resource(BoidMaterial(materials.add(Color::WHITE)));
+        #[cfg(feature = "render")]
+        app.insert_resource(self.appearance.clone())
+            .add_systems(Startup, (render::setup_render, lod::setup_lod, continuum::setup_continuum, measure::setup_ruler))
+            .add_systems(Startup, temperature::setup_heatmap.run_if(resource_exists::<TemperatureField>))
+            .add_systems(Update, (
+                squads::draw_squad_goals,
+                corridors::draw_corridors,
+                bounds::draw_hexagon,
+                bounds::draw_edge_margin,
+                foraging::draw_food,
+                roosting::draw_roosts,
+                clusters::draw_cluster_chart,
+                inspector::draw_history,
+                temperature::update_heatmap.run_if(resource_exists::<TemperatureField>),
+                springs::draw_springs.run_if(resource_exists::<SpringCohesion>),
+                flow::draw_flow.run_if(resource_exists::<FlowField>),
+                measure::draw_ruler,
+                prefabs::draw_prefab_tool,
+                tags::draw_tag_selection,
+            ))
+            .init_resource::<render::SpeciesMaterials>()
+            .init_resource::<render::BoidMaterial>()
+            .add_systems(Update, render::shape_input)
+            .init_resource::<density::ShadedMaterials>()
+            .init_resource::<density::HueMaterials>()
+            .init_resource::<rose::HeadingRose>()
+            .init_resource::<labels::BoidLabels>()
+            .add_systems(Update, (
+                labels::label_input,
+                labels::update_labels,
+            ).chain())
+            .add_systems(Update, (
+                rose::rose_input,
+                rose::sample_headings,
+                rose::draw_rose,
+            ).chain())
+            .add_systems(PostUpdate, (
+                render::sync_species_materials,
+                (render::attach_boid_meshes, render::attach_predator_meshes, render::attach_obstacle_meshes),
+                render::sync_boid_materials,
+                density::shade_boids,
+                lod::update_lod,
+                render::texture_boid_materials,
+                (render::sync_boid_transforms, render::sync_predator_transforms, render::sync_obstacle_transforms),
+                (spawning::grow_spawning, despawning::shrink_despawning),
+            ).chain().before(TransformSystem::TransformPropagate))
+            .add_systems(PostUpdate, continuum::update_continuum.after(lod::update_lod))
+            .init_resource::<inspector::SelectedMaterial>()
+            .init_resource::<inspector::Readouts>()
+            .add_systems(Update, inspector::update_readouts.after(inspector::record_history))
+            .add_systems(PostUpdate, inspector::highlight_selected
+                .after(density::shade_boids)
+                .after(flash::show_flashes)
+                .before(lod::update_lod))
+            .init_resource::<camera::FollowSelected>()
+            .add_systems(Update, camera::camera_input)
+            .add_systems(PostUpdate, camera::follow_selected
+                .after(render::sync_boid_transforms)
+                .before(TransformSystem::TransformPropagate));
+    }
 }
 
 fn spawn(
-    mut commands: Commands,
-    mut boids: ResMut<Boids>,
-    mesh: Res<BoidMesh>,
-    material: Res<BoidMaterial>,
-    mut rng: ResMut<RandomGenerator>,
+    mut spawner: BoidSpawner,
+    area: Res<SpawnArea>,
+    strategy: Res<SpawnStrategy>,
     max_boid_count: Res<MaxBoidCount>,
-    mut boid_count: ResMut<BoidCount>,
+    time: Res<Time>,
+    mut owed: Local<f32>,
 ) {
-    if boid_count.0 < max_boid_count.0 {
-        let a = rng.random_f32(0.0..TAU);
-        let boid = BoidBundle {
-            marker: Default::default(),
-            position: Position(Vec2::ZERO),
-            velocity: Velocity(Vec2::new(a.cos(), a.sin()).mul(MAX_SPEED/2.0)),
-            acceleration: Acceleration(Vec2::ZERO),
-            mesh: MaterialMesh2dBundle {
-                mesh: mesh.0.clone(),
-                material: material.0.clone(),
-                ..Default::default()
-            },
-        };
-        let boid_id = commands.spawn(boid).id();
-        boids.0.push(boid_id);
-        boid_count.0 += 1;
+    let room = max_boid_count.0.saturating_sub(spawner.boid_count.0);
+    if room == 0 {
+        *owed = 0.;
+        return;
+    }
+    let count = match *strategy {
+        SpawnStrategy::AllAtOnce => room,
+        SpawnStrategy::PerFrame(count) => count.min(room),
+        SpawnStrategy::PerSecond(rate) => {
+            // carry fractions of a boid over to the next frame
+            *owed += rate.max(0.) * time.delta_seconds();
+            let due = owed.floor();
+            *owed -= due;
+            (due as u32).min(room)
+        }
+    };
+    for _ in 0..count {
+        spawner.spawn_in(*area, None);
+    }
+}
+
+/// Adds boids spawned outside [`BoidSpawner`] to the boid list.
+fn adopt_boids(mut boids: ResMut<Boids>, added: Query<Entity, Added<Boid>>) {
+    for entity in added.iter() {
+        if !boids.0.contains(&entity) {
+            boids.0.push(entity);
+        }
     }
 }
 
+fn spawn_initial_boids(mut spawner: BoidSpawner, initial: Res<InitialBoids>) {
+    for &(position, velocity) in initial.0.iter() {
+        spawner.spawn(position, velocity);
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn flock(
-    mut query: Query<(&Position, &Velocity, &mut Acceleration, &Boid), With<Boid>>,
-    positions: Query<&Position>,
+    mut query: Query<(
+        &Position,
+        &Velocity,
+        &mut Acceleration,
+        &mut Crowding,
+        &Boid,
+        &BodyRadius,
+        &Species,
+        Option<&FlockId>,
+        &Inbox,
+        &Forager,
+        Option<&Panic>,
+        Option<&Squad>,
+        &WanderAngle,
+        Option<&mut SteeringForces>
+    ), (With<Boid>, Without<Dormant>, Without<Despawning>)>,
     velocities: Query<&Velocity>,
-    boids: Res<Boids>,
+    kinds: Query<(&Species, Option<&FlockId>)>,
+    obstacles: Query<(&Position, &Obstacle), Without<Boid>>,
+    predators: Query<(&Position, &Velocity), With<Predator>>,
+    grid: Res<SpatialGrid>,
+    squads: Res<Squads>,
+    queueing: Res<Queueing>,
+    corridors: Res<Corridors>,
+    species: Res<SpeciesTable>,
+    behaviors: Res<SteeringBehaviors>,
+    flow: Option<Res<FlowField>>,
+    attractor: Res<CursorAttractor>,
+    // grouped to stay within the parameters a system can take
+    (bounds, mode, metric): (Res<WorldBounds>, Res<BoundaryMode>, Res<PerceptionMetric>),
+    settings: Res<BoidsSettings>,
+    // lookups are tallied per thread, so counting doesn't contend across
+    // them
+    (query_count, mut lookups): (Option<ResMut<GridQueryCount>>, Local<Parallel<usize>>),
 ) {
-    for (pos, vel, mut acc, boid) in query.iter_mut() {
-        let sep = boid.separate(pos, vel, &boids, &positions)
-            .mul(SEPARATION_MULTIPLIER); // Separation
-        let ali = boid.align(pos, vel, &boids, &positions, &velocities)
-            .mul(ALIGN_MULTIPLIER); // Alignment
-        let coh = boid.cohesion(pos, vel, &boids, &positions)
-            .mul(COHESION_MULTIPLIER); // Cohesion
-
-        acc.0.add_assign(Vec2::from((sep.x, sep.y)));
-        acc.0.add_assign(ali);
-        acc.0.add_assign(coh);
+    let counting = query_count.is_some();
+    let velocity_of = |boid| velocities.get(boid).ok().map(|vel| vel.0);
+    let obstacle_list: Vec<(Vec2, f32)> = obstacles.iter().map(|(pos, obstacle)| (pos.0, obstacle.radius)).collect();
+    // each boid only reads its neighbours through the grid and the
+    // read-only queries, so boids steer in parallel
+    query.par_iter_mut().for_each(|(pos, vel, mut acc, mut crowding, boid, body, kind, flock_id, inbox, forager, panic, squad, wander_angle, forces)| {
+        let desc = species.get(*kind);
+        let mode = desc.boundary.unwrap_or(*mode);
+        // boids of other flocks are only kept apart from
+        let affinity = |other: Entity| match kinds.get(other) {
+            Ok((_, other_flock)) if other_flock != flock_id => 0.,
+            Ok((other, _)) => desc.affinity(*other),
+            Err(_) => 1.,
+        };
+        let radius = settings.desired_separation.max(settings.neighbour_radius);
+        let reach = metric.reach(radius);
+        // boids across a wrapping edge are seen where they would be if the
+        // world carried on past it
+        let shifts = mode.wrap_shifts(&bounds, pos.0, reach, body.0);
+        if counting {
+            *lookups.borrow_local_mut() += shifts.len();
+        }
+        let neighbours: Vec<Neighbour> = shifts
+            .into_iter()
+            .flat_map(|shift| grid.neighbours(pos.0 + shift, reach).map(move |(entity, position)| (entity, position - shift)))
+            .filter_map(|(entity, position)| {
+                let distance = metric.distance(position - pos.0, vel.0);
+                if distance <= 0. || distance >= radius || !settings.in_view(pos.0, vel.0, position) {
+                    return None;
+                }
+                let velocity = velocities.get(entity).ok()?.0;
+                Some(Neighbour { entity, position, velocity, distance, affinity: affinity(entity) })
+            })
+            .collect();
+        crowding.0 = neighbours.iter().filter(|n| n.distance < settings.neighbour_radius).count() as u32;
+        let steering = Steering {
+            position: pos.0,
+            velocity: vel.0,
+            max_speed: boid.max_speed,
+            max_force: boid.max_force,
+            species: desc,
+            settings: &settings,
+            neighbours: &neighbours,
+            boid,
+            wander_angle: wander_angle.0,
+            obstacles: &obstacle_list,
+            bounds: &bounds,
+            mode,
+            grid: &grid,
+            velocity_of: &velocity_of,
+        };
+        let weighted = |behavior: &dyn SteeringBehavior| behavior.steer(&steering) * behavior.weight(&settings, desc);
+        let sep = weighted(&*behaviors.separation); // Separation
+        let ali = weighted(&*behaviors.alignment); // Alignment
+        let coh = weighted(&*behaviors.cohesion); // Cohesion
+        let custom = behaviors.custom
+            .iter()
+            .map(|(behavior, weight)| weighted(&**behavior) * *weight);
+
+        let avo = boid.avoid(pos, vel, &obstacles)
+            .mul(AVOID_MULTIPLIER); // Avoidance
+        let eva = predators::evade(boid, pos, vel, &predators, &settings)
+            .mul(settings.evasion); // Evasion
+        let edge = mode.steer(boid, pos, vel, &bounds, &settings)
+            .mul(settings.edge_avoidance); // Edge avoidance
+
+        let alarm = inbox.0
+            .iter()
+            .filter(|signal| signal.kind == SignalKind::Alarm)
+            .map(|signal| boid.flee(signal.origin, pos, vel))
+            .sum::<Vec2>()
+            .clamp_length_max(boid.max_force)
+            .mul(ALARM_MULTIPLIER); // Alarm
+        let fright = panic
+            .map(|panic| boid.flee(panic.origin, pos, vel).mul(PANIC_MULTIPLIER * panic.weight()))
+            .unwrap_or(Vec2::ZERO); // Panic
+
+        let que = if queueing.0 {
+            boid.queue(pos, vel, &grid).mul(QUEUE_MULTIPLIER)
+        } else {
+            Vec2::ZERO
+        };
+
+        // pull or push from the attract tool
+        let cursor = attractor.steer(boid, pos, vel, &settings);
+
+        // advertised food, weighted by how much the boid still trusts it
+        let recruit = forager.target
+            .map(|target| boid.seek(target, pos, vel).mul(RECRUIT_MULTIPLIER * forager.trust))
+            .unwrap_or(Vec2::ZERO);
+
+        // painted flow
+        let flow = flow
+            .as_ref()
+            .map_or(Vec2::ZERO, |flow| flow.steer(boid, pos, vel));
+
+        // squad goal
+        let goal = squad
+            .and_then(|squad| squads.goal(*squad))
+            .and_then(|goal| Some(boid.seek(goal.target?, pos, vel).mul(goal.weight)))
+            .unwrap_or(Vec2::ZERO);
+
+        // highest priority first, collisions before flocking before navigation
+        let mut forces_by_priority = vec![avo, eva, edge, fright, alarm, sep, que, cursor, ali, coh];
+        forces_by_priority.extend(custom);
+        forces_by_priority.extend([
+            corridors.steer(boid, pos, vel),
+            flow,
+            recruit,
+            goal,
+        ]);
+        acc.0.add_assign(desc.mixing.mix(&forces_by_priority, boid.max_force));
+
+        // only inspected boids record their forces
+        if let Some(mut forces) = forces {
+            *forces = SteeringForces {
+                separation: sep.length(),
+                alignment: ali.length(),
+                cohesion: coh.length(),
+                avoidance: avo.length(),
+            };
+        }
+    });
+    if let Some(mut query_count) = query_count {
+        query_count.0 += lookups.iter_mut().map(std::mem::take).sum::<usize>();
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn update_boid(
     mut query: Query<(
         &mut Position,
         &mut Velocity,
         &mut Acceleration,
-        &mut Transform, &Boid
-    ), With<Boid>>,
-    mut windows: Query<&mut Window>,
+        &SpeedScale,
+        &Mass,
+        &Boid
+    ), (With<Boid>, Without<Dormant>)>,
+    integrator: Res<Integration>,
+    drag: Option<Res<DragForce>>,
     time: Res<Time>
 ) {
-    let window = windows.single_mut();
-    let half_width = window.width() / 2.0;
-    let half_height = window.height() / 2.0;
+    let mut invalid = 0;
     for (
         mut pos,
         mut vel,
         mut acc,
-        mut transform,
+        speed_scale,
+        mass,
         boid
     ) in query.iter_mut() {
-        let theta = vel.0.y.atan2(vel.0.x) + (90. * PI / 180.) * -1.;
-        transform.translation = Vec3::new(pos.0.x, pos.0.y, 0.);
-        transform.rotation = Quat::from_rotation_z(theta);
+        let previous = pos.0;
+        let acceleration = acc.0 / mass.0.max(MIN_MASS);
 
-        // update velocity
-        vel.0.add_assign(acc.0);
-        // limit speed
-        vel.0 = vel.0.clamp_length_max(boid.max_speed);
-        // update position
-        pos.0.add_assign(vel.0 * time.delta_seconds());
+        // update velocity and position
+        let state = integrator.0.step(
+            State { position: pos.0, velocity: vel.0 },
+            time.delta_seconds(),
+            |_, velocity| match &drag {
+                Some(drag) => acceleration + drag.0.acceleration(velocity),
+                None => acceleration,
+            },
+        );
+        pos.0 = state.position;
+        // limit turn rate and speed
+        let velocity = orientation::limit_turn(vel.0, state.velocity, time.delta_seconds());
+        vel.0 = velocity.clamp_length_max(boid.max_speed * speed_scale.0);
 
-        // Wrap around the x-axis
-        if pos.0.x < -half_width - R {
-            pos.0.x = half_width + R;
-        } else if pos.0.x > half_width + R {
-            pos.0.x = -half_width - R;
+        // reset acceleration to 0
+        acc.0 = Vec2::ZERO;
+
+        if !pos.0.is_finite() || !vel.0.is_finite() {
+            pos.0 = previous;
+            vel.0 = Vec2::ZERO;
+            invalid += 1;
         }
+    }
+    soft_assert!(invalid == 0, "reset {invalid} boids with non-finite position or velocity");
+}
 
-        // Wrap around the y-axis
-        if pos.0.y < -half_height - R {
-            pos.0.y = half_height + R;
-        } else if pos.0.y > half_height + R {
-            pos.0.y = -half_height - R;
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+fn measure_memory(
+    mut memory: ResMut<BoidMemoryUsage>,
+    archetypes: &Archetypes,
+    components: &Components,
+    boids: Res<Boids>,
+    boid_count: Res<BoidCount>,
+    grid: Res<SpatialGrid>,
+    flying: Query<Entity, (With<Boid>, Without<Dormant>, Without<Despawning>)>,
+    perched: Query<(), (With<Boid>, With<Dormant>, Without<Despawning>)>,
+) {
+    let Some(boid_id) = components.component_id::<Boid>() else {
+        return;
+    };
+    let mut count = 0;
+    let mut bytes = 0;
+    for archetype in archetypes.iter().filter(|archetype| archetype.contains(boid_id)) {
+        let row_size: usize = archetype
+            .components()
+            .filter_map(|id| components.get_info(id))
+            .map(|info| info.layout().size())
+            .sum();
+        count += archetype.len();
+        bytes += archetype.len() * row_size;
+    }
+    memory.boids = count;
+    memory.component_bytes = bytes;
+    memory.index_bytes = boids.0.capacity() * std::mem::size_of::<Entity>() + grid.heap_bytes();
+
+    let listed: HashSet<Entity> = boids.0.iter().copied().collect();
+    let flying_listed = flying.iter().filter(|entity| listed.contains(entity)).count();
+    memory.unindexed = flying.iter().count() - flying_listed;
+    memory.stale_entries = boids.0.len() - flying_listed;
+    memory.listed = boids.0.len();
+    memory.perched = perched.iter().count();
+    memory.counted = boid_count.0 as usize;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        prelude::World,
+        tasks::{ComputeTaskPool, TaskPool},
+    };
+
+    use super::*;
+
+    #[test]
+    fn flocking_counts_grid_lookups_until_measured() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        let settings = BoidsSettings::default();
+        let mut world = World::new();
+        world.init_resource::<Boids>();
+        world.init_resource::<SpatialGrid>();
+        world.init_resource::<Squads>();
+        world.init_resource::<Corridors>();
+        world.init_resource::<CursorAttractor>();
+        world.init_resource::<GridStats>();
+        world.init_resource::<GridQueryCount>();
+        world.insert_resource(Queueing(false));
+        world.insert_resource(SpeciesTable(vec![SpeciesDesc::default()]));
+        world.insert_resource(SteeringBehaviors::default());
+        world.insert_resource(WorldBounds::default());
+        world.insert_resource(BoundaryMode::Wrap);
+        world.insert_resource(PerceptionMetric::default());
+        world.insert_resource(settings);
+        // well inside the world, so nobody looks across a wrapping edge
+        for i in 0..10 {
+            let boid = world.spawn(BoidBundle::new(Vec2::new(i as f32 * 5., 0.), Vec2::X, &settings)).id();
+            world.resource_mut::<Boids>().0.push(boid);
         }
+        world.run_system_once(grid::rebuild_grid);
 
-        // reset acceleration to 0
-        acc.0.mul_assign(0f32);
+        world.run_system_once(flock);
+        world.run_system_once(flock);
+        assert_eq!(world.resource::<GridQueryCount>().0, 20);
+        world.run_system_once(grid::measure_grid);
+        assert_eq!(world.resource::<GridStats>().flocking_lookups, 20);
+        assert_eq!(world.resource::<GridQueryCount>().0, 0);
     }
 }
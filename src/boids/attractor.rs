@@ -0,0 +1,66 @@
+use std::ops::Mul;
+use bevy::{
+    input::ButtonInput,
+    prelude::{KeyCode, MouseButton, Res, ResMut, Resource, Vec2},
+};
+
+use crate::tools::{ActiveTool, CursorWorld};
+use super::{Boid, BoidsSettings, Position, Velocity};
+
+/// Where the cursor pulls or pushes boids while a mouse button is held with
+/// the attract tool active.
+#[derive(Resource, Default)]
+pub(super) struct CursorAttractor {
+    point: Option<Vec2>,
+    /// 1 attracts, -1 repels
+    sign: f32,
+}
+
+impl CursorAttractor {
+    /// Seeks or flees the cursor, weighted by the cursor strength and fading
+    /// out towards the edge of the cursor radius.
+    pub(super) fn steer(&self, boid: &Boid, position: &Position, velocity: &Velocity, settings: &BoidsSettings) -> Vec2 {
+        let Some(point) = self.point else {
+            return Vec2::ZERO;
+        };
+        let dist = position.0.distance(point);
+        if dist >= settings.cursor_radius {
+            return Vec2::ZERO;
+        }
+        let falloff = 1. - dist / settings.cursor_radius;
+        let steer = if self.sign > 0. {
+            boid.seek(point, position, velocity)
+        } else {
+            boid.flee(point, position, velocity)
+        };
+        steer.mul(settings.cursor_strength * falloff)
+    }
+}
+
+/// C toggles the attract tool. Holding the left mouse button draws boids
+/// to the cursor, holding the right one drives them away.
+pub(super) fn attract_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut attractor: ResMut<CursorAttractor>,
+) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        tool.toggle(ActiveTool::Attract);
+    }
+    let sign = if *tool != ActiveTool::Attract {
+        None
+    } else if buttons.pressed(MouseButton::Left) {
+        Some(1.)
+    } else if buttons.pressed(MouseButton::Right) {
+        Some(-1.)
+    } else {
+        None
+    };
+    let point = sign.and(cursor.0);
+    if attractor.point != point {
+        attractor.point = point;
+        attractor.sign = sign.unwrap_or(0.);
+    }
+}
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+use bevy::prelude::{Entity, Resource, Vec2};
+use boids_core::steering;
+
+use super::{grid::SpatialGrid, species::SpeciesDesc, Boid, BoidsSettings, BoundaryMode, Position, Velocity, WorldBounds};
+
+/// A steering rule composed into every boid's acceleration, like
+/// Reynolds' separation, alignment and cohesion. Register your own with
+/// [`BoidsPlugin::with_steering_behavior`](super::BoidsPlugin::with_steering_behavior).
+///
+/// Boids are steered in parallel, so behaviours only get a read-only view
+/// of the boid and its neighbours.
+pub trait SteeringBehavior: Send + Sync + 'static {
+    /// Shown in the inspector and logs
+    fn name(&self) -> &str;
+
+    /// Steering force for the boid, usually clamped to its max force.
+    fn steer(&self, boid: &Steering) -> Vec2;
+
+    /// Weight on the force on top of the one it was registered with, for
+    /// behaviours tuned live through [`BoidsSettings`] or per species.
+    fn weight(&self, _settings: &BoidsSettings, _species: &SpeciesDesc) -> f32 {
+        1.
+    }
+}
+
+/// A boid seen by a neighbour steering around it: within the larger of the
+/// separation and neighbour radii by the
+/// [`PerceptionMetric`](super::PerceptionMetric) and inside the neighbour's
+/// field of view.
+#[derive(Clone, Copy, Debug)]
+pub struct Neighbour {
+    pub entity: Entity,
+    /// Across a wrapping edge, where the neighbour would be if the world
+    /// carried on past the edge, so it may lie outside the world bounds
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Distance as perceived by the steering boid
+    pub distance: f32,
+    /// How much the steering boid aligns and coheres with this one: none
+    /// with boids of other flocks, otherwise its species' affinity
+    pub affinity: f32,
+}
+
+/// The boid a [`SteeringBehavior`] steers and what it can see.
+pub struct Steering<'a> {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub max_speed: f32,
+    pub max_force: f32,
+    pub species: &'a SpeciesDesc,
+    pub settings: &'a BoidsSettings,
+    pub neighbours: &'a [Neighbour],
+    pub(super) boid: &'a Boid,
+    /// Where on its [`Wander`](super::Wander) circle the boid is heading
+    pub(super) wander_angle: f32,
+    /// Centre and radius of every obstacle
+    pub(super) obstacles: &'a [(Vec2, f32)],
+    pub(super) bounds: &'a WorldBounds,
+    /// The boundary mode of the boid's species
+    pub(super) mode: BoundaryMode,
+    pub(super) grid: &'a SpatialGrid,
+    pub(super) velocity_of: &'a (dyn Fn(Entity) -> Option<Vec2> + Sync),
+}
+
+impl Steering<'_> {
+    /// Force turning the boid towards `target` at full speed.
+    pub fn seek(&self, target: Vec2) -> Vec2 {
+        self.boid.seek(target, &Position(self.position), &Velocity(self.velocity))
+    }
+
+    /// Force turning the boid away from `threat` at full speed.
+    pub fn flee(&self, threat: Vec2) -> Vec2 {
+        self.boid.flee(threat, &Position(self.position), &Velocity(self.velocity))
+    }
+
+    /// Force turning the boid to fly along `direction` at full speed, or
+    /// nothing if `direction` is zero.
+    pub fn head(&self, direction: Vec2) -> Vec2 {
+        steering::head(self.velocity, direction, self.max_speed, self.max_force)
+    }
+}
+
+/// Behaviours every boid is steered by. The three flocking rules each have
+/// their own slot, so a variant such as spring cohesion replaces the rule
+/// it stands in for and keeps its priority.
+#[derive(Resource, Clone)]
+pub(super) struct SteeringBehaviors {
+    pub(super) separation: Arc<dyn SteeringBehavior>,
+    pub(super) alignment: Arc<dyn SteeringBehavior>,
+    pub(super) cohesion: Arc<dyn SteeringBehavior>,
+    /// Registered with their weights, highest priority first
+    pub(super) custom: Vec<(Arc<dyn SteeringBehavior>, f32)>,
+}
+
+impl Default for SteeringBehaviors {
+    fn default() -> Self {
+        SteeringBehaviors {
+            separation: Arc::new(Separation),
+            alignment: Arc::new(Alignment),
+            cohesion: Arc::new(Cohesion),
+            custom: Vec::new(),
+        }
+    }
+}
+
+/// Steering away from neighbours closer than the desired separation,
+/// weighted by the separation setting.
+pub struct Separation;
+
+impl SteeringBehavior for Separation {
+    fn name(&self) -> &str {
+        "separation"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        let close = boid.neighbours
+            .iter()
+            .filter(|n| n.distance < boid.settings.desired_separation)
+            .map(|n| (n.position, n.distance));
+        steering::separation(boid.position, boid.velocity, close, boid.max_speed, boid.max_force)
+    }
+
+    fn weight(&self, settings: &BoidsSettings, species: &SpeciesDesc) -> f32 {
+        settings.separation * species.weights[0]
+    }
+}
+
+/// Separation by predicted closest approach instead of current distance:
+/// boids steer away from where neighbours will pass closest within
+/// `horizon` seconds, so fast boids on a collision course turn early and
+/// ones flying side by side are left alone. Weighted by the separation
+/// setting, like [`Separation`] which it replaces.
+#[derive(Clone, Copy, Debug)]
+pub struct PredictiveSeparation {
+    pub horizon: f32,
+}
+
+impl SteeringBehavior for PredictiveSeparation {
+    fn name(&self) -> &str {
+        "separation"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        let near = boid.neighbours.iter().map(|n| (n.position, n.velocity));
+        steering::predictive_separation(
+            boid.position,
+            boid.velocity,
+            near,
+            boid.settings.desired_separation,
+            self.horizon,
+            boid.max_speed,
+            boid.max_force,
+        )
+    }
+
+    fn weight(&self, settings: &BoidsSettings, species: &SpeciesDesc) -> f32 {
+        settings.separation * species.weights[0]
+    }
+}
+
+/// Steering towards the neighbours' mean heading, weighted by the
+/// alignment setting.
+pub struct Alignment;
+
+impl SteeringBehavior for Alignment {
+    fn name(&self) -> &str {
+        "alignment"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        let near = boid.neighbours
+            .iter()
+            .filter(|n| n.distance < boid.settings.neighbour_radius)
+            .map(|n| (n.velocity, n.affinity));
+        steering::alignment(boid.velocity, near, boid.max_speed, boid.max_force)
+    }
+
+    fn weight(&self, settings: &BoidsSettings, species: &SpeciesDesc) -> f32 {
+        settings.alignment * species.weights[1]
+    }
+}
+
+/// Steering towards the neighbours' centre, weighted by the cohesion
+/// setting.
+pub struct Cohesion;
+
+impl SteeringBehavior for Cohesion {
+    fn name(&self) -> &str {
+        "cohesion"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        let near = boid.neighbours
+            .iter()
+            .filter(|n| n.distance < boid.settings.neighbour_radius)
+            .map(|n| (n.position, n.affinity));
+        steering::cohesion(boid.position, boid.velocity, near, boid.max_speed, boid.max_force)
+    }
+
+    fn weight(&self, settings: &BoidsSettings, species: &SpeciesDesc) -> f32 {
+        settings.cohesion * species.weights[2]
+    }
+}
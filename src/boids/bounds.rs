@@ -0,0 +1,372 @@
+#[cfg(feature = "render")]
+use std::f32::consts::FRAC_PI_6;
+use bevy::{
+    input::ButtonInput,
+    log::info,
+    prelude::{Entity, Event, EventReader, EventWriter, KeyCode, Query, Res, ResMut, Resource, Vec2, Window, With, Without},
+    window::{PrimaryWindow, WindowCreated, WindowResized},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+use boids_core::bounds::{apply_axis, hex_inradius, wrap_hexagonal};
+
+pub use boids_core::bounds::AxisBoundary;
+
+use super::{
+    despawning::Despawning,
+    species::BodyRadius,
+    Boid,
+    BoidSpawner,
+    BoidsSettings,
+    Position,
+    Species,
+    SpeciesTable,
+    Velocity,
+};
+
+// half the size of the default window, used when there is no window
+const DEFAULT_HALF_EXTENTS: Vec2 = Vec2::new(640., 360.);
+
+/// Rectangle centred on the origin that boids live in. Boundaries,
+/// spawning and steering all go by it rather than by the window, so the
+/// window only decides how much of the world is on screen.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct WorldBounds {
+    pub half_extents: Vec2,
+    /// Resize the world along with the primary window
+    pub follow_window: bool,
+}
+
+impl WorldBounds {
+    /// A world `size` across that keeps its size when the window is
+    /// resized.
+    pub fn fixed(size: Vec2) -> Self {
+        WorldBounds {
+            half_extents: size / 2.,
+            follow_window: false,
+        }
+    }
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        WorldBounds {
+            half_extents: DEFAULT_HALF_EXTENTS,
+            follow_window: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+impl Edge {
+    pub fn opposite(self) -> Edge {
+        match self {
+            Edge::Left => Edge::Right,
+            Edge::Right => Edge::Left,
+            Edge::Bottom => Edge::Top,
+            Edge::Top => Edge::Bottom,
+        }
+    }
+
+    /// Unit vector pointing from this edge into the world.
+    pub fn inward(self) -> Vec2 {
+        match self {
+            Edge::Left => Vec2::X,
+            Edge::Right => Vec2::NEG_X,
+            Edge::Bottom => Vec2::Y,
+            Edge::Top => Vec2::NEG_Y,
+        }
+    }
+
+    /// Point just outside this edge, where something of `radius` is right
+    /// at the limit it leaves the world at, `t` in `[-1, 1]` running along
+    /// it.
+    pub fn point(self, bounds: &WorldBounds, t: f32, radius: f32) -> Vec2 {
+        let outside = bounds.half_extents + radius;
+        match self {
+            Edge::Left => Vec2::new(-outside.x, t * bounds.half_extents.y),
+            Edge::Right => Vec2::new(outside.x, t * bounds.half_extents.y),
+            Edge::Bottom => Vec2::new(t * bounds.half_extents.x, -outside.y),
+            Edge::Top => Vec2::new(t * bounds.half_extents.x, outside.y),
+        }
+    }
+}
+
+/// What happens to boids that leave the [`WorldBounds`].
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// Reappear at the opposite edge
+    #[default]
+    Wrap,
+    /// Reflect off the edges
+    Bounce,
+    /// Steer back towards the centre within the edge margin of the
+    /// [`BoidsSettings`], reflecting off the edges if that wasn't enough
+    Avoid,
+    /// Despawn, optionally spawning a replacement at the given edge
+    Despawn { respawn: Option<Edge> },
+    /// Separate behaviour for the left/right and bottom/top edges
+    Axes { x: AxisBoundary, y: AxisBoundary },
+    /// Periodic hexagonal world: boids leaving the largest flat-topped
+    /// hexagon that fits the bounds reappear at the opposite side
+    Hexagonal,
+}
+
+impl BoundaryMode {
+    /// Periodic left to right, walled at the bottom and top, like a river.
+    pub fn cylinder() -> Self {
+        BoundaryMode::Axes {
+            x: AxisBoundary::Wrap,
+            y: AxisBoundary::Bounce,
+        }
+    }
+
+    /// Steering back towards the centre for boids within the edge margin,
+    /// stronger the closer they are to the edge. Only [`BoundaryMode::Avoid`]
+    /// steers.
+    pub(super) fn steer(
+        self,
+        boid: &Boid,
+        position: &Position,
+        velocity: &Velocity,
+        bounds: &WorldBounds,
+        settings: &BoidsSettings,
+    ) -> Vec2 {
+        if self != BoundaryMode::Avoid || settings.edge_margin <= 0. {
+            return Vec2::ZERO;
+        }
+        let inner = (bounds.half_extents - settings.edge_margin).max(Vec2::ZERO);
+        // how far into the margin along each axis, 1 at the edge
+        let depth = ((position.0.abs() - inner) / settings.edge_margin).clamp(Vec2::ZERO, Vec2::ONE);
+        if depth == Vec2::ZERO {
+            return Vec2::ZERO;
+        }
+        let inward = (-position.0.signum() * depth).normalize();
+        (inward * boid.max_speed - velocity.0).clamp_length_max(boid.max_force) * depth.max_element()
+    }
+
+    /// Moves `position` at least `margin` inside the world, away from the
+    /// edges and wrap seams.
+    pub(super) fn fit(self, bounds: &WorldBounds, position: Vec2, margin: f32) -> Vec2 {
+        match self {
+            BoundaryMode::Hexagonal => wrap_hexagonal(position, (hex_inradius(bounds.half_extents) - margin).max(0.)),
+            _ => {
+                let limit = (bounds.half_extents - margin).max(Vec2::ZERO);
+                position.clamp(-limit, limit)
+            }
+        }
+    }
+
+    /// Keeps something of `radius` that can't be despawned inside the
+    /// world, returning its new position and velocity. Despawning bounds
+    /// bounce it instead.
+    pub(super) fn confine(
+        self,
+        bounds: &WorldBounds,
+        mut position: Vec2,
+        mut velocity: Vec2,
+        radius: f32,
+    ) -> (Vec2, Vec2) {
+        let limit = bounds.half_extents + radius;
+        let (x, y) = match self {
+            BoundaryMode::Wrap => (AxisBoundary::Wrap, AxisBoundary::Wrap),
+            BoundaryMode::Bounce | BoundaryMode::Avoid | BoundaryMode::Despawn { .. } => {
+                (AxisBoundary::Bounce, AxisBoundary::Bounce)
+            }
+            BoundaryMode::Axes { x, y } => (x, y),
+            BoundaryMode::Hexagonal => return (wrap_hexagonal(position, hex_inradius(bounds.half_extents) + radius), velocity),
+        };
+        apply_axis(x, &mut position.x, &mut velocity.x, limit.x);
+        apply_axis(y, &mut position.y, &mut velocity.y, limit.y);
+        (position, velocity)
+    }
+
+    /// Whether the left and right, and the bottom and top edges are walls
+    /// boids bounce off.
+    pub(super) fn walls(self) -> (bool, bool) {
+        match self {
+            BoundaryMode::Bounce | BoundaryMode::Avoid => (true, true),
+            BoundaryMode::Axes { x, y } => (x == AxisBoundary::Bounce, y == AxisBoundary::Bounce),
+            BoundaryMode::Wrap | BoundaryMode::Despawn { .. } | BoundaryMode::Hexagonal => (false, false),
+        }
+    }
+
+    /// Offsets to look for neighbours of `position` around, so boids just
+    /// across a wrapping edge are found as well: none, plus the wrapping
+    /// period towards each wrapping edge within `radius`. Boids wrap once
+    /// their body is past the edge, as in [`apply_boundaries`], so the
+    /// period is the world's width or height plus a boid of `body_radius`
+    /// on either side. A boid found around `position + shift` is really
+    /// `shift` closer.
+    pub(super) fn wrap_shifts(self, bounds: &WorldBounds, position: Vec2, radius: f32, body_radius: f32) -> Vec<Vec2> {
+        let (wrap_x, wrap_y) = match self {
+            BoundaryMode::Wrap => (true, true),
+            BoundaryMode::Axes { x, y } => (x == AxisBoundary::Wrap, y == AxisBoundary::Wrap),
+            _ => (false, false),
+        };
+        let limit = bounds.half_extents + body_radius;
+        let period = limit * 2.;
+        // in a world narrower than the radius a boid could see itself
+        let axis = |wraps: bool, position: f32, half_extent: f32, period: f32| {
+            let mut shifts = vec![0.];
+            if wraps && period > 2. * radius {
+                if position + radius > half_extent {
+                    shifts.push(-period);
+                } else if position - radius < -half_extent {
+                    shifts.push(period);
+                }
+            }
+            shifts
+        };
+        let xs = axis(wrap_x, position.x, limit.x, period.x);
+        let ys = axis(wrap_y, position.y, limit.y, period.y);
+        ys.iter().flat_map(|&y| xs.iter().map(move |&x| Vec2::new(x, y))).collect()
+    }
+}
+
+/// Sent when a boid is despawned for leaving the world bounds.
+#[derive(Event)]
+pub struct BoidExited {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+/// Resizes bounds that follow the window when the primary window is opened
+/// or resized, to the last size it was resized to this frame.
+pub(super) fn sync_bounds_to_window(
+    mut created: EventReader<WindowCreated>,
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut bounds: ResMut<WorldBounds>,
+) {
+    let opened = created
+        .read()
+        .filter_map(|event| windows.get(event.window).ok())
+        .last()
+        .map(|window| Vec2::new(window.width(), window.height()));
+    let resized = resized
+        .read()
+        .filter(|event| windows.contains(event.window))
+        .last()
+        .map(|event| Vec2::new(event.width, event.height));
+    if !bounds.follow_window {
+        return;
+    }
+    if let Some(size) = resized.or(opened) {
+        let half_extents = size / 2.;
+        if bounds.half_extents != half_extents {
+            bounds.half_extents = half_extents;
+        }
+    }
+}
+
+/// Applies the boundary mode of each boid's species, or the plugin's.
+#[allow(clippy::type_complexity)]
+pub(super) fn apply_boundaries(
+    mut query: Query<(Entity, &mut Position, &mut Velocity, &BodyRadius, &Species), (With<Boid>, Without<Despawning>)>,
+    bounds: Res<WorldBounds>,
+    mode: Res<BoundaryMode>,
+    species: Res<SpeciesTable>,
+    mut spawner: BoidSpawner,
+    mut exited: EventWriter<BoidExited>,
+) {
+    for (entity, mut pos, mut vel, radius, kind) in query.iter_mut() {
+        // boids cross the edge only once their whole body is past it
+        let limit = bounds.half_extents + radius.0;
+        match species.get(*kind).boundary.unwrap_or(*mode) {
+            BoundaryMode::Wrap => {
+                // Wrap around the x-axis
+                if pos.0.x < -limit.x {
+                    pos.0.x = limit.x;
+                } else if pos.0.x > limit.x {
+                    pos.0.x = -limit.x;
+                }
+
+                // Wrap around the y-axis
+                if pos.0.y < -limit.y {
+                    pos.0.y = limit.y;
+                } else if pos.0.y > limit.y {
+                    pos.0.y = -limit.y;
+                }
+            }
+            // avoiding boids steer clear of the edges in flock, boids that
+            // got there anyway bounce
+            BoundaryMode::Bounce | BoundaryMode::Avoid => {
+                let (pos, vel) = (&mut pos.0, &mut vel.0);
+                apply_axis(AxisBoundary::Bounce, &mut pos.x, &mut vel.x, limit.x);
+                apply_axis(AxisBoundary::Bounce, &mut pos.y, &mut vel.y, limit.y);
+            }
+            BoundaryMode::Despawn { respawn } => {
+                if pos.0.x.abs() <= limit.x && pos.0.y.abs() <= limit.y {
+                    continue;
+                }
+                spawner.despawn(entity);
+                exited.send(BoidExited { entity, position: pos.0 });
+                // the boid comes back as the species that left
+                if let Some(edge) = respawn {
+                    let t = spawner.rng.random_f32(-1.0..1.0);
+                    let speed = spawner.settings.max_speed / 2.;
+                    spawner.spawn_at_edge(edge, t, edge.inward() * speed, Some(*kind));
+                }
+            }
+            BoundaryMode::Axes { x, y } => {
+                let (pos, vel) = (&mut pos.0, &mut vel.0);
+                apply_axis(x, &mut pos.x, &mut vel.x, limit.x);
+                apply_axis(y, &mut pos.y, &mut vel.y, limit.y);
+            }
+            BoundaryMode::Hexagonal => {
+                let inradius = hex_inradius(bounds.half_extents) + radius.0;
+                let wrapped = wrap_hexagonal(pos.0, inradius);
+                if wrapped != pos.0 {
+                    pos.0 = wrapped;
+                }
+            }
+        }
+    }
+}
+
+/// O cycles between wrapping, bouncing and avoiding the edges.
+pub(super) fn boundary_input(keys: Res<ButtonInput<KeyCode>>, mut mode: ResMut<BoundaryMode>) {
+    if !keys.just_pressed(KeyCode::KeyO) {
+        return;
+    }
+    *mode = match *mode {
+        BoundaryMode::Wrap => BoundaryMode::Bounce,
+        BoundaryMode::Bounce => BoundaryMode::Avoid,
+        _ => BoundaryMode::Wrap,
+    };
+    info!("boundary mode: {:?}", *mode);
+}
+
+/// Outlines the margin boids avoid the edges by.
+#[cfg(feature = "render")]
+pub(super) fn draw_edge_margin(
+    mut gizmos: Gizmos,
+    bounds: Res<WorldBounds>,
+    mode: Res<BoundaryMode>,
+    settings: Res<BoidsSettings>,
+) {
+    if *mode != BoundaryMode::Avoid {
+        return;
+    }
+    let inner = (bounds.half_extents - settings.edge_margin).max(Vec2::ZERO);
+    gizmos.rect_2d(Vec2::ZERO, 0., inner * 2., Color::srgba(1., 1., 1., 0.15));
+}
+
+/// Outlines the hexagonal world.
+#[cfg(feature = "render")]
+pub(super) fn draw_hexagon(mut gizmos: Gizmos, bounds: Res<WorldBounds>, mode: Res<BoundaryMode>) {
+    if *mode != BoundaryMode::Hexagonal {
+        return;
+    }
+    let circumradius = hex_inradius(bounds.half_extents) * 2. / 3f32.sqrt();
+    let corners = (0..=6).map(|corner| Vec2::from_angle(corner as f32 * 2. * FRAC_PI_6) * circumradius);
+    gizmos.linestrip_2d(corners, Color::srgba(1., 1., 1., 0.15));
+}
@@ -0,0 +1,124 @@
+use bevy::{
+    input::{
+        mouse::{MouseMotion, MouseScrollUnit, MouseWheel},
+        ButtonInput,
+    },
+    log::info,
+    prelude::{
+        Camera2d,
+        EventReader,
+        KeyCode,
+        MouseButton,
+        OrthographicProjection,
+        Query,
+        Real,
+        Res,
+        ResMut,
+        Resource,
+        Time,
+        Transform,
+        Vec2,
+        With,
+        Without,
+    },
+};
+
+use super::{inspector::Selected, Boid, WorldBounds};
+
+// screen pixels per second the keys pan the camera by
+const PAN_SPEED: f32 = 600.;
+// zoom factor per line scrolled
+const ZOOM_STEP: f32 = 1.1;
+// pixels touchpads scroll by per line
+const PIXELS_PER_LINE: f32 = 20.;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 10.;
+// how quickly the camera closes in on a followed boid, per second
+const FOLLOW_RATE: f32 = 5.;
+
+/// Whether the camera follows the selected boid.
+#[derive(Resource, Default)]
+pub(super) struct FollowSelected(bool);
+
+/// WASD or dragging with the middle mouse button pans the camera, the
+/// scroll wheel zooms it and Tab toggles following the boid selected with
+/// the inspect tool. Panning lets go of the followed boid. The camera only
+/// changes the view; the world bounds stay where they are.
+pub(super) fn camera_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    time: Res<Time<Real>>,
+    mut follow: ResMut<FollowSelected>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    if keys.just_pressed(KeyCode::Tab) {
+        follow.0 = !follow.0;
+        info!("camera: {}", if follow.0 { "following the selected boid" } else { "free" });
+    }
+    let dragged: Vec2 = motion.read().map(|event| event.delta).sum();
+    let scrolled: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    let Ok((mut transform, mut projection)) = cameras.get_single_mut() else {
+        return;
+    };
+
+    // real time, so the view can still be moved around a paused flock
+    let mut pan = Vec2::ZERO;
+    for (key, direction) in [
+        (KeyCode::KeyW, Vec2::Y),
+        (KeyCode::KeyA, Vec2::NEG_X),
+        (KeyCode::KeyS, Vec2::NEG_Y),
+        (KeyCode::KeyD, Vec2::X),
+    ] {
+        if keys.pressed(key) {
+            pan += direction * PAN_SPEED * time.delta_seconds();
+        }
+    }
+    if buttons.pressed(MouseButton::Middle) {
+        // the world follows the cursor, whose y points down the screen
+        pan += Vec2::new(-dragged.x, dragged.y);
+    }
+    if pan != Vec2::ZERO {
+        if follow.0 {
+            follow.0 = false;
+            info!("camera: free");
+        }
+        transform.translation += (pan * projection.scale).extend(0.);
+    }
+    if scrolled != 0. {
+        projection.scale = (projection.scale * ZOOM_STEP.powf(-scrolled)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+/// Eases the camera towards the selected boid while following it, and
+/// jumps straight there when the boid wraps around the world.
+#[allow(clippy::type_complexity)]
+pub(super) fn follow_selected(
+    follow: Res<FollowSelected>,
+    time: Res<Time<Real>>,
+    bounds: Res<WorldBounds>,
+    selected: Query<&Transform, (With<Selected>, With<Boid>, Without<Camera2d>)>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    if !follow.0 {
+        return;
+    }
+    let (Some(target), Ok(mut camera)) = (selected.iter().next(), cameras.get_single_mut()) else {
+        return;
+    };
+    let target = target.translation.truncate();
+    let current = camera.translation.truncate();
+    let position = if current.distance(target) > bounds.half_extents.min_element() {
+        target
+    } else {
+        current.lerp(target, 1. - (-FOLLOW_RATE * time.delta_seconds()).exp())
+    };
+    camera.translation = position.extend(camera.translation.z);
+}
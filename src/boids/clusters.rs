@@ -0,0 +1,141 @@
+use std::{collections::VecDeque, fs::File, io::{self, Write}, time::Duration};
+use bevy::{
+    input::ButtonInput,
+    log::{error, info},
+    prelude::{KeyCode, Query, Res, ResMut, Resource, Time, Timer, TimerMode, Vec2, With},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use super::{Boid, BoidsSettings, Position};
+#[cfg(feature = "render")]
+use super::WorldBounds;
+
+/// Upper bound (inclusive) of each cluster size bucket.
+const BUCKETS: [usize; 6] = [1, 4, 9, 24, 49, usize::MAX];
+const BUCKET_LABELS: [&str; 6] = ["1", "2-4", "5-9", "10-24", "25-49", "50+"];
+const HISTORY_LEN: usize = 120;
+const EXPORT_PATH: &str = "cluster_sizes.csv";
+
+#[cfg(feature = "render")]
+const CHART_HEIGHT: f32 = 100.;
+#[cfg(feature = "render")]
+const CHART_COLUMN_WIDTH: f32 = 2.;
+#[cfg(feature = "render")]
+const CHART_MARGIN: f32 = 16.;
+
+/// Number of boids in clusters of each size bucket, sampled once a second.
+#[derive(Resource)]
+pub struct ClusterHistogram {
+    /// Seconds since startup each sample was taken at, and its counts
+    pub history: VecDeque<(f32, [u32; BUCKETS.len()])>,
+    timer: Timer,
+    visible: bool,
+}
+
+impl Default for ClusterHistogram {
+    fn default() -> Self {
+        ClusterHistogram {
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
+            visible: false,
+        }
+    }
+}
+
+impl ClusterHistogram {
+    fn export(&self) -> io::Result<()> {
+        let mut file = File::create(EXPORT_PATH)?;
+        writeln!(file, "second,{}", BUCKET_LABELS.join(","))?;
+        for (second, counts) in &self.history {
+            let counts: Vec<String> = counts.iter().map(u32::to_string).collect();
+            writeln!(file, "{second:.0},{}", counts.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sizes of the connected components formed by linking boids closer than
+/// `radius` to each other.
+pub(super) fn cluster_sizes(positions: &[Vec2], radius: f32) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..positions.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let radius_squared = radius * radius;
+    for i in 0..positions.len() {
+        for j in i + 1..positions.len() {
+            if positions[i].distance_squared(positions[j]) < radius_squared {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[a] = b;
+            }
+        }
+    }
+    let mut sizes = vec![0; positions.len()];
+    for i in 0..positions.len() {
+        let root = find(&mut parent, i);
+        sizes[root] += 1;
+    }
+    sizes.retain(|&size| size > 0);
+    sizes
+}
+
+pub(super) fn sample_clusters(
+    mut histogram: ResMut<ClusterHistogram>,
+    boids: Query<&Position, With<Boid>>,
+    settings: Res<BoidsSettings>,
+    time: Res<Time>,
+) {
+    if !histogram.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let positions: Vec<Vec2> = boids.iter().map(|pos| pos.0).collect();
+    let mut counts = [0; BUCKETS.len()];
+    for size in cluster_sizes(&positions, settings.neighbour_radius) {
+        let bucket = BUCKETS.iter().position(|&max| size <= max).unwrap_or(BUCKETS.len() - 1);
+        counts[bucket] += size as u32;
+    }
+    if histogram.history.len() == HISTORY_LEN {
+        histogram.history.pop_front();
+    }
+    histogram.history.push_back((time.elapsed_seconds(), counts));
+}
+
+pub(super) fn cluster_chart_input(keys: Res<ButtonInput<KeyCode>>, mut histogram: ResMut<ClusterHistogram>) {
+    if keys.just_pressed(KeyCode::F2) {
+        histogram.visible = !histogram.visible;
+    }
+    if keys.just_pressed(KeyCode::F3) {
+        match histogram.export() {
+            Ok(()) => info!("exported cluster sizes to {EXPORT_PATH}"),
+            Err(err) => error!("failed to export cluster sizes: {err}"),
+        }
+    }
+}
+
+/// Stacked chart of the bucket shares, newest sample on the right.
+#[cfg(feature = "render")]
+pub(super) fn draw_cluster_chart(mut gizmos: Gizmos, histogram: Res<ClusterHistogram>, bounds: Res<WorldBounds>) {
+    if !histogram.visible {
+        return;
+    }
+    let origin = -bounds.half_extents + CHART_MARGIN;
+    for (column, (_, counts)) in histogram.history.iter().enumerate() {
+        let total: u32 = counts.iter().sum();
+        if total == 0 {
+            continue;
+        }
+        let x = origin.x + column as f32 * CHART_COLUMN_WIDTH;
+        let mut y = origin.y;
+        for (bucket, &count) in counts.iter().enumerate() {
+            let height = CHART_HEIGHT * count as f32 / total as f32;
+            let color = Color::hsl(240. * bucket as f32 / (BUCKETS.len() - 1) as f32, 0.8, 0.55);
+            gizmos.line_2d(Vec2::new(x, y), Vec2::new(x, y + height), color);
+            y += height;
+        }
+    }
+}
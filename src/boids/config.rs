@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use bevy::{
+    log::{error, info, warn},
+    prelude::{DetectChangesMut, Fixed, Real, Res, ResMut, Resource, Time, Timer, TimerMode, Vec2, Virtual},
+};
+use ron::{extensions::Extensions, Options};
+use serde::{de::Error, Deserialize, Deserializer};
+
+use super::{
+    keyframes::KeyframeTracks,
+    species::{self, SpeciesDesc, SpeciesTable},
+    BoidsPlugin,
+    BoidsSettings,
+    KeyframeTrack,
+    MaxBoidCount,
+    Parameter,
+    SpawnArea,
+    SpawnStrategy,
+    TimeScale,
+    WorldBounds,
+};
+
+// how often the config file is checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The values a config file sets, each `None` or empty when left out.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct Config {
+    max_boid_count: Option<u32>,
+    seed: Option<u64>,
+    tick_rate: Option<f64>,
+    time_scale: Option<f32>,
+    world_size: Option<Vec2>,
+    spawn_area: Option<SpawnArea>,
+    spawn_strategy: Option<SpawnStrategy>,
+    /// [`BoidsSettings`] fields by name
+    settings: BTreeMap<String, f32>,
+    /// In the species file format, one string per species
+    #[serde(deserialize_with = "species_lines")]
+    species: Vec<SpeciesDesc>,
+    /// `(seconds, count)` keys
+    #[serde(deserialize_with = "boid_count_track")]
+    boid_count: Option<KeyframeTrack>,
+}
+
+fn species_lines<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<SpeciesDesc>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|line| species::parse_species(line).map_err(|_| D::Error::custom(format!("invalid species `{line}`"))))
+        .collect()
+}
+
+fn boid_count_track<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<KeyframeTrack>, D::Error> {
+    let keys = Vec::<(f32, f32)>::deserialize(deserializer)?;
+    let track = keys
+        .into_iter()
+        .fold(KeyframeTrack::new(Parameter::BoidCount), |track, (seconds, count)| track.key(seconds, count));
+    Ok(Some(track))
+}
+
+impl Config {
+    pub(super) fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+        let config: Config = Options::default()
+            .with_default_extension(Extensions::IMPLICIT_SOME)
+            .from_str(&text)
+            .map_err(|err| invalid(err.to_string()))?;
+        let bad_value = |key: &str, value: &dyn std::fmt::Display| invalid(format!("invalid value `{value}` for `{key}`"));
+        if let Some(hz) = config.tick_rate.filter(|hz| !(hz.is_finite() && *hz > 0.)) {
+            return Err(bad_value("tick_rate", &hz));
+        }
+        if let Some(scale) = config.time_scale.filter(|scale| !(scale.is_finite() && *scale >= 0.)) {
+            return Err(bad_value("time_scale", &scale));
+        }
+        if let Some(size) = config.world_size.filter(|&size| !valid_size(size)) {
+            return Err(bad_value("world_size", &size));
+        }
+        let mut names = BoidsSettings::default();
+        if let Some(key) = config.settings.keys().find(|key| !names.named_values_mut().any(|(name, _)| name == *key)) {
+            return Err(invalid(format!("unknown setting `{key}`")));
+        }
+        Ok(config)
+    }
+
+    fn apply_settings(&self, settings: &mut BoidsSettings) {
+        for (name, field) in settings.named_values_mut() {
+            if let Some(&value) = self.settings.get(name) {
+                *field = value;
+            }
+        }
+    }
+}
+
+/// Both sides positive and finite.
+fn valid_size(size: Vec2) -> bool {
+    size.is_finite() && size.x > 0. && size.y > 0.
+}
+
+/// Replaces the track of the same parameter as `track` in `tracks`.
+fn replace_track(tracks: &mut Vec<KeyframeTrack>, track: &KeyframeTrack) {
+    tracks.retain(|other| other.parameter != track.parameter);
+    tracks.push(track.clone());
+}
+
+impl BoidsPlugin {
+    /// Sets up this plugin with every value `config` gives, keeping the
+    /// rest.
+    pub(super) fn apply_config(&mut self, config: &Config) {
+        if let Some(count) = config.max_boid_count {
+            self.max_boid_count = count;
+        }
+        if let Some(seed) = config.seed {
+            self.seed = seed;
+        }
+        if let Some(hz) = config.tick_rate {
+            self.tick_rate = hz;
+        }
+        if let Some(scale) = config.time_scale {
+            self.time_scale = scale;
+        }
+        if let Some(size) = config.world_size {
+            self.world_bounds = WorldBounds::fixed(size);
+        }
+        if let Some(area) = config.spawn_area {
+            self.spawn_area = area;
+        }
+        if let Some(strategy) = config.spawn_strategy {
+            self.spawn_strategy = strategy;
+        }
+        config.apply_settings(&mut self.settings);
+        if !config.species.is_empty() {
+            self.species = config.species.clone();
+        }
+        if let Some(track) = &config.boid_count {
+            replace_track(&mut self.keyframes, track);
+        }
+    }
+}
+
+/// The config file the plugin was set up from, watched for changes.
+#[derive(Resource)]
+pub(super) struct ConfigFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl ConfigFile {
+    pub(super) fn new(path: PathBuf) -> Self {
+        let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+        ConfigFile {
+            path,
+            modified,
+            timer: Timer::new(RELOAD_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Applies the config file to the running simulation whenever it is
+/// saved, so the flock can be tuned without restarting. The seed only
+/// takes effect on the next start, and species are only added or changed,
+/// never removed, since boids may still belong to them.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn reload_config(
+    mut file: ResMut<ConfigFile>,
+    time: Res<Time<Real>>,
+    mut settings: ResMut<BoidsSettings>,
+    mut table: ResMut<SpeciesTable>,
+    mut max_boid_count: ResMut<MaxBoidCount>,
+    mut scale: ResMut<TimeScale>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut fixed: ResMut<Time<Fixed>>,
+    mut area: ResMut<SpawnArea>,
+    mut strategy: ResMut<SpawnStrategy>,
+    mut bounds: ResMut<WorldBounds>,
+    tracks: Option<ResMut<KeyframeTracks>>,
+) {
+    // real time, so a paused or slowed down simulation still picks up edits
+    if !file.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(modified) = fs::metadata(&file.path).and_then(|meta| meta.modified()) else {
+        return;
+    };
+    if file.modified == Some(modified) {
+        return;
+    }
+    file.modified = Some(modified);
+    let config = match Config::load(&file.path) {
+        Ok(config) => config,
+        Err(err) => {
+            error!("failed to reload config from {}: {err}", file.path.display());
+            return;
+        }
+    };
+
+    let mut changed = *settings;
+    config.apply_settings(&mut changed);
+    settings.set_if_neq(changed);
+    if !config.species.is_empty() && config.species != table.0 {
+        if config.species.len() < table.0.len() {
+            warn!("config removes species, restart to apply the species");
+        } else {
+            table.0 = config.species.clone();
+        }
+    }
+    if let Some(count) = config.max_boid_count {
+        max_boid_count.0 = count;
+    }
+    if let Some(hz) = config.tick_rate {
+        fixed.set_timestep_hz(hz);
+    }
+    if let Some(relative) = config.time_scale {
+        scale.0 = relative;
+        virtual_time.set_relative_speed(relative);
+    }
+    if let Some(size) = config.world_size {
+        bounds.set_if_neq(WorldBounds::fixed(size));
+    }
+    if let Some(changed) = config.spawn_area {
+        area.set_if_neq(changed);
+    }
+    if let Some(changed) = config.spawn_strategy {
+        strategy.set_if_neq(changed);
+    }
+    match (&config.boid_count, tracks) {
+        (Some(track), Some(mut tracks)) => replace_track(&mut tracks.0, track),
+        (Some(_), None) => warn!("config adds a boid count track, restart to apply it"),
+        _ => {}
+    }
+    info!("reloaded config from {}", file.path.display());
+}
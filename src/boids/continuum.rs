@@ -0,0 +1,125 @@
+use bevy::{
+    prelude::{
+        default,
+        Assets,
+        Color,
+        Commands,
+        Component,
+        Gizmos,
+        Handle,
+        Image,
+        Query,
+        Res,
+        ResMut,
+        Sprite,
+        SpriteBundle,
+        Transform,
+        Vec2,
+        Visibility,
+        With,
+    },
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+
+use super::{
+    grid::SpatialGrid,
+    lod::{BoidLod, LodLevel},
+    BoidsSettings,
+    Velocity,
+};
+
+// largest texture side, in grid cells, the field is drawn into
+const MAX_SIDE: u32 = 1024;
+const DENSITY_COLOR: [f32; 3] = [0.55, 0.8, 1.];
+// velocity arrows reach this far across a cell at max speed
+const ARROW_REACH: f32 = 0.45;
+
+/// Sprite showing how many boids are in each cell of the spatial grid.
+#[derive(Component)]
+pub(super) struct ContinuumView;
+
+pub(super) fn setup_continuum(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = Image::new_fill(
+        Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    commands.spawn((
+        ContinuumView,
+        SpriteBundle {
+            texture: images.add(image),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+/// Once the boids are hidden at the field level of detail, draws the flock
+/// as a continuum instead: brightness for the density in each grid cell
+/// and an arrow for the cell's mean velocity, so very large or far away
+/// flocks still read as flows rather than noise.
+pub(super) fn update_continuum(
+    lod: Res<BoidLod>,
+    grid: Res<SpatialGrid>,
+    settings: Res<BoidsSettings>,
+    velocities: Query<&Velocity>,
+    mut view: Query<(&mut Visibility, &mut Sprite, &mut Transform, &Handle<Image>), With<ContinuumView>>,
+    mut images: ResMut<Assets<Image>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((mut visibility, mut sprite, mut transform, handle)) = view.get_single_mut() else {
+        return;
+    };
+    let extent = grid.extent();
+    let size = extent.map(|(min, max)| (max - min).as_uvec2() + 1);
+    let (Some((min, _)), Some(size), LodLevel::Field) = (extent, size, lod.level()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    if size.max_element() > MAX_SIDE {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    let Some(image) = images.get_mut(handle) else {
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    let cell_size = grid.cell_size();
+    sprite.custom_size = Some(size.as_vec2() * cell_size);
+    transform.translation = (min.as_vec2() * cell_size + size.as_vec2() * cell_size / 2.).extend(-1.);
+    if image.width() != size.x || image.height() != size.y {
+        image.resize(Extent3d { width: size.x, height: size.y, depth_or_array_layers: 1 });
+    }
+    image.data.fill(0);
+
+    let densest = grid.cells().map(|(_, boids)| boids.len()).max().unwrap_or(1) as f32;
+    for (cell, boids) in grid.cells() {
+        let offset = (cell - min).as_uvec2();
+        // image rows run top to bottom, grid rows bottom to top
+        let pixel = ((size.y - 1 - offset.y) * size.x + offset.x) as usize * 4;
+        // square root so sparse cells still show up next to dense ones
+        let density = (boids.len() as f32 / densest).sqrt();
+        image.data[pixel..pixel + 4].copy_from_slice(&[
+            (255. * DENSITY_COLOR[0]) as u8,
+            (255. * DENSITY_COLOR[1]) as u8,
+            (255. * DENSITY_COLOR[2]) as u8,
+            (255. * density) as u8,
+        ]);
+
+        let velocity = boids
+            .iter()
+            .filter_map(|&(boid, _)| velocities.get(boid).ok())
+            .map(|vel| vel.0)
+            .sum::<Vec2>()
+            / boids.len() as f32;
+        let center = (cell.as_vec2() + 0.5) * cell_size;
+        let reach = velocity / settings.max_speed.max(f32::EPSILON) * cell_size * ARROW_REACH;
+        gizmos.arrow_2d(center, center + reach.clamp_length_max(cell_size * ARROW_REACH), Color::WHITE);
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::{Resource, Vec2};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos, Res};
+
+use super::{Boid, Position, Velocity};
+
+// samples per control point span when flattening splines
+const SPLINE_SAMPLES: usize = 8;
+#[cfg(feature = "render")]
+const ARROW_LENGTH: f32 = 20.;
+
+/// Lane boids are nudged along while inside it, authored as a polyline.
+#[derive(Clone, Debug)]
+pub struct Corridor {
+    pub points: Vec<Vec2>,
+    pub width: f32,
+    pub strength: f32,
+}
+
+impl Corridor {
+    pub fn new(points: Vec<Vec2>, width: f32, strength: f32) -> Self {
+        Corridor { points, width, strength }
+    }
+
+    /// Corridor following a Catmull-Rom spline through `control_points`.
+    pub fn spline(control_points: &[Vec2], width: f32, strength: f32) -> Self {
+        let n = control_points.len();
+        if n < 3 {
+            return Corridor::new(control_points.to_vec(), width, strength);
+        }
+        let at = |i: isize| control_points[i.clamp(0, n as isize - 1) as usize];
+        let mut points = Vec::with_capacity((n - 1) * SPLINE_SAMPLES + 1);
+        for i in 0..n as isize - 1 {
+            let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+            for step in 0..SPLINE_SAMPLES {
+                let t = step as f32 / SPLINE_SAMPLES as f32;
+                let (t2, t3) = (t * t, t * t * t);
+                points.push(
+                    (p1 * 2.
+                        + (p2 - p0) * t
+                        + (p0 * 2. - p1 * 5. + p2 * 4. - p3) * t2
+                        + (p1 * 3. - p0 - p2 * 3. + p3) * t3)
+                        * 0.5,
+                );
+            }
+        }
+        points.push(control_points[n - 1]);
+        Corridor::new(points, width, strength)
+    }
+
+    /// Lane direction at `position`, scaled from 1 on the centre line down
+    /// to 0 at the corridor's edge, or `None` outside the corridor.
+    fn direction_at(&self, position: Vec2) -> Option<Vec2> {
+        let half_width = self.width / 2.;
+        let mut best: Option<(f32, Vec2)> = None;
+        for segment in self.points.windows(2) {
+            let (a, b) = (segment[0], segment[1]);
+            let ab = b - a;
+            let t = ((position - a).dot(ab) / ab.length_squared().max(f32::EPSILON)).clamp(0., 1.);
+            let distance = position.distance(a + ab * t);
+            if distance < half_width && best.is_none_or(|(d, _)| distance < d) {
+                best = Some((distance, ab.normalize_or_zero()));
+            }
+        }
+        best.map(|(distance, direction)| direction * (1. - distance / half_width))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Corridors(pub Vec<Corridor>);
+
+impl Corridors {
+    pub(super) fn steer(&self, boid: &Boid, position: &Position, velocity: &Velocity) -> Vec2 {
+        let mut steer = Vec2::ZERO;
+        for corridor in self.0.iter() {
+            if let Some(direction) = corridor.direction_at(position.0) {
+                let desired = direction.normalize_or_zero() * boid.max_speed;
+                steer += (desired - velocity.0).clamp_length_max(boid.max_force)
+                    * corridor.strength
+                    * direction.length();
+            }
+        }
+        steer
+    }
+}
+
+#[cfg(feature = "render")]
+pub(super) fn draw_corridors(mut gizmos: Gizmos, corridors: Res<Corridors>) {
+    let color = Color::srgba(0.4, 0.8, 1.0, 0.25);
+    for corridor in corridors.0.iter() {
+        gizmos.linestrip_2d(corridor.points.iter().copied(), color);
+        for segment in corridor.points.windows(2).step_by(SPLINE_SAMPLES) {
+            let mid = (segment[0] + segment[1]) / 2.;
+            let direction = (segment[1] - segment[0]).normalize_or_zero();
+            gizmos.arrow_2d(mid - direction * ARROW_LENGTH / 2., mid + direction * ARROW_LENGTH / 2., color);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    input::ButtonInput,
+    log::info,
+    prelude::{Color, Gizmos, IntoSystemConfigs, KeyCode, Query, Res, ResMut, Resource, Vec2, With},
+};
+
+use super::{grid::SpatialGrid, Boid, BoidsSettings, PerceptionMetric, Position, Velocity, WorldBounds};
+
+// velocity vectors are drawn this many seconds of flight long
+const VELOCITY_SCALE: f32 = 0.25;
+
+/// Debug overlay drawn over the flock with gizmos, one layer per key:
+/// F1 each boid's velocity, Q its neighbour and separation radii, Z lines
+/// to the boids it flocks with and 0 the cells of the spatial grid, occupied
+/// ones shaded by how many boids they hold.
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugLayers>()
+            .add_systems(Update, (debug_input, draw_debug_overlay).chain());
+    }
+}
+
+/// Which layers of the [`DebugOverlayPlugin`] are drawn, all off at first.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct DebugLayers {
+    pub velocity: bool,
+    /// Neighbour and separation radius around each boid, in the shape the
+    /// [`PerceptionMetric`] gives them
+    pub radii: bool,
+    /// Lines from each boid to the boids within its neighbour radius and
+    /// field of view. Neighbours across a wrapping edge are left out.
+    pub neighbours: bool,
+    pub grid: bool,
+}
+
+fn debug_input(keys: Res<ButtonInput<KeyCode>>, mut layers: ResMut<DebugLayers>) {
+    let layers = &mut *layers;
+    let toggles = [
+        (KeyCode::F1, "velocity", &mut layers.velocity),
+        (KeyCode::KeyQ, "radii", &mut layers.radii),
+        (KeyCode::KeyZ, "neighbours", &mut layers.neighbours),
+        (KeyCode::Digit0, "grid", &mut layers.grid),
+    ];
+    for (key, name, shown) in toggles {
+        if keys.just_pressed(key) {
+            *shown = !*shown;
+            info!("debug {name}: {}", if *shown { "shown" } else { "hidden" });
+        }
+    }
+}
+
+/// Half size of the region a boid flying along `heading` perceives as
+/// closer than `radius`, along and across its heading.
+fn perceived_half_size(metric: PerceptionMetric, radius: f32, heading: Vec2) -> Vec2 {
+    match metric {
+        PerceptionMetric::Elliptical { aspect } if aspect > 0. && heading != Vec2::ZERO => {
+            Vec2::new(radius * aspect.sqrt(), radius / aspect.sqrt())
+        }
+        _ => Vec2::splat(radius),
+    }
+}
+
+fn draw_debug_overlay(
+    mut gizmos: Gizmos,
+    layers: Res<DebugLayers>,
+    boids: Query<(&Position, &Velocity), With<Boid>>,
+    grid: Res<SpatialGrid>,
+    bounds: Res<WorldBounds>,
+    metric: Res<PerceptionMetric>,
+    settings: Res<BoidsSettings>,
+) {
+    if layers.grid {
+        // the lattice over the world, with occupied cells outlined
+        let size = grid.cell_size();
+        let min = (-bounds.half_extents / size).floor() * size;
+        let max = (bounds.half_extents / size).ceil() * size;
+        let faint = Color::srgba(1., 1., 1., 0.08);
+        let mut x = min.x;
+        while x <= max.x {
+            gizmos.line_2d(Vec2::new(x, min.y), Vec2::new(x, max.y), faint);
+            x += size;
+        }
+        let mut y = min.y;
+        while y <= max.y {
+            gizmos.line_2d(Vec2::new(min.x, y), Vec2::new(max.x, y), faint);
+            y += size;
+        }
+        // brighter the fuller the cell is next to the fullest one
+        let fullest = grid.cells().map(|(_, boids)| boids.len()).max().unwrap_or(1);
+        for (cell, boids) in grid.cells() {
+            let center = (cell.as_vec2() + 0.5) * size;
+            let fill = boids.len() as f32 / fullest as f32;
+            gizmos.rect_2d(center, 0., Vec2::splat(size * 0.96), Color::srgba(1., 0.7, 0.2, 0.15 + 0.65 * fill));
+        }
+    }
+    if !(layers.velocity || layers.radii || layers.neighbours) {
+        return;
+    }
+    for (pos, vel) in boids.iter() {
+        let heading = vel.0.normalize_or_zero();
+        if layers.velocity {
+            gizmos.arrow_2d(pos.0, pos.0 + vel.0 * VELOCITY_SCALE, Color::srgb(0.3, 1., 0.4));
+        }
+        if layers.radii {
+            let angle = heading.to_angle();
+            let neighbour = perceived_half_size(*metric, settings.neighbour_radius, heading);
+            let separation = perceived_half_size(*metric, settings.desired_separation, heading);
+            gizmos.ellipse_2d(pos.0, angle, neighbour, Color::srgba(0.4, 0.6, 1., 0.3));
+            gizmos.ellipse_2d(pos.0, angle, separation, Color::srgba(1., 0.4, 0.4, 0.4));
+        }
+        if layers.neighbours {
+            let reach = metric.reach(settings.neighbour_radius);
+            for (_, other) in grid.neighbours(pos.0, reach) {
+                let distance = metric.distance(other - pos.0, vel.0);
+                if distance > 0. && distance < settings.neighbour_radius && settings.in_view(pos.0, vel.0, other) {
+                    gizmos.line_2d(pos.0, other, Color::srgba(1., 0.9, 0.3, 0.2));
+                }
+            }
+        }
+    }
+}
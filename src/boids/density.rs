@@ -0,0 +1,174 @@
+use bevy::{
+    input::ButtonInput,
+    log::info,
+    prelude::{Component, KeyCode, Res, ResMut, Resource},
+};
+#[cfg(feature = "render")]
+use bevy::{
+    prelude::{Assets, Color, DetectChanges, FromWorld, Handle, LinearRgba, Query, With, World},
+    sprite::ColorMaterial,
+};
+
+#[cfg(feature = "render")]
+use super::{
+    palette::PaletteMaterial,
+    render::{BoidMaterial, SpeciesMaterials},
+    species::{Species, SpeciesTable},
+    Boid,
+    Heading,
+    Velocity,
+};
+
+// boids with this many neighbours or more are drawn darkest
+#[cfg(feature = "render")]
+const CROWDED: u32 = 20;
+// steps between full brightness and the darkest shade
+#[cfg(feature = "render")]
+const SHADES: usize = 8;
+// brightness of the most crowded or slowest boids
+#[cfg(feature = "render")]
+const DARKEST: f32 = 0.25;
+// steps around the colour wheel boids are coloured by heading in
+#[cfg(feature = "render")]
+const HUES: usize = 36;
+
+/// Neighbours inside the neighbour radius on the last tick, as counted
+/// while aligning with them.
+#[derive(Component, Default)]
+pub(super) struct Crowding(pub(super) u32);
+
+/// How boids are shaded, recomputed every frame. U cycles through the
+/// modes.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoidShading {
+    /// Each boid in its own material
+    #[default]
+    Off,
+    /// Darker the more neighbours a boid has, so dense clumps show up dark
+    /// in the middle
+    Density,
+    /// Hue by heading, so boids flying the same way share a colour
+    /// whatever their species
+    Heading,
+    /// Darker the slower a boid flies next to its max speed
+    Speed,
+}
+
+impl BoidShading {
+    fn next(self) -> Self {
+        match self {
+            BoidShading::Off => BoidShading::Density,
+            BoidShading::Density => BoidShading::Heading,
+            BoidShading::Heading => BoidShading::Speed,
+            BoidShading::Speed => BoidShading::Off,
+        }
+    }
+}
+
+/// U cycles the shading modes.
+pub(super) fn shading_input(keys: Res<ButtonInput<KeyCode>>, mut shading: ResMut<BoidShading>) {
+    if keys.just_pressed(KeyCode::KeyU) {
+        *shading = shading.next();
+        info!("shading: {shading:?}");
+    }
+}
+
+/// Each species' colour at every shade, by index into the species table.
+#[cfg(feature = "render")]
+#[derive(Resource, Default)]
+pub(super) struct ShadedMaterials(Vec<[Handle<ColorMaterial>; SHADES]>);
+
+/// One material per step around the colour wheel, from heading along +x
+/// counterclockwise.
+#[cfg(feature = "render")]
+#[derive(Resource)]
+pub(super) struct HueMaterials([Handle<ColorMaterial>; HUES]);
+
+#[cfg(feature = "render")]
+impl FromWorld for HueMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        HueMaterials(std::array::from_fn(|hue| {
+            materials.add(Color::hsl(hue as f32 * 360. / HUES as f32, 0.8, 0.6))
+        }))
+    }
+}
+
+#[cfg(feature = "render")]
+fn shaded(color: Color, shade: usize) -> Color {
+    let brightness = 1. - (1. - DARKEST) * shade as f32 / (SHADES - 1) as f32;
+    let linear = color.to_linear();
+    LinearRgba::new(linear.red * brightness, linear.green * brightness, linear.blue * brightness, linear.alpha).into()
+}
+
+/// Swaps each boid's material for the one the shading mode picks for it,
+/// and back to the boid's own material when shading is turned off. Boids
+/// drawn with a [`BoidMaterial`] are left as they are.
+#[cfg(feature = "render")]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn shade_boids(
+    shading: Res<BoidShading>,
+    custom: Res<BoidMaterial>,
+    table: Res<SpeciesTable>,
+    species_materials: Res<SpeciesMaterials>,
+    mut shaded_materials: ResMut<ShadedMaterials>,
+    hue_materials: Res<HueMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut boids: Query<(
+        &Crowding,
+        &Heading,
+        &Velocity,
+        &Boid,
+        &Species,
+        Option<&PaletteMaterial>,
+        &mut Handle<ColorMaterial>
+    ), With<Boid>>,
+) {
+    if table.is_changed() {
+        for (index, desc) in table.0.iter().enumerate() {
+            match shaded_materials.0.get(index) {
+                Some(handles) => {
+                    for (shade, handle) in handles.iter().enumerate() {
+                        if let Some(material) = materials.get_mut(handle) {
+                            material.color = shaded(desc.color, shade);
+                        }
+                    }
+                }
+                None => {
+                    let handles = std::array::from_fn(|shade| materials.add(shaded(desc.color, shade)));
+                    shaded_materials.0.push(handles);
+                }
+            }
+        }
+    }
+
+    if custom.0.is_some() {
+        return;
+    }
+    if *shading == BoidShading::Off {
+        if shading.is_changed() {
+            for (_, _, _, _, species, own, mut material) in boids.iter_mut() {
+                *material = species_materials.boid_material(&custom, own, *species);
+            }
+        }
+        return;
+    }
+    for (crowding, heading, vel, boid, species, _, mut material) in boids.iter_mut() {
+        let shades = &shaded_materials.0[species.0 as usize];
+        let handle = match *shading {
+            BoidShading::Density => &shades[crowding.0.min(CROWDED) as usize * (SHADES - 1) / CROWDED as usize],
+            BoidShading::Heading => {
+                let turn = heading.0.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+                &hue_materials.0[(turn * HUES as f32).round() as usize % HUES]
+            }
+            BoidShading::Speed => {
+                let slowness = 1. - (vel.0.length() / boid.max_speed.max(f32::EPSILON)).clamp(0., 1.);
+                &shades[(slowness * (SHADES - 1) as f32).round() as usize]
+            }
+            BoidShading::Off => unreachable!(),
+        };
+        if *material != *handle {
+            *material = handle.clone();
+        }
+    }
+}
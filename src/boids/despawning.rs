@@ -0,0 +1,31 @@
+use bevy::prelude::{Commands, Component, Entity, Query, Res, Time, Timer};
+#[cfg(feature = "render")]
+use bevy::prelude::{Transform, With};
+
+/// A boid on its way out. It has already left the boid list, so it no
+/// longer affects its neighbours, and coasts while shrinking away until
+/// the timer runs out and it is despawned.
+#[derive(Component)]
+pub(super) struct Despawning(pub(super) Timer);
+
+pub(super) fn finish_despawning(
+    mut commands: Commands,
+    mut despawning: Query<(Entity, &mut Despawning)>,
+    time: Res<Time>,
+) {
+    for (entity, mut despawning) in despawning.iter_mut() {
+        if despawning.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Shrinks despawning boids to nothing over their remaining time.
+#[cfg(feature = "render")]
+pub(super) fn shrink_despawning(mut boids: Query<(&Despawning, &mut Transform), With<super::Boid>>) {
+    for (despawning, mut transform) in boids.iter_mut() {
+        let size = despawning.0.fraction_remaining();
+        transform.scale.x *= size;
+        transform.scale.y *= size;
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+use bevy::{
+    input::ButtonInput,
+    prelude::{
+        Commands,
+        Component,
+        Entity,
+        Event,
+        EventReader,
+        EventWriter,
+        KeyCode,
+        MouseButton,
+        Query,
+        Res,
+        ResMut,
+        Resource,
+        Time,
+        Timer,
+        TimerMode,
+        Vec2,
+        With,
+    },
+};
+
+use crate::tools::{ActiveTool, CursorWorld};
+
+use super::{Boid, Position};
+
+// seconds a disturbed boid stays panicked
+const PANIC_SECONDS: f32 = 2.;
+const CLICK_RADIUS: f32 = 150.;
+const CLICK_STRENGTH: f32 = 2.;
+
+/// Something that startles boids within `radius` of `position`: sleeping
+/// boids wake and take off, and every boid in range flees from it with
+/// `strength` times the usual flee force, fading over a couple of seconds.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Disturbance {
+    pub position: Vec2,
+    pub radius: f32,
+    pub strength: f32,
+}
+
+/// Disturbances a scenario fires at fixed times, in seconds since startup.
+#[derive(Resource, Default)]
+pub(super) struct ScheduledDisturbances(pub(super) Vec<(f32, Disturbance)>);
+
+/// A boid fleeing from a disturbance.
+#[derive(Component)]
+pub(super) struct Panic {
+    pub(super) origin: Vec2,
+    pub(super) strength: f32,
+    timer: Timer,
+}
+
+impl Panic {
+    /// Flee weight, fading out as the panic wears off.
+    pub(super) fn weight(&self) -> f32 {
+        self.strength * (1. - self.timer.fraction())
+    }
+}
+
+pub(super) fn fire_scheduled_disturbances(
+    mut scheduled: ResMut<ScheduledDisturbances>,
+    mut disturbances: EventWriter<Disturbance>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    scheduled.0.retain(|&(at, disturbance)| {
+        if at > now {
+            return true;
+        }
+        disturbances.send(disturbance);
+        false
+    });
+}
+
+/// X toggles the disturb tool. Left click startles the boids around the cursor.
+pub(super) fn disturb_on_click(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut disturbances: EventWriter<Disturbance>,
+) {
+    if keys.just_pressed(KeyCode::KeyX) {
+        tool.toggle(ActiveTool::Disturb);
+    }
+    if *tool != ActiveTool::Disturb || !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if let Some(position) = cursor.0 {
+        disturbances.send(Disturbance {
+            position,
+            radius: CLICK_RADIUS,
+            strength: CLICK_STRENGTH,
+        });
+    }
+}
+
+pub(super) fn panic_on_disturbance(
+    mut commands: Commands,
+    mut disturbances: EventReader<Disturbance>,
+    boids: Query<(Entity, &Position), With<Boid>>,
+) {
+    for disturbance in disturbances.read() {
+        for (entity, pos) in boids.iter() {
+            if pos.0.distance(disturbance.position) < disturbance.radius {
+                commands.entity(entity).try_insert(Panic {
+                    origin: disturbance.position,
+                    strength: disturbance.strength,
+                    timer: Timer::new(Duration::from_secs_f32(PANIC_SECONDS), TimerMode::Once),
+                });
+            }
+        }
+    }
+}
+
+pub(super) fn calm_down(
+    mut commands: Commands,
+    mut panicked: Query<(Entity, &mut Panic)>,
+    time: Res<Time>,
+) {
+    for (entity, mut panic) in panicked.iter_mut() {
+        if panic.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Panic>();
+        }
+    }
+}
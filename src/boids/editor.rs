@@ -0,0 +1,149 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    input::ButtonInput,
+    log::{error, info},
+    prelude::{Color, KeyCode, Local, Query, Res, ResMut},
+};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use rand::seq::SliceRandom;
+
+use super::{
+    species::{ForceMixing, Species, SpeciesFile, SpeciesTable},
+    Boids,
+    RandomGenerator,
+};
+
+// most boids a single click converts
+const MAX_CONVERT: u32 = 500;
+
+/// Window (F7) for creating and editing species while the simulation runs,
+/// converting random boids to them and saving the table to the species
+/// file.
+pub struct SpeciesEditorPlugin;
+
+impl Plugin for SpeciesEditorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.add_systems(Update, species_editor);
+    }
+}
+
+struct EditorState {
+    open: bool,
+    selected: usize,
+    /// How many boids the convert button moves to the selected species
+    convert: u32,
+}
+
+impl Default for EditorState {
+    fn default() -> Self {
+        EditorState {
+            open: false,
+            selected: 0,
+            convert: 20,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn species_editor(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: Local<EditorState>,
+    mut table: ResMut<SpeciesTable>,
+    file: Res<SpeciesFile>,
+    boids: Res<Boids>,
+    mut rng: ResMut<RandomGenerator>,
+    mut kinds: Query<&mut Species>,
+) {
+    if keys.just_pressed(KeyCode::F7) {
+        state.open = !state.open;
+    }
+    if !state.open {
+        return;
+    }
+
+    // edit a copy so the table is only marked changed when something was
+    let mut species = table.0.clone();
+    let mut convert = false;
+    let mut save = false;
+    let state = &mut *state;
+    state.selected = state.selected.min(species.len() - 1);
+    egui::Window::new("Species").open(&mut state.open).show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            for index in 0..species.len() {
+                ui.selectable_value(&mut state.selected, index, format!("#{index}"));
+            }
+            if ui.button("+").on_hover_text("copy the selected species").clicked() {
+                species.push(species[state.selected].clone());
+                state.selected = species.len() - 1;
+            }
+        });
+        ui.separator();
+
+        let count = species.len();
+        let desc = &mut species[state.selected];
+        egui::ComboBox::from_label("mixing")
+            .selected_text(format!("{:?}", desc.mixing))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut desc.mixing, ForceMixing::WeightedSum, "WeightedSum");
+                ui.selectable_value(&mut desc.mixing, ForceMixing::Prioritized, "Prioritized");
+            });
+        ui.horizontal(|ui| {
+            let color = desc.color.to_srgba();
+            let mut rgb = [color.red, color.green, color.blue];
+            // only write back edits, round tripping would mark the table changed
+            if ui.color_edit_button_rgb(&mut rgb).changed() {
+                desc.color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+            }
+            ui.label("colour");
+        });
+        ui.add(egui::Slider::new(&mut desc.size, 0.25..=4.).text("size"));
+        ui.add(egui::Slider::new(&mut desc.mass, 0.1..=10.).logarithmic(true).text("mass"));
+        ui.add(egui::Slider::new(&mut desc.speed, 0.1..=3.).text("speed"));
+        ui.add(egui::Slider::new(&mut desc.layer, -5..=5).text("draw layer"));
+        for (weight, name) in desc.weights.iter_mut().zip(["separation", "alignment", "cohesion"]) {
+            ui.add(egui::Slider::new(weight, 0.0..=3.).text(name));
+        }
+
+        ui.separator();
+        ui.label("affinity towards");
+        for other in 0..count {
+            let mut affinity = desc.affinity(Species(other as u32));
+            let slider = egui::Slider::new(&mut affinity, 0.0..=2.).text(format!("#{other}"));
+            if ui.add(slider).changed() {
+                if desc.affinity.len() <= other {
+                    desc.affinity.resize(other + 1, 1.);
+                }
+                desc.affinity[other] = affinity;
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut state.convert).range(1..=MAX_CONVERT));
+            convert = ui.button("convert random boids").clicked();
+        });
+        save = ui.button(format!("save to {}", file.0.display())).clicked();
+    });
+
+    if species != table.0 {
+        table.0 = species;
+    }
+    if convert {
+        let selected = Species(state.selected as u32);
+        for &entity in boids.0.choose_multiple(&mut rng.rng, state.convert as usize) {
+            if let Ok(mut kind) = kinds.get_mut(entity) {
+                *kind = selected;
+            }
+        }
+    }
+    if save {
+        match table.save(&file.0) {
+            Ok(()) => info!("saved species to {}", file.0.display()),
+            Err(err) => error!("failed to save species to {}: {err}", file.0.display()),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::{Res, ResMut, Resource, Time, Vec2};
+
+use super::{BoidSpawner, Edge, MaxBoidCount, MAX_SPEED};
+
+/// Continuously emits boids from one edge of the world. Used together with
+/// [`super::BoundaryMode::Despawn`] so boids flow through the world and
+/// leave at the far side.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct EdgeEmitter {
+    pub edge: Edge,
+    /// Direction emitted boids fly in
+    pub heading: Vec2,
+    /// Boids per second
+    pub rate: f32,
+    pub speed: f32,
+    accumulator: f32,
+}
+
+impl EdgeEmitter {
+    pub fn new(edge: Edge, rate: f32) -> Self {
+        EdgeEmitter {
+            edge,
+            heading: edge.inward(),
+            rate,
+            speed: MAX_SPEED / 2.,
+            accumulator: 0.,
+        }
+    }
+}
+
+pub(super) fn emit_boids(
+    mut emitter: ResMut<EdgeEmitter>,
+    mut spawner: BoidSpawner,
+    max_boid_count: Res<MaxBoidCount>,
+    time: Res<Time>,
+) {
+    emitter.accumulator += emitter.rate * time.delta_seconds();
+    while emitter.accumulator >= 1. {
+        emitter.accumulator -= 1.;
+        if spawner.boids.0.len() as u32 >= max_boid_count.0 {
+            continue;
+        }
+        let t = spawner.rng.random_f32(-1.0..1.0);
+        spawner.spawn_at_edge(emitter.edge, t, emitter.heading.normalize_or_zero() * emitter.speed, None);
+    }
+}
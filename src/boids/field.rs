@@ -0,0 +1,99 @@
+use std::ops::{Add, Mul};
+use bevy::prelude::{UVec2, Vec2};
+
+/// Regular grid of values covering a rectangle centred on the origin.
+#[derive(Clone, Debug)]
+pub struct FieldGrid<T> {
+    size: UVec2,
+    cell_size: f32,
+    cells: Vec<T>,
+}
+
+impl<T: Copy + Default> FieldGrid<T> {
+    pub fn new(size: UVec2, cell_size: f32) -> Self {
+        FieldGrid {
+            size,
+            cell_size,
+            cells: vec![T::default(); (size.x * size.y) as usize],
+        }
+    }
+
+    pub fn from_fn(size: UVec2, cell_size: f32, f: impl Fn(UVec2) -> T) -> Self {
+        let mut grid = FieldGrid::new(size, cell_size);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                grid.set(UVec2::new(x, y), f(UVec2::new(x, y)));
+            }
+        }
+        grid
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn half_extents(&self) -> Vec2 {
+        self.size.as_vec2() * self.cell_size / 2.
+    }
+
+    pub fn get(&self, cell: UVec2) -> T {
+        self.cells[self.index(cell)]
+    }
+
+    pub fn set(&mut self, cell: UVec2, value: T) {
+        let index = self.index(cell);
+        self.cells[index] = value;
+    }
+
+    pub fn cell_center(&self, cell: UVec2) -> Vec2 {
+        (cell.as_vec2() + 0.5) * self.cell_size - self.half_extents()
+    }
+
+    /// Applies `f` to every cell within `radius` of `center`, passing a
+    /// weight that falls off linearly from 1 at the centre to 0 at the rim.
+    pub fn paint(&mut self, center: Vec2, radius: f32, mut f: impl FnMut(&mut T, f32)) {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let cell = UVec2::new(x, y);
+                let distance = self.cell_center(cell).distance(center);
+                if distance < radius {
+                    let index = self.index(cell);
+                    f(&mut self.cells[index], 1. - distance / radius);
+                }
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (UVec2, T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (UVec2::new(i as u32 % self.size.x, i as u32 / self.size.x), *value))
+    }
+
+    fn index(&self, cell: UVec2) -> usize {
+        (cell.y * self.size.x + cell.x) as usize
+    }
+}
+
+impl<T> FieldGrid<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<f32, Output = T>,
+{
+    /// Bilinearly interpolated value at `position`, clamped to the grid edges.
+    pub fn sample_bilinear(&self, position: Vec2) -> T {
+        let max = (self.size - 1).as_vec2();
+        let p = ((position + self.half_extents()) / self.cell_size - 0.5).clamp(Vec2::ZERO, max);
+        let p0 = p.floor();
+        let p1 = (p0 + 1.).min(max);
+        let t = p - p0;
+        let at = |x: f32, y: f32| self.get(UVec2::new(x as u32, y as u32));
+        let bottom = at(p0.x, p0.y) * (1. - t.x) + at(p1.x, p0.y) * t.x;
+        let top = at(p0.x, p1.y) * (1. - t.x) + at(p1.x, p1.y) * t.x;
+        bottom * (1. - t.y) + top * t.y
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::prelude::{Added, Commands, Component, Entity, Query, RemovedComponents, Res, Resource, Time, Timer, TimerMode};
+#[cfg(feature = "render")]
+use bevy::{
+    prelude::{Assets, Color, FromWorld, Handle, With, Without, World},
+    sprite::ColorMaterial,
+};
+
+use super::{disturbance::Panic, roosting::Dormant, signals::Inbox, Boid};
+#[cfg(feature = "render")]
+use super::{
+    palette::PaletteMaterial,
+    render::{BoidMaterial, SpeciesMaterials},
+    species::Species,
+};
+
+// seconds a boid stays lit up after an event
+const FLASH_DURATION: f32 = 0.3;
+
+/// Present when boids light up as things happen to them.
+#[derive(Resource)]
+pub(super) struct EventFlashes;
+
+/// A boid lit up for a moment because something happened to it: it
+/// spawned, got caught, received a signal, or perched, woke up or panicked.
+/// Drawn bright until the timer runs out.
+#[derive(Component)]
+pub(super) struct Flash(Timer);
+
+impl Flash {
+    pub(super) fn new() -> Self {
+        Flash(Timer::from_seconds(FLASH_DURATION, TimerMode::Once))
+    }
+}
+
+/// Lights up boids that spawned, were signalled, or perched, woke up or
+/// panicked since the last tick. Caught boids are lit up as they are
+/// caught.
+pub(super) fn flash_on_events(
+    mut commands: Commands,
+    spawned: Query<Entity, Added<Boid>>,
+    signalled: Query<(Entity, &Inbox)>,
+    perched: Query<Entity, Added<Dormant>>,
+    panicked: Query<Entity, Added<Panic>>,
+    mut woke: RemovedComponents<Dormant>,
+) {
+    let signalled = signalled.iter().filter(|(_, inbox)| !inbox.0.is_empty()).map(|(entity, _)| entity);
+    let events: Vec<Entity> = spawned
+        .iter()
+        .chain(signalled)
+        .chain(perched.iter())
+        .chain(panicked.iter())
+        .chain(woke.read())
+        .collect();
+    for entity in events {
+        if let Some(mut boid) = commands.get_entity(entity) {
+            boid.try_insert(Flash::new());
+        }
+    }
+}
+
+pub(super) fn fade_flashes(mut commands: Commands, time: Res<Time>, mut flashes: Query<(Entity, &mut Flash)>) {
+    for (entity, mut flash) in flashes.iter_mut() {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Flash>();
+        }
+    }
+}
+
+/// Material lit up boids are drawn with.
+#[cfg(feature = "render")]
+#[derive(Resource)]
+pub(super) struct FlashMaterial(Handle<ColorMaterial>);
+
+#[cfg(feature = "render")]
+impl FromWorld for FlashMaterial {
+    fn from_world(world: &mut World) -> Self {
+        FlashMaterial(world.resource_mut::<Assets<ColorMaterial>>().add(Color::WHITE))
+    }
+}
+
+/// Draws lit up boids with the flash material, over whatever material
+/// they had, and gives boids whose flash ended their own material back.
+#[cfg(feature = "render")]
+#[allow(clippy::type_complexity)]
+pub(super) fn show_flashes(
+    flash: Res<FlashMaterial>,
+    custom: Res<BoidMaterial>,
+    species_materials: Res<SpeciesMaterials>,
+    mut flashing: Query<&mut Handle<ColorMaterial>, (With<Flash>, With<Boid>)>,
+    mut ended: RemovedComponents<Flash>,
+    mut unlit: Query<(&Species, Option<&PaletteMaterial>, &mut Handle<ColorMaterial>), (With<Boid>, Without<Flash>)>,
+) {
+    for mut material in flashing.iter_mut() {
+        if *material != flash.0 {
+            *material = flash.0.clone();
+        }
+    }
+    for entity in ended.read() {
+        if let Ok((species, own, mut material)) = unlit.get_mut(entity) {
+            *material = species_materials.boid_material(&custom, own, *species);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::{Component, Resource};
+
+use super::SpeciesDesc;
+
+/// Flock a boid belongs to. Boids only align and cohere with their own
+/// flock, but keep apart from every boid.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FlockId(pub u32);
+
+/// A flock of `count` boids, drawn and steered according to `species`.
+#[derive(Clone, Debug, Default)]
+pub struct FlockDesc {
+    pub count: u32,
+    pub species: SpeciesDesc,
+}
+
+/// Boid count of each flock, in order. Flock `n` uses species `n`.
+#[derive(Resource, Default)]
+pub(super) struct Flocks(pub(super) Vec<u32>);
+
+impl Flocks {
+    /// Flock the `index`th spawned boid joins, `None` without flocks. The
+    /// first boids fill the first flock, then the next, wrapping around
+    /// once every flock is full.
+    pub(super) fn assign(&self, index: u32) -> Option<FlockId> {
+        let total: u32 = self.0.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let mut index = index % total;
+        for (flock, &count) in self.0.iter().enumerate() {
+            if index < count {
+                return Some(FlockId(flock as u32));
+            }
+            index -= count;
+        }
+        None
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+use bevy::{
+    input::ButtonInput,
+    log::{error, info},
+    prelude::{KeyCode, Local, MouseButton, Res, ResMut, Resource, Time, UVec2, Vec2},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use crate::tools::{ActiveTool, CursorWorld};
+use super::{field::FieldGrid, Boid, Position, Velocity};
+
+const GRID_SIZE: UVec2 = UVec2::new(64, 40);
+const CELL_SIZE: f32 = 25.;
+
+const BRUSH_RADIUS: f32 = 60.;
+// how far a painted cell moves towards the drag direction per unit of
+// cursor travel at the brush centre
+const BRUSH_HARDNESS: f32 = 0.02;
+// fraction of a cell's vector the eraser removes per second at the brush centre
+const ERASE_RATE: f32 = 3.;
+// vectors shorter than this are treated as no flow
+const MIN_FLOW: f32 = 0.05;
+#[cfg(feature = "render")]
+const ARROW_LENGTH: f32 = 10.;
+
+/// Vector field boids steer along, with each vector's length in `[0, 1]`
+/// scaling how hard they are pushed. Painted with the flow brush and saved
+/// to `path` so a scenario can load it again.
+#[derive(Resource)]
+pub struct FlowField {
+    pub grid: FieldGrid<Vec2>,
+    path: PathBuf,
+}
+
+impl FlowField {
+    /// Loads the field saved at `path`, or starts an empty one that will
+    /// be saved there.
+    pub fn load_or_empty(path: PathBuf) -> Self {
+        let grid = match read_field(&path) {
+            Ok(grid) => grid,
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!("failed to load flow field {}: {err}", path.display());
+                }
+                FieldGrid::new(GRID_SIZE, CELL_SIZE)
+            }
+        };
+        FlowField { grid, path }
+    }
+
+    /// Steering towards the flow at `position`, weighted by its strength.
+    pub(super) fn steer(&self, boid: &Boid, position: &Position, velocity: &Velocity) -> Vec2 {
+        let flow = self.grid.sample_bilinear(position.0);
+        let strength = flow.length().min(1.);
+        if strength < MIN_FLOW {
+            return Vec2::ZERO;
+        }
+        let desired = flow.normalize() * boid.max_speed * strength;
+        (desired - velocity.0).clamp_length_max(boid.max_force) * strength
+    }
+}
+
+/// One line with the grid size and cell size, then one `x y` line per cell.
+fn read_field(path: &Path) -> io::Result<FieldGrid<Vec2>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed flow field");
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.lines();
+    let header: Vec<&str> = lines.next().ok_or_else(invalid)?.split_whitespace().collect();
+    let [width, height, cell_size] = header[..] else {
+        return Err(invalid());
+    };
+    let size = UVec2::new(width.parse().map_err(|_| invalid())?, height.parse().map_err(|_| invalid())?);
+    if size.x == 0 || size.y == 0 {
+        return Err(invalid());
+    }
+    let cell_size: f32 = cell_size.parse().map_err(|_| invalid())?;
+    if !(cell_size.is_finite() && cell_size > 0.) {
+        return Err(invalid());
+    }
+    let mut grid = FieldGrid::new(size, cell_size);
+    for (index, line) in lines.enumerate() {
+        let cell = UVec2::new(index as u32 % size.x, index as u32 / size.x);
+        if cell.y >= size.y {
+            return Err(invalid());
+        }
+        let values: Vec<f32> = line
+            .split_whitespace()
+            .map(|value| value.parse().map_err(|_| invalid()))
+            .collect::<io::Result<_>>()?;
+        let [x, y] = values[..] else {
+            return Err(invalid());
+        };
+        grid.set(cell, Vec2::new(x, y));
+    }
+    Ok(grid)
+}
+
+fn write_field(path: &Path, grid: &FieldGrid<Vec2>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let size = grid.size();
+    let mut text = format!("{} {} {}\n", size.x, size.y, grid.cell_size());
+    for (_, value) in grid.iter() {
+        text.push_str(&format!("{} {}\n", value.x, value.y));
+    }
+    fs::write(path, text)
+}
+
+/// V toggles the flow brush. Dragging with the left mouse paints the drag
+/// direction into the field, holding the right mouse erases it and F6
+/// saves the field.
+pub(super) fn paint_flow(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut field: ResMut<FlowField>,
+    mut last_cursor: Local<Option<Vec2>>,
+    time: Res<Time>,
+) {
+    if keys.just_pressed(KeyCode::KeyV) {
+        tool.toggle(ActiveTool::FlowBrush);
+    }
+    if keys.just_pressed(KeyCode::F6) {
+        save_flow(&field);
+    }
+    let previous = last_cursor.take();
+    if *tool != ActiveTool::FlowBrush {
+        return;
+    }
+    let Some(center) = cursor.0 else {
+        return;
+    };
+    *last_cursor = Some(center);
+
+    if buttons.pressed(MouseButton::Left) {
+        let Some(previous) = previous else {
+            return;
+        };
+        let drag = center - previous;
+        if drag == Vec2::ZERO {
+            return;
+        }
+        let direction = drag.normalize();
+        let blend = drag.length() * BRUSH_HARDNESS;
+        field.grid.paint(center, BRUSH_RADIUS, |flow, weight| {
+            *flow = flow.lerp(direction, (blend * weight).min(1.));
+        });
+    } else if buttons.pressed(MouseButton::Right) {
+        let fade = ERASE_RATE * time.delta_seconds();
+        field.grid.paint(center, BRUSH_RADIUS, |flow, weight| {
+            *flow *= 1. - (fade * weight).min(1.);
+        });
+    }
+}
+
+fn save_flow(field: &FlowField) {
+    let path = &field.path;
+    match write_field(path, &field.grid) {
+        Ok(()) => info!("saved flow field to {}", path.display()),
+        Err(err) => error!("failed to save flow field to {}: {err}", path.display()),
+    }
+}
+
+/// Draws the field as short arrows while the flow brush is active.
+#[cfg(feature = "render")]
+pub(super) fn draw_flow(mut gizmos: Gizmos, field: Res<FlowField>, tool: Res<ActiveTool>) {
+    if *tool != ActiveTool::FlowBrush {
+        return;
+    }
+    for (cell, flow) in field.grid.iter() {
+        if flow.length() < MIN_FLOW {
+            continue;
+        }
+        let start = field.grid.cell_center(cell);
+        gizmos.arrow_2d(start, start + flow * ARROW_LENGTH, Color::srgba(0.5, 0.9, 1., 0.5));
+    }
+}
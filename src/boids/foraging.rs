@@ -0,0 +1,126 @@
+use std::time::Duration;
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    EventWriter,
+    Query,
+    Res,
+    Resource,
+    Time,
+    Timer,
+    TimerMode,
+    Vec2,
+    With,
+    Without,
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use super::{
+    roosting::Dormant,
+    signals::{Inbox, Signal, SignalKind},
+    Boid,
+    Position,
+};
+
+// how far a food dance is noticed
+const RECRUIT_RADIUS: f32 = 120.;
+// trust placed in a freshly received food location
+const RECRUIT_TRUST: f32 = 1.;
+// seconds for trust in an advertised location to halve
+const TRUST_HALF_LIFE: f32 = 4.;
+// trust below this is forgotten
+const MIN_TRUST: f32 = 0.05;
+// seconds between broadcasts of a boid feeding at a source
+const DANCE_INTERVAL: f32 = 0.5;
+
+/// A patch of food that boids passing over advertise to their neighbours.
+#[derive(Component)]
+pub struct FoodSource {
+    pub radius: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FoodDesc {
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+/// Food sources spawned at startup.
+#[derive(Resource, Default)]
+pub(super) struct FoodLayout(pub(super) Vec<FoodDesc>);
+
+/// Food location a boid was recruited to and how much it still trusts it.
+#[derive(Component)]
+pub(super) struct Forager {
+    pub(super) target: Option<Vec2>,
+    pub(super) trust: f32,
+    dance: Timer,
+}
+
+impl Default for Forager {
+    fn default() -> Self {
+        Forager {
+            target: None,
+            trust: 0.,
+            dance: Timer::new(Duration::from_secs_f32(DANCE_INTERVAL), TimerMode::Repeating),
+        }
+    }
+}
+
+pub(super) fn spawn_food(mut commands: Commands, layout: Res<FoodLayout>) {
+    for food in layout.0.iter() {
+        commands.spawn((Position(food.position), FoodSource { radius: food.radius }));
+    }
+}
+
+/// Boids over a food source advertise it and stop seeking; boids hearing an
+/// advertisement adopt it with full trust, which then decays until the
+/// location is forgotten.
+#[allow(clippy::type_complexity)]
+pub(super) fn forage(
+    mut boids: Query<(Entity, &Position, &Inbox, &mut Forager), (With<Boid>, Without<Dormant>)>,
+    foods: Query<(&Position, &FoodSource), Without<Boid>>,
+    mut signals: EventWriter<Signal>,
+    time: Res<Time>,
+) {
+    let decay = 0.5f32.powf(time.delta_seconds() / TRUST_HALF_LIFE);
+    for (entity, pos, inbox, mut forager) in boids.iter_mut() {
+        forager.trust *= decay;
+        for signal in inbox.0.iter() {
+            if let SignalKind::FoodFound { location } = signal.kind {
+                forager.target = Some(location);
+                forager.trust = RECRUIT_TRUST;
+            }
+        }
+
+        let feeding = foods
+            .iter()
+            .find(|(center, food)| pos.0.distance(center.0) < food.radius);
+        if let Some((center, _)) = feeding {
+            // found it, no need to follow anyone there
+            forager.target = None;
+            forager.trust = 0.;
+            if forager.dance.tick(time.delta()).just_finished() {
+                signals.send(Signal {
+                    kind: SignalKind::FoodFound { location: center.0 },
+                    source: entity,
+                    origin: pos.0,
+                    radius: RECRUIT_RADIUS,
+                });
+            }
+        }
+
+        if forager.trust < MIN_TRUST {
+            forager.target = None;
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+pub(super) fn draw_food(mut gizmos: Gizmos, foods: Query<(&Position, &FoodSource)>) {
+    for (pos, food) in foods.iter() {
+        gizmos.circle_2d(pos.0, food.radius, Color::srgb(0.5, 0.9, 0.3));
+    }
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::{
+    Event,
+    EventReader,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    Time,
+    Timer,
+    TimerMode,
+    Vec2,
+    With,
+    Without,
+};
+
+use super::{
+    despawning::Despawning,
+    roosting::Dormant,
+    species::Species,
+    squads::Squad,
+    Acceleration,
+    Boid,
+    Position,
+};
+
+/// The boids an [`ApplyForce`] pushes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForceFilter {
+    All,
+    Species(Species),
+    Squad(Squad),
+    /// Boids within `radius` of `center`, checked every tick, so boids
+    /// flying into the region while the force lasts are pushed too
+    Region { center: Vec2, radius: f32 },
+}
+
+impl ForceFilter {
+    fn matches(&self, position: Vec2, species: Species, squad: Option<&Squad>) -> bool {
+        match *self {
+            ForceFilter::All => true,
+            ForceFilter::Species(only) => species == only,
+            ForceFilter::Squad(only) => squad == Some(&only),
+            ForceFilter::Region { center, radius } => position.distance(center) < radius,
+        }
+    }
+}
+
+/// Pushes the boids `filter` picks with `force` on top of their steering
+/// for `duration` seconds of simulation time, for game code, scripts or
+/// network messages to drive gusts, blasts and scripted pushes. Like
+/// steering the force is divided by each boid's mass, and forces from
+/// several events add up. Perched and fading boids are left alone.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ApplyForce {
+    pub filter: ForceFilter,
+    pub force: Vec2,
+    pub duration: f32,
+}
+
+/// Forces sent with [`ApplyForce`] that haven't run out yet.
+#[derive(Resource, Default)]
+pub(super) struct ActiveForces(Vec<(ApplyForce, Timer)>);
+
+/// Adds the active forces to the acceleration of the boids they push, once
+/// the flock has steered.
+#[allow(clippy::type_complexity)]
+pub(super) fn apply_forces(
+    mut events: EventReader<ApplyForce>,
+    mut active: ResMut<ActiveForces>,
+    mut boids: Query<
+        (&Position, &Species, Option<&Squad>, &mut Acceleration),
+        (With<Boid>, Without<Dormant>, Without<Despawning>),
+    >,
+    time: Res<Time>,
+) {
+    active.0.extend(
+        events
+            .read()
+            .filter(|event| event.duration > 0.)
+            .map(|&event| (event, Timer::from_seconds(event.duration, TimerMode::Once))),
+    );
+    if active.0.is_empty() {
+        return;
+    }
+    for (pos, species, squad, mut acc) in boids.iter_mut() {
+        for (event, _) in &active.0 {
+            if event.filter.matches(pos.0, *species, squad) {
+                acc.0 += event.force;
+            }
+        }
+    }
+    active.0.retain_mut(|(_, timer)| !timer.tick(time.delta()).finished());
+}
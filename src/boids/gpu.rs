@@ -0,0 +1,584 @@
+use std::f32::consts::TAU;
+use bevy::{
+    app::{App, Plugin, Startup, Update},
+    asset::{load_internal_asset, Handle},
+    color::ColorToComponents,
+    core_pipeline::core_2d::graph::{Core2d, Node2d},
+    ecs::query::QueryItem,
+    log::info,
+    prelude::{
+        Camera2dBundle,
+        Color,
+        Commands,
+        Component,
+        Entity,
+        FromWorld,
+        IntoSystemConfigs,
+        LinearRgba,
+        Msaa,
+        Query,
+        Res,
+        ResMut,
+        Resource,
+        Shader,
+        Time,
+        Vec2,
+        World,
+    },
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        graph::CameraDriverLabel,
+        render_graph::{
+            Node,
+            NodeRunError,
+            RenderGraph,
+            RenderGraphApp,
+            RenderGraphContext,
+            RenderLabel,
+            ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{storage_buffer, storage_buffer_read_only, storage_buffer_sized, uniform_buffer},
+            encase,
+            BindGroup,
+            BindGroupEntries,
+            BindGroupLayout,
+            BindGroupLayoutEntries,
+            BlendState,
+            Buffer,
+            BufferDescriptor,
+            BufferInitDescriptor,
+            BufferUsages,
+            CachedComputePipelineId,
+            CachedRenderPipelineId,
+            ColorTargetState,
+            ColorWrites,
+            ComputePassDescriptor,
+            ComputePipelineDescriptor,
+            FragmentState,
+            MultisampleState,
+            PipelineCache,
+            PrimitiveState,
+            RenderPassDescriptor,
+            RenderPipelineDescriptor,
+            ShaderStages,
+            SpecializedRenderPipeline,
+            SpecializedRenderPipelines,
+            TextureFormat,
+            UniformBuffer,
+            VertexState,
+        },
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        texture::BevyDefault,
+        view::{ExtractedView, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+        Render,
+        RenderApp,
+        RenderSet,
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::BoidsSettings;
+use self::layout::{GpuBoid, GpuParams};
+
+const TYPES_SHADER: Handle<Shader> = Handle::weak_from_u128(0x5e1d_0b01_d5a1_4c3e_9f2a_7e61_0c4b_d201);
+const SIMULATE_SHADER: Handle<Shader> = Handle::weak_from_u128(0x5e1d_0b01_d5a1_4c3e_9f2a_7e61_0c4b_d202);
+const DRAW_SHADER: Handle<Shader> = Handle::weak_from_u128(0x5e1d_0b01_d5a1_4c3e_9f2a_7e61_0c4b_d203);
+
+// must match `@workgroup_size` in simulate.wgsl
+const WORKGROUP_SIZE: u32 = 64;
+// the grid is at most this many cells along each side, and at least 3 so
+// the neighbouring cells of a boid are all different ones
+const MAX_GRID_SIDE: u32 = 256;
+const MIN_GRID_SIDE: u32 = 3;
+// longest step a frame advances the simulation by, so a stall doesn't
+// fling every boid across the world
+const MAX_STEP: f32 = 1. / 30.;
+
+/// Runs separation, alignment and cohesion for a large flock in compute
+/// shaders, with the boids kept in GPU buffers and drawn by instancing
+/// straight from them. Use it instead of [`BoidsPlugin`](super::BoidsPlugin)
+/// for flocks of 100k boids and more.
+///
+/// The boids only flock: they live in a wrapping world of `size` centred on
+/// the origin and are not entities, so nothing else in this crate sees
+/// them. [`BoidsSettings`] is read every frame, so the flocking parameters
+/// can still be changed while it runs. Spawns its own 2d camera.
+///
+/// Panics when built with a `size` that isn't positive on both axes.
+pub struct GpuBoidsPlugin {
+    pub count: u32,
+    pub size: Vec2,
+    pub seed: u64,
+    pub settings: BoidsSettings,
+    pub color: Color,
+}
+
+impl Default for GpuBoidsPlugin {
+    fn default() -> Self {
+        GpuBoidsPlugin {
+            count: 100_000,
+            size: Vec2::new(1280., 720.),
+            seed: 0,
+            settings: BoidsSettings::default(),
+            color: Color::WHITE,
+        }
+    }
+}
+
+// the derive emits its layout checks as free functions beside each struct,
+// out of reach of an attribute on the struct, and nothing ever calls them
+#[allow(dead_code)]
+mod layout {
+    use bevy::{
+        prelude::{Vec2, Vec4},
+        render::render_resource::ShaderType,
+    };
+
+    #[derive(ShaderType, Clone, Copy, Default)]
+    pub(super) struct GpuBoid {
+        pub(super) position: Vec2,
+        pub(super) velocity: Vec2,
+    }
+
+    /// Uniforms shared by the compute and draw shaders, laid out like `Params`
+    /// in types.wgsl.
+    #[derive(ShaderType, Clone, Copy, Default)]
+    pub(super) struct GpuParams {
+        pub(super) count: u32,
+        pub(super) grid_width: u32,
+        pub(super) grid_height: u32,
+        pub(super) cell_size: Vec2,
+        pub(super) half_size: Vec2,
+        pub(super) dt: f32,
+        pub(super) max_speed: f32,
+        pub(super) max_force: f32,
+        pub(super) desired_separation: f32,
+        pub(super) neighbour_radius: f32,
+        pub(super) separation: f32,
+        pub(super) alignment: f32,
+        pub(super) cohesion: f32,
+        pub(super) boid_size: f32,
+        pub(super) color: Vec4,
+    }
+}
+
+/// This frame's uniforms, extracted to the render world.
+#[derive(Resource, Clone, ExtractResource)]
+struct GpuBoidsParams(GpuParams);
+
+/// The flock the buffers start out with, read once the renderer exists.
+#[derive(Resource)]
+struct InitialGpuBoids(Vec<GpuBoid>);
+
+impl Plugin for GpuBoidsPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(
+            self.size.x > 0. && self.size.y > 0.,
+            "GpuBoidsPlugin needs a positive world size, got {}",
+            self.size,
+        );
+        load_internal_asset!(app, TYPES_SHADER, "gpu/types.wgsl", Shader::from_wgsl);
+        load_internal_asset!(app, SIMULATE_SHADER, "gpu/simulate.wgsl", Shader::from_wgsl);
+        load_internal_asset!(app, DRAW_SHADER, "gpu/draw.wgsl", Shader::from_wgsl);
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let half_size = self.size / 2.;
+        let boids = (0..self.count)
+            .map(|_| GpuBoid {
+                position: Vec2::new(
+                    rng.gen_range(-half_size.x..half_size.x),
+                    rng.gen_range(-half_size.y..half_size.y),
+                ),
+                velocity: Vec2::from_angle(rng.gen_range(0.0..TAU)) * self.settings.max_speed,
+            })
+            .collect();
+        let params = GpuParams {
+            count: self.count,
+            half_size,
+            boid_size: 1.,
+            color: LinearRgba::from(self.color).to_vec4(),
+            ..Default::default()
+        };
+        info!("simulating {} boids on the GPU", self.count);
+
+        app.insert_resource(self.settings)
+            .insert_resource(GpuBoidsParams(params))
+            .insert_resource(InitialGpuBoids(boids))
+            .add_plugins(ExtractResourcePlugin::<GpuBoidsParams>::default())
+            .add_systems(Startup, spawn_camera)
+            .add_systems(Update, update_params);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<GpuBoidsState>()
+            .init_resource::<SpecializedRenderPipelines<GpuBoidsPipeline>>()
+            .add_systems(Render, (
+                queue_draw_pipelines.in_set(RenderSet::Queue),
+                prepare_bind_groups.in_set(RenderSet::PrepareBindGroups),
+            ))
+            .add_render_graph_node::<ViewNodeRunner<DrawNode>>(Core2d, DrawLabel)
+            .add_render_graph_edges(Core2d, (Node2d::MainTransparentPass, DrawLabel, Node2d::EndMainPass));
+        let mut graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        graph.add_node(SimulateLabel, SimulateNode);
+        graph.add_node_edge(SimulateLabel, CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(InitialGpuBoids(boids)) = app.world_mut().remove_resource::<InitialGpuBoids>() else {
+            return;
+        };
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let buffers = GpuBoidsBuffers::new(render_app.world().resource::<RenderDevice>(), &boids);
+        render_app.insert_resource(buffers).init_resource::<GpuBoidsPipeline>();
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn update_params(settings: Res<BoidsSettings>, time: Res<Time>, mut params: ResMut<GpuBoidsParams>) {
+    let params = &mut params.0;
+    // cells as wide as the furthest a boid sees, so its neighbours are all
+    // in the 3x3 cells around it
+    let reach = settings.neighbour_radius.max(settings.desired_separation);
+    let size = params.half_size * 2.;
+    let cell_size = reach
+        .max(size.max_element() / MAX_GRID_SIDE as f32)
+        .min(size.min_element() / MIN_GRID_SIDE as f32);
+    params.grid_width = ((size.x / cell_size).floor() as u32).clamp(MIN_GRID_SIDE, MAX_GRID_SIDE);
+    params.grid_height = ((size.y / cell_size).floor() as u32).clamp(MIN_GRID_SIDE, MAX_GRID_SIDE);
+    // cells fit each axis exactly, so the last row and column wrap onto the
+    // first even when the world isn't square
+    params.cell_size = size / Vec2::new(params.grid_width as f32, params.grid_height as f32);
+    params.dt = time.delta_seconds().min(MAX_STEP);
+    params.max_speed = settings.max_speed;
+    params.max_force = settings.max_force;
+    params.desired_separation = settings.desired_separation;
+    params.neighbour_radius = settings.neighbour_radius;
+    params.separation = settings.separation;
+    params.alignment = settings.alignment;
+    params.cohesion = settings.cohesion;
+}
+
+/// Boid state on the GPU. The compute pass reads one of the two boid
+/// buffers and writes the other, swapping every frame.
+#[derive(Resource)]
+struct GpuBoidsBuffers {
+    params: UniformBuffer<GpuParams>,
+    boids: [Buffer; 2],
+    /// Boids in each grid cell this frame
+    cell_counts: Buffer,
+    /// Where each cell's boids start in `cell_boids`, one past the cells
+    cell_starts: Buffer,
+    /// Every boid's index, sorted by grid cell
+    cell_boids: Buffer,
+}
+
+impl GpuBoidsBuffers {
+    fn new(device: &RenderDevice, boids: &[GpuBoid]) -> Self {
+        let mut contents = encase::StorageBuffer::new(Vec::new());
+        contents.write(&boids.to_vec()).expect("boid buffer should encode");
+        let contents = contents.into_inner();
+        let boid_buffer = |label| device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some(label),
+            contents: &contents,
+            usage: BufferUsages::STORAGE,
+        });
+        let cells = (MAX_GRID_SIDE * MAX_GRID_SIDE) as u64;
+        let grid_buffer = |label, size| device.create_buffer(&BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        GpuBoidsBuffers {
+            params: UniformBuffer::default(),
+            boids: [boid_buffer("gpu_boids_a"), boid_buffer("gpu_boids_b")],
+            cell_counts: grid_buffer("gpu_boids_cell_counts", cells * 4),
+            cell_starts: grid_buffer("gpu_boids_cell_starts", (cells + 1) * 4),
+            cell_boids: grid_buffer("gpu_boids_cell_boids", boids.len().max(1) as u64 * 4),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GpuBoidsPipeline {
+    simulate_layout: BindGroupLayout,
+    view_layout: BindGroupLayout,
+    draw_layout: BindGroupLayout,
+    clear_grid: CachedComputePipelineId,
+    count_boids: CachedComputePipelineId,
+    scan_cells: CachedComputePipelineId,
+    scatter_boids: CachedComputePipelineId,
+    simulate: CachedComputePipelineId,
+}
+
+impl FromWorld for GpuBoidsPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let device = world.resource::<RenderDevice>();
+        let simulate_layout = device.create_bind_group_layout(
+            "gpu_boids_simulate_layout",
+            &BindGroupLayoutEntries::sequential(ShaderStages::COMPUTE, (
+                uniform_buffer::<GpuParams>(false),
+                storage_buffer_read_only::<Vec<GpuBoid>>(false),
+                storage_buffer::<Vec<GpuBoid>>(false),
+                storage_buffer_sized(false, None),
+                storage_buffer_sized(false, None),
+                storage_buffer_sized(false, None),
+            )),
+        );
+        let view_layout = device.create_bind_group_layout(
+            "gpu_boids_view_layout",
+            &BindGroupLayoutEntries::single(ShaderStages::VERTEX, uniform_buffer::<ViewUniform>(true)),
+        );
+        let draw_layout = device.create_bind_group_layout(
+            "gpu_boids_draw_layout",
+            &BindGroupLayoutEntries::sequential(ShaderStages::VERTEX_FRAGMENT, (
+                uniform_buffer::<GpuParams>(false),
+                storage_buffer_read_only::<Vec<GpuBoid>>(false),
+            )),
+        );
+        let cache = world.resource::<PipelineCache>();
+        let compute = |entry_point: &'static str| cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some(format!("gpu_boids_{entry_point}").into()),
+            layout: vec![simulate_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader: SIMULATE_SHADER,
+            shader_defs: Vec::new(),
+            entry_point: entry_point.into(),
+        });
+        GpuBoidsPipeline {
+            clear_grid: compute("clear_grid"),
+            count_boids: compute("count_boids"),
+            scan_cells: compute("scan_cells"),
+            scatter_boids: compute("scatter_boids"),
+            simulate: compute("simulate"),
+            simulate_layout,
+            view_layout,
+            draw_layout,
+        }
+    }
+}
+
+/// What the draw pipeline depends on about the view it draws into.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct DrawKey {
+    hdr: bool,
+    samples: u32,
+}
+
+impl SpecializedRenderPipeline for GpuBoidsPipeline {
+    type Key = DrawKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let format = if key.hdr { ViewTarget::TEXTURE_FORMAT_HDR } else { TextureFormat::bevy_default() };
+        RenderPipelineDescriptor {
+            label: Some("gpu_boids_draw".into()),
+            layout: vec![self.view_layout.clone(), self.draw_layout.clone()],
+            push_constant_ranges: Vec::new(),
+            vertex: VertexState {
+                shader: DRAW_SHADER,
+                shader_defs: Vec::new(),
+                entry_point: "vertex".into(),
+                buffers: Vec::new(),
+            },
+            fragment: Some(FragmentState {
+                shader: DRAW_SHADER,
+                shader_defs: Vec::new(),
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: key.samples,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// The draw pipeline specialized for a view.
+#[derive(Component)]
+struct DrawPipeline(CachedRenderPipelineId);
+
+fn queue_draw_pipelines(
+    mut commands: Commands,
+    cache: Res<PipelineCache>,
+    pipeline: Res<GpuBoidsPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GpuBoidsPipeline>>,
+    msaa: Res<Msaa>,
+    views: Query<(Entity, &ExtractedView)>,
+) {
+    for (entity, view) in views.iter() {
+        let key = DrawKey { hdr: view.hdr, samples: msaa.samples() };
+        let id = pipelines.specialize(&cache, &pipeline, key);
+        commands.entity(entity).insert(DrawPipeline(id));
+    }
+}
+
+/// Which boid buffer this frame's compute pass reads.
+#[derive(Resource, Default)]
+struct GpuBoidsState {
+    read: usize,
+}
+
+#[derive(Resource)]
+struct GpuBoidsBindGroups {
+    /// Reading buffer `i` and writing the other, by `i`
+    simulate: [BindGroup; 2],
+    /// Drawing the buffer written when reading buffer `i`, by `i`
+    draw: [BindGroup; 2],
+    view: BindGroup,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_bind_groups(
+    mut commands: Commands,
+    pipeline: Res<GpuBoidsPipeline>,
+    mut buffers: ResMut<GpuBoidsBuffers>,
+    params: Res<GpuBoidsParams>,
+    mut state: ResMut<GpuBoidsState>,
+    view_uniforms: Res<ViewUniforms>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+) {
+    state.read = 1 - state.read;
+    buffers.params.set(params.0);
+    buffers.params.write_buffer(&device, &queue);
+    let (Some(params), Some(view)) = (buffers.params.binding(), view_uniforms.uniforms.binding()) else {
+        return;
+    };
+    let simulate = |read: usize| device.create_bind_group(
+        "gpu_boids_simulate",
+        &pipeline.simulate_layout,
+        &BindGroupEntries::sequential((
+            params.clone(),
+            buffers.boids[read].as_entire_binding(),
+            buffers.boids[1 - read].as_entire_binding(),
+            buffers.cell_counts.as_entire_binding(),
+            buffers.cell_starts.as_entire_binding(),
+            buffers.cell_boids.as_entire_binding(),
+        )),
+    );
+    let draw = |read: usize| device.create_bind_group(
+        "gpu_boids_draw",
+        &pipeline.draw_layout,
+        &BindGroupEntries::sequential((params.clone(), buffers.boids[1 - read].as_entire_binding())),
+    );
+    commands.insert_resource(GpuBoidsBindGroups {
+        simulate: [simulate(0), simulate(1)],
+        draw: [draw(0), draw(1)],
+        view: device.create_bind_group("gpu_boids_view", &pipeline.view_layout, &BindGroupEntries::single(view)),
+    });
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct SimulateLabel;
+
+/// Sorts the boids by grid cell, counting the boids in each cell, scanning
+/// the counts into where each cell starts and scattering the boids there,
+/// then steps them, before any camera draws.
+struct SimulateNode;
+
+impl Node for SimulateNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline = world.resource::<GpuBoidsPipeline>();
+        let cache = world.resource::<PipelineCache>();
+        let params = world.resource::<GpuBoidsParams>().0;
+        let (
+            Some(bind_groups),
+            Some(clear_grid),
+            Some(count_boids),
+            Some(scan_cells),
+            Some(scatter_boids),
+            Some(simulate),
+        ) = (
+            world.get_resource::<GpuBoidsBindGroups>(),
+            cache.get_compute_pipeline(pipeline.clear_grid),
+            cache.get_compute_pipeline(pipeline.count_boids),
+            cache.get_compute_pipeline(pipeline.scan_cells),
+            cache.get_compute_pipeline(pipeline.scatter_boids),
+            cache.get_compute_pipeline(pipeline.simulate),
+        ) else {
+            // still compiling
+            return Ok(());
+        };
+        let read = world.resource::<GpuBoidsState>().read;
+        let cells = params.grid_width * params.grid_height;
+
+        let mut pass = render_context
+            .command_encoder()
+            .begin_compute_pass(&ComputePassDescriptor { label: Some("gpu_boids_simulate"), ..Default::default() });
+        pass.set_bind_group(0, &bind_groups.simulate[read], &[]);
+        pass.set_pipeline(clear_grid);
+        pass.dispatch_workgroups(cells.div_ceil(WORKGROUP_SIZE), 1, 1);
+        pass.set_pipeline(count_boids);
+        pass.dispatch_workgroups(params.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        pass.set_pipeline(scan_cells);
+        pass.dispatch_workgroups(1, 1, 1);
+        pass.set_pipeline(scatter_boids);
+        pass.dispatch_workgroups(params.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        pass.set_pipeline(simulate);
+        pass.dispatch_workgroups(params.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct DrawLabel;
+
+/// Draws every boid as an instance of one triangle, on top of the 2d pass.
+#[derive(Default)]
+struct DrawNode;
+
+impl ViewNode for DrawNode {
+    type ViewQuery = (&'static ViewTarget, &'static ViewUniformOffset, &'static DrawPipeline);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (target, view_offset, draw_pipeline): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let cache = world.resource::<PipelineCache>();
+        let (Some(bind_groups), Some(pipeline)) = (
+            world.get_resource::<GpuBoidsBindGroups>(),
+            cache.get_render_pipeline(draw_pipeline.0),
+        ) else {
+            return Ok(());
+        };
+        let read = world.resource::<GpuBoidsState>().read;
+        let count = world.resource::<GpuBoidsParams>().0.count;
+
+        let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("gpu_boids_draw"),
+            color_attachments: &[Some(target.get_color_attachment())],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_render_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_groups.view, &[view_offset.offset]);
+        pass.set_bind_group(1, &bind_groups.draw[read], &[]);
+        pass.draw(0..3, 0..count);
+        Ok(())
+    }
+}
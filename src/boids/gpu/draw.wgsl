@@ -0,0 +1,30 @@
+#import bevy_render::view::View
+#import boids::gpu::{Boid, Params}
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(1) @binding(0) var<uniform> params: Params;
+@group(1) @binding(1) var<storage, read> boids: array<Boid>;
+
+// one instance per boid, three vertices each
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> @builtin(position) vec4<f32> {
+    let boid = boids[instance_index];
+    // the same triangle as the CPU boids, pointing along +y
+    var corners = array<vec2<f32>, 3>(vec2(0.0, 6.0), vec2(-3.0, -3.0), vec2(3.0, -3.0));
+    var forward = vec2<f32>(0.0, 1.0);
+    if length(boid.velocity) > 0.0 {
+        forward = normalize(boid.velocity);
+    }
+    let right = vec2<f32>(forward.y, -forward.x);
+    let corner = corners[vertex_index] * params.boid_size;
+    let position = boid.position + right * corner.x + forward * corner.y;
+    return view.clip_from_world * vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return params.color;
+}
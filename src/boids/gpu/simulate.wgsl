@@ -0,0 +1,170 @@
+#import boids::gpu::{Boid, Params}
+
+// threads of the single workgroup that scans the cell counts
+const SCAN_THREADS: u32 = 256u;
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> boids_in: array<Boid>;
+@group(0) @binding(2) var<storage, read_write> boids_out: array<Boid>;
+@group(0) @binding(3) var<storage, read_write> cell_counts: array<atomic<u32>>;
+// where each cell's boids start in `cell_boids`, with the boid count at the end
+@group(0) @binding(4) var<storage, read_write> cell_starts: array<u32>;
+// every boid's index, sorted by cell
+@group(0) @binding(5) var<storage, read_write> cell_boids: array<u32>;
+
+var<workgroup> chunk_sums: array<u32, SCAN_THREADS>;
+
+fn cell_of(position: vec2<f32>) -> vec2<i32> {
+    let grid = vec2<i32>(i32(params.grid_width), i32(params.grid_height));
+    let cell = vec2<i32>(floor((position + params.half_size) / params.cell_size));
+    return clamp(cell, vec2<i32>(0), grid - 1);
+}
+
+// the world wraps around, and so does the grid
+fn cell_index(cell: vec2<i32>) -> u32 {
+    let grid = vec2<i32>(i32(params.grid_width), i32(params.grid_height));
+    let wrapped = (cell % grid + grid) % grid;
+    return u32(wrapped.y) * params.grid_width + u32(wrapped.x);
+}
+
+// shortest offset between two points across the wrapping edges
+fn wrap_offset(offset: vec2<f32>) -> vec2<f32> {
+    let size = params.half_size * 2.0;
+    return offset - size * round(offset / size);
+}
+
+fn clamp_length_max(v: vec2<f32>, max_length: f32) -> vec2<f32> {
+    let len = length(v);
+    if len > max_length {
+        return v * (max_length / len);
+    }
+    return v;
+}
+
+// the steering towards flying along `direction` at full speed, as the CPU
+// rules steer
+fn steer_towards(direction: vec2<f32>, velocity: vec2<f32>) -> vec2<f32> {
+    if length(direction) == 0.0 {
+        return vec2<f32>(0.0);
+    }
+    return clamp_length_max(normalize(direction) * params.max_speed - velocity, params.max_force);
+}
+
+@compute @workgroup_size(64)
+fn clear_grid(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.grid_width * params.grid_height {
+        atomicStore(&cell_counts[id.x], 0u);
+    }
+}
+
+@compute @workgroup_size(64)
+fn count_boids(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    atomicAdd(&cell_counts[cell_index(cell_of(boids_in[index].position))], 1u);
+}
+
+// exclusive prefix sum of the cell counts: each thread sums a run of cells,
+// the run totals are scanned in workgroup memory, then each thread writes
+// the starts of its own run
+@compute @workgroup_size(256)
+fn scan_cells(@builtin(local_invocation_index) thread: u32) {
+    let cells = params.grid_width * params.grid_height;
+    let run = (cells + SCAN_THREADS - 1u) / SCAN_THREADS;
+    let first = thread * run;
+    let last = min(first + run, cells);
+
+    var sum = 0u;
+    for (var cell = first; cell < last; cell++) {
+        sum += atomicLoad(&cell_counts[cell]);
+    }
+    chunk_sums[thread] = sum;
+    workgroupBarrier();
+    for (var offset = 1u; offset < SCAN_THREADS; offset *= 2u) {
+        var before = 0u;
+        if thread >= offset {
+            before = chunk_sums[thread - offset];
+        }
+        workgroupBarrier();
+        chunk_sums[thread] += before;
+        workgroupBarrier();
+    }
+
+    var start = chunk_sums[thread] - sum;
+    for (var cell = first; cell < last; cell++) {
+        cell_starts[cell] = start;
+        start += atomicLoad(&cell_counts[cell]);
+    }
+    if thread == SCAN_THREADS - 1u {
+        cell_starts[cells] = chunk_sums[thread];
+    }
+}
+
+// fills each cell's run of `cell_boids`, counting its count back down to 0
+@compute @workgroup_size(64)
+fn scatter_boids(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    let cell = cell_index(cell_of(boids_in[index].position));
+    let slot = atomicSub(&cell_counts[cell], 1u) - 1u;
+    cell_boids[cell_starts[cell] + slot] = index;
+}
+
+@compute @workgroup_size(64)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    let boid = boids_in[index];
+
+    var away = vec2<f32>(0.0);
+    var crowded = 0u;
+    var heading = vec2<f32>(0.0);
+    var centre = vec2<f32>(0.0);
+    var neighbours = 0u;
+    let home = cell_of(boid.position);
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let cell = cell_index(home + vec2<i32>(dx, dy));
+            for (var slot = cell_starts[cell]; slot < cell_starts[cell + 1u]; slot++) {
+                let other = boids_in[cell_boids[slot]];
+                let offset = wrap_offset(other.position - boid.position);
+                let distance = length(offset);
+                if distance <= 0.0 {
+                    continue;
+                }
+                if distance < params.desired_separation {
+                    // pushed away harder the closer the neighbour is
+                    away -= offset / (distance * distance);
+                    crowded++;
+                }
+                if distance < params.neighbour_radius {
+                    heading += other.velocity;
+                    centre += offset;
+                    neighbours++;
+                }
+            }
+        }
+    }
+
+    var force = vec2<f32>(0.0);
+    if crowded > 0u {
+        force += steer_towards(away, boid.velocity) * params.separation;
+    }
+    if neighbours > 0u {
+        force += steer_towards(heading, boid.velocity) * params.alignment;
+        force += steer_towards(centre, boid.velocity) * params.cohesion;
+    }
+    force = clamp_length_max(force, params.max_force);
+
+    let velocity = clamp_length_max(boid.velocity + force * params.dt, params.max_speed);
+    let size = params.half_size * 2.0;
+    var position = boid.position + velocity * params.dt;
+    position -= size * floor((position + params.half_size) / size);
+    boids_out[index] = Boid(position, velocity);
+}
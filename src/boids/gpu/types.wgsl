@@ -0,0 +1,25 @@
+#define_import_path boids::gpu
+
+// kept in the same field order as `GpuParams` in gpu.rs
+struct Params {
+    count: u32,
+    grid_width: u32,
+    grid_height: u32,
+    cell_size: vec2<f32>,
+    half_size: vec2<f32>,
+    dt: f32,
+    max_speed: f32,
+    max_force: f32,
+    desired_separation: f32,
+    neighbour_radius: f32,
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
+    boid_size: f32,
+    color: vec4<f32>,
+}
+
+struct Boid {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::{Deref, DerefMut, Entity, Query, Res, ResMut, Resource, Without};
+
+use super::{spawning::Spawning, Boids, BoidsSettings, Position, NEIGHBOUR_RADIUS};
+
+/// Spatial hash of the boid list, rebuilt every frame before flocking so
+/// neighbour lookups only visit nearby cells instead of every boid. Boids
+/// still spawning are left out.
+#[derive(Resource, Deref, DerefMut)]
+pub(super) struct SpatialGrid(boids_core::grid::SpatialGrid<Entity>);
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        // the largest query radius, so most lookups touch at most 3x3 cells
+        SpatialGrid(boids_core::grid::SpatialGrid::new(NEIGHBOUR_RADIUS))
+    }
+}
+
+pub(super) fn rebuild_grid(
+    mut grid: ResMut<SpatialGrid>,
+    boids: Res<Boids>,
+    positions: Query<&Position, Without<Spawning>>,
+    settings: Res<BoidsSettings>,
+) {
+    // cells follow the neighbour radius when it is changed at runtime
+    if grid.cell_size() != settings.neighbour_radius && settings.neighbour_radius > 0. {
+        grid.set_cell_size(settings.neighbour_radius);
+    }
+    grid.clear();
+    for &boid in &boids.0 {
+        let Ok(pos) = positions.get(boid) else {
+            continue;
+        };
+        if !pos.0.is_finite() {
+            continue;
+        }
+        grid.insert(boid, pos.0);
+    }
+}
+
+/// Neighbour lookups flocking made since the grid stats were last measured,
+/// present when counting them was turned on with
+/// [`BoidsPlugin::with_grid_query_count`](super::BoidsPlugin::with_grid_query_count).
+#[derive(Resource, Default)]
+pub(super) struct GridQueryCount(pub(super) usize);
+
+/// How the spatial grid was filled on the last tick and how often flocking
+/// looked it up over the last frame, shown in the HUD to help tune the cell
+/// size against the neighbour radius.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct GridStats {
+    pub cell_size: f32,
+    pub occupied_cells: usize,
+    /// Most boids in any one cell
+    pub max_per_cell: usize,
+    /// Boids per occupied cell
+    pub mean_per_cell: f32,
+    /// Neighbour lookups flocking made over the last frame, across all its
+    /// ticks: one per boid, plus one per copy of its view across a wrapping
+    /// edge. Queueing, springs and the tools look up the grid uncounted.
+    /// 0 when they aren't counted.
+    pub flocking_lookups: usize,
+}
+
+pub(super) fn measure_grid(
+    mut stats: ResMut<GridStats>,
+    grid: Res<SpatialGrid>,
+    lookups: Option<ResMut<GridQueryCount>>,
+) {
+    let mut occupied = 0;
+    let mut entries = 0;
+    let mut max = 0;
+    for (_, boids) in grid.cells() {
+        occupied += 1;
+        entries += boids.len();
+        max = max.max(boids.len());
+    }
+    *stats = GridStats {
+        cell_size: grid.cell_size(),
+        occupied_cells: occupied,
+        max_per_cell: max,
+        mean_per_cell: if occupied > 0 { entries as f32 / occupied as f32 } else { 0. },
+        flocking_lookups: lookups.map_or(0, |mut lookups| std::mem::take(&mut lookups.0)),
+    };
+}
@@ -0,0 +1,324 @@
+use std::collections::VecDeque;
+use bevy::{
+    input::ButtonInput,
+    prelude::{Commands, Component, Entity, KeyCode, MouseButton, Query, Res, ResMut, Time, Vec2, With},
+};
+#[cfg(feature = "render")]
+use bevy::{
+    prelude::{
+        default,
+        Assets,
+        Color,
+        FromWorld,
+        Gizmos,
+        Handle,
+        RemovedComponents,
+        Resource,
+        Text,
+        Text2dBundle,
+        TextSection,
+        TextStyle,
+        Transform,
+        Visibility,
+        Without,
+        World,
+    },
+    sprite::{Anchor, ColorMaterial},
+};
+
+use crate::tools::{ActiveTool, CursorWorld};
+
+use super::{Boid, BoidsSettings, Position, Velocity};
+#[cfg(feature = "render")]
+use super::{
+    palette::PaletteMaterial,
+    render::{BoidMaterial, SpeciesMaterials},
+    species::Species,
+};
+
+// seconds of history kept per inspected boid
+const HISTORY_SECONDS: f32 = 30.;
+// how close to a boid a click has to land to select it
+const PICK_RADIUS: f32 = 15.;
+
+#[cfg(feature = "render")]
+const CHART_WIDTH: f32 = 120.;
+#[cfg(feature = "render")]
+const CHART_ROW_HEIGHT: f32 = 24.;
+#[cfg(feature = "render")]
+const CHART_OFFSET: Vec2 = Vec2::new(20., 20.);
+#[cfg(feature = "render")]
+const READOUT_FONT_SIZE: f32 = 11.;
+#[cfg(feature = "render")]
+const READOUT_Z: f32 = 10.;
+#[cfg(feature = "render")]
+const FORCE_NAMES: [&str; 4] = ["sep", "ali", "coh", "avo"];
+#[cfg(feature = "render")]
+const FORCE_COLORS: [Color; 4] = [
+    Color::srgb(1., 0.4, 0.4),
+    Color::srgb(0.4, 1., 0.4),
+    Color::srgb(0.4, 0.6, 1.),
+    Color::srgb(1., 0.8, 0.3),
+];
+
+/// Marks a boid picked with the inspect tool.
+#[derive(Component)]
+pub(super) struct Selected;
+
+/// Magnitude of each steering force applied to a boid in the last flock
+/// step. Only written for boids that have the component.
+#[derive(Component, Clone, Copy, Default)]
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(super) struct SteeringForces {
+    pub(super) separation: f32,
+    pub(super) alignment: f32,
+    pub(super) cohesion: f32,
+    pub(super) avoidance: f32,
+}
+
+impl SteeringForces {
+    #[cfg(feature = "render")]
+    fn magnitudes(&self) -> [f32; 4] {
+        [self.separation, self.alignment, self.cohesion, self.avoidance]
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+struct Sample {
+    time: f32,
+    position: Vec2,
+    speed: f32,
+    neighbours: u32,
+    forces: SteeringForces,
+}
+
+/// Rolling record of an inspected boid's state over the last 30 seconds.
+#[derive(Component, Default)]
+pub(super) struct BoidHistory {
+    samples: VecDeque<Sample>,
+}
+
+impl BoidHistory {
+    fn push(&mut self, sample: Sample) {
+        while self.samples.front().is_some_and(|oldest| sample.time - oldest.time > HISTORY_SECONDS) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+/// I toggles the inspect tool. Left click selects the boid under the
+/// cursor, or deselects it if it is already selected; right click clears
+/// the selection.
+pub(super) fn select_boids(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    boids: Query<(Entity, &Position, Option<&Selected>), With<Boid>>,
+) {
+    if keys.just_pressed(KeyCode::KeyI) {
+        tool.toggle(ActiveTool::Inspect);
+    }
+    if *tool != ActiveTool::Inspect {
+        return;
+    }
+    if buttons.just_pressed(MouseButton::Right) {
+        for (entity, _, selected) in boids.iter() {
+            if selected.is_some() {
+                commands.entity(entity).remove::<(Selected, SteeringForces, BoidHistory)>();
+            }
+        }
+        return;
+    }
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor) = cursor.0 else {
+        return;
+    };
+    let picked = boids
+        .iter()
+        .map(|(entity, pos, selected)| (entity, pos.0.distance(cursor), selected.is_some()))
+        .filter(|&(_, dist, _)| dist < PICK_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    match picked {
+        Some((entity, _, true)) => {
+            commands.entity(entity).remove::<(Selected, SteeringForces, BoidHistory)>();
+        }
+        Some((entity, _, false)) => {
+            commands.entity(entity).try_insert((Selected, SteeringForces::default(), BoidHistory::default()));
+        }
+        None => {}
+    }
+}
+
+pub(super) fn record_history(
+    mut selected: Query<(&Position, &Velocity, &SteeringForces, &mut BoidHistory), With<Selected>>,
+    positions: Query<&Position, With<Boid>>,
+    settings: Res<BoidsSettings>,
+    time: Res<Time>,
+) {
+    for (pos, vel, forces, mut history) in selected.iter_mut() {
+        let neighbours = positions
+            .iter()
+            .filter(|other| {
+                let dist = pos.0.distance(other.0);
+                dist > 0. && dist < settings.neighbour_radius
+            })
+            .count();
+        history.push(Sample {
+            time: time.elapsed_seconds(),
+            position: pos.0,
+            speed: vel.0.length(),
+            neighbours: neighbours as u32,
+            forces: *forces,
+        });
+    }
+}
+
+/// Draws the recorded trail of each selected boid and a small chart next to
+/// it with speed, neighbour count and steering force rows. Every row is
+/// scaled to its own maximum over the window.
+#[cfg(feature = "render")]
+pub(super) fn draw_history(
+    mut gizmos: Gizmos,
+    selected: Query<(&Position, &BoidHistory), With<Selected>>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    for (pos, history) in selected.iter() {
+        gizmos.circle_2d(pos.0, PICK_RADIUS, Color::WHITE);
+        gizmos.linestrip_2d(history.samples.iter().map(|sample| sample.position), Color::srgba(1., 1., 1., 0.3));
+
+        let origin = pos.0 + CHART_OFFSET;
+        let row = |index: usize| origin + Vec2::Y * CHART_ROW_HEIGHT * index as f32;
+        let x = |sample: &Sample| CHART_WIDTH * (1. - (now - sample.time) / HISTORY_SECONDS);
+        for index in 0..3 {
+            let base = row(index);
+            gizmos.line_2d(base, base + Vec2::X * CHART_WIDTH, Color::srgba(1., 1., 1., 0.2));
+        }
+
+        let max_speed = history.samples.iter().map(|sample| sample.speed).fold(0., f32::max);
+        let max_neighbours = history.samples.iter().map(|sample| sample.neighbours).max().unwrap_or(0);
+        let max_force = history.samples
+            .iter()
+            .flat_map(|sample| sample.forces.magnitudes())
+            .fold(0., f32::max);
+        let scaled = |value: f32, max: f32| if max > 0. { value / max } else { 0. };
+
+        gizmos.linestrip_2d(
+            history.samples.iter().map(|sample| {
+                row(2) + Vec2::new(x(sample), CHART_ROW_HEIGHT * scaled(sample.speed, max_speed))
+            }),
+            Color::WHITE,
+        );
+        gizmos.linestrip_2d(
+            history.samples.iter().map(|sample| {
+                let share = scaled(sample.neighbours as f32, max_neighbours as f32);
+                row(1) + Vec2::new(x(sample), CHART_ROW_HEIGHT * share)
+            }),
+            Color::srgb(0.8, 0.5, 1.),
+        );
+        for (force, color) in FORCE_COLORS.into_iter().enumerate() {
+            gizmos.linestrip_2d(
+                history.samples.iter().map(|sample| {
+                    let share = scaled(sample.forces.magnitudes()[force], max_force);
+                    row(0) + Vec2::new(x(sample), CHART_ROW_HEIGHT * share)
+                }),
+                color,
+            );
+        }
+    }
+}
+
+/// Material selected boids are drawn with.
+#[cfg(feature = "render")]
+#[derive(Resource)]
+pub(super) struct SelectedMaterial(Handle<ColorMaterial>);
+
+#[cfg(feature = "render")]
+impl FromWorld for SelectedMaterial {
+    fn from_world(world: &mut World) -> Self {
+        SelectedMaterial(world.resource_mut::<Assets<ColorMaterial>>().add(Color::srgb(1., 0.9, 0.2)))
+    }
+}
+
+/// Draws selected boids with the selected material, and gives deselected
+/// boids their own material back.
+#[cfg(feature = "render")]
+#[allow(clippy::type_complexity)]
+pub(super) fn highlight_selected(
+    highlight: Res<SelectedMaterial>,
+    custom: Res<BoidMaterial>,
+    species_materials: Res<SpeciesMaterials>,
+    mut selected: Query<&mut Handle<ColorMaterial>, (With<Selected>, With<Boid>)>,
+    mut deselected: RemovedComponents<Selected>,
+    mut unselected: Query<(&Species, Option<&PaletteMaterial>, &mut Handle<ColorMaterial>), (With<Boid>, Without<Selected>)>,
+) {
+    for mut material in selected.iter_mut() {
+        if *material != highlight.0 {
+            *material = highlight.0.clone();
+        }
+    }
+    for entity in deselected.read() {
+        if let Ok((species, own, mut material)) = unselected.get_mut(entity) {
+            *material = species_materials.boid_material(&custom, own, *species);
+        }
+    }
+}
+
+/// Text next to each selected boid's chart, reused from frame to frame.
+#[cfg(feature = "render")]
+#[derive(Resource, Default)]
+pub(super) struct Readouts(Vec<Entity>);
+
+/// Writes each selected boid's position, speed, neighbour count and
+/// steering forces above its chart, the forces in their chart colours.
+#[cfg(feature = "render")]
+pub(super) fn update_readouts(
+    mut commands: Commands,
+    mut readouts: ResMut<Readouts>,
+    selected: Query<(Entity, &Position, &Velocity, &SteeringForces, &BoidHistory), With<Selected>>,
+    mut texts: Query<(&mut Text, &mut Transform, &mut Visibility), Without<Boid>>,
+) {
+    let style = |color: Color| TextStyle { font_size: READOUT_FONT_SIZE, color, ..default() };
+    let mut shown = 0;
+    for (entity, pos, vel, forces, history) in selected.iter() {
+        let neighbours = history.samples.back().map_or(0, |sample| sample.neighbours);
+        let summary = format!(
+            "{entity}\n({:.0}, {:.0})  {:.0} u/s  {neighbours} neighbours\n",
+            pos.0.x,
+            pos.0.y,
+            vel.0.length(),
+        );
+        let mut sections = vec![TextSection::new(summary, style(Color::WHITE))];
+        for ((name, magnitude), color) in FORCE_NAMES.into_iter().zip(forces.magnitudes()).zip(FORCE_COLORS) {
+            sections.push(TextSection::new(format!("{name} {magnitude:.0}  "), style(color)));
+        }
+        let text = Text::from_sections(sections);
+        let corner = pos.0 + CHART_OFFSET + Vec2::Y * (CHART_ROW_HEIGHT * 3. + 4.);
+        let transform = Transform::from_translation(corner.extend(READOUT_Z));
+
+        if let Some(&readout) = readouts.0.get(shown) {
+            if let Ok((mut old, mut old_transform, mut visibility)) = texts.get_mut(readout) {
+                *old = text;
+                *old_transform = transform;
+                *visibility = Visibility::Visible;
+            }
+        } else {
+            let readout = commands
+                .spawn(Text2dBundle { text, transform, text_anchor: Anchor::BottomLeft, ..default() })
+                .id();
+            readouts.0.push(readout);
+        }
+        shown += 1;
+    }
+    for &readout in readouts.0.iter().skip(shown) {
+        if let Ok((_, _, mut visibility)) = texts.get_mut(readout) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
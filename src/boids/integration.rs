@@ -0,0 +1,11 @@
+use bevy::prelude::Resource;
+
+pub use boids_core::integration::{Drag, Integrator, State};
+
+/// The integrator boids are moved with.
+#[derive(Resource)]
+pub(super) struct Integration(pub(super) Integrator);
+
+/// Drag on every boid, present when the plugin was set up with some.
+#[derive(Resource)]
+pub(super) struct DragForce(pub(super) Drag);
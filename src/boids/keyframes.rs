@@ -0,0 +1,112 @@
+use bevy::prelude::{Res, ResMut, Resource, Time};
+
+use super::{BoidSpawner, BoidsSettings, MaxBoidCount, SpawnArea};
+
+/// Tunable value a keyframe track can drive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    Separation,
+    Alignment,
+    Cohesion,
+    /// Number of boids in the flock, spawned and despawned as it changes
+    BoidCount,
+}
+
+impl Parameter {
+    fn set(self, settings: &mut BoidsSettings, value: f32) {
+        match self {
+            Parameter::Separation => settings.separation = value,
+            Parameter::Alignment => settings.alignment = value,
+            Parameter::Cohesion => settings.cohesion = value,
+            // followed by the spawner instead, see `follow_boid_count`
+            Parameter::BoidCount => {}
+        }
+    }
+}
+
+/// Values of one parameter at points in time, in seconds since startup.
+/// Between keys the value is interpolated linearly; before the first and
+/// after the last key it holds.
+#[derive(Clone, Debug)]
+pub struct KeyframeTrack {
+    pub parameter: Parameter,
+    keys: Vec<(f32, f32)>,
+}
+
+impl KeyframeTrack {
+    pub fn new(parameter: Parameter) -> Self {
+        KeyframeTrack {
+            parameter,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Adds a key, keeping the keys sorted by time.
+    pub fn key(mut self, seconds: f32, value: f32) -> Self {
+        let index = self.keys.partition_point(|&(time, _)| time <= seconds);
+        self.keys.insert(index, (seconds, value));
+        self
+    }
+
+    pub fn sample(&self, seconds: f32) -> Option<f32> {
+        let index = self.keys.partition_point(|&(time, _)| time <= seconds);
+        if index == 0 {
+            return self.keys.first().map(|&(_, value)| value);
+        }
+        let (start, from) = self.keys[index - 1];
+        let Some(&(end, to)) = self.keys.get(index) else {
+            return Some(from);
+        };
+        let t = (seconds - start) / (end - start);
+        Some(from + (to - from) * t)
+    }
+}
+
+#[derive(Resource, Default)]
+pub(super) struct KeyframeTracks(pub(super) Vec<KeyframeTrack>);
+
+pub(super) fn animate_parameters(
+    tracks: Res<KeyframeTracks>,
+    mut settings: ResMut<BoidsSettings>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    for track in tracks.0.iter() {
+        if let Some(value) = track.sample(now) {
+            track.parameter.set(&mut settings, value);
+        }
+    }
+}
+
+/// Spawns or despawns boids until the flock is as large as the boid count
+/// tracks say, newest boids first, and moves the max boid count along so
+/// the spawner doesn't fill the flock back up.
+pub(super) fn follow_boid_count(
+    tracks: Res<KeyframeTracks>,
+    time: Res<Time>,
+    area: Res<SpawnArea>,
+    mut max_boid_count: ResMut<MaxBoidCount>,
+    mut spawner: BoidSpawner,
+) {
+    let now = time.elapsed_seconds();
+    let Some(target) = tracks.0
+        .iter()
+        .filter(|track| track.parameter == Parameter::BoidCount)
+        .find_map(|track| track.sample(now))
+    else {
+        return;
+    };
+    let target = target.max(0.).round() as usize;
+    let live = spawner.boids.0.len();
+    if target > live {
+        for _ in live..target {
+            spawner.spawn_in(*area, None);
+        }
+    } else if target < live {
+        let removed = spawner.boids.0[target..].to_vec();
+        for entity in removed {
+            spawner.despawn(entity);
+        }
+    }
+    max_boid_count.0 = spawner.boids.0.len() as u32;
+}
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+use bevy::{
+    input::ButtonInput,
+    prelude::{
+        default,
+        Color,
+        Commands,
+        Entity,
+        KeyCode,
+        Query,
+        Res,
+        ResMut,
+        Resource,
+        Text,
+        Text2dBundle,
+        TextStyle,
+        Transform,
+        Vec2,
+        Visibility,
+        With,
+        Without,
+    },
+};
+
+use super::{
+    disturbance::Panic,
+    foraging::Forager,
+    inspector::Selected,
+    roosting::Dormant,
+    Boid,
+    Position,
+    Velocity,
+};
+
+// at most one label per cell of this size, so dense flocks stay readable
+const DECLUTTER_CELL: f32 = 60.;
+const MAX_LABELS: usize = 150;
+const LABEL_OFFSET: Vec2 = Vec2::new(0., 14.);
+const LABEL_Z: f32 = 10.;
+const FONT_SIZE: f32 = 11.;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LabelMode {
+    #[default]
+    Off,
+    Selected,
+    All,
+}
+
+/// Floating debug text above boids. L cycles between off, selected boids
+/// only and every boid.
+#[derive(Resource, Default)]
+pub struct BoidLabels {
+    pub mode: LabelMode,
+    /// Text entities, reused from frame to frame
+    pool: Vec<Entity>,
+}
+
+pub(super) fn label_input(keys: Res<ButtonInput<KeyCode>>, mut labels: ResMut<BoidLabels>) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        labels.mode = match labels.mode {
+            LabelMode::Off => LabelMode::Selected,
+            LabelMode::Selected => LabelMode::All,
+            LabelMode::All => LabelMode::Off,
+        };
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn update_labels(
+    mut commands: Commands,
+    mut labels: ResMut<BoidLabels>,
+    boids: Query<(
+        Entity,
+        &Position,
+        &Velocity,
+        Option<&Selected>,
+        Option<&Dormant>,
+        Option<&Panic>,
+        &Forager,
+    ), With<Boid>>,
+    mut texts: Query<(&mut Text, &mut Transform, &mut Visibility), Without<Boid>>,
+) {
+    let mut occupied = HashSet::new();
+    let mut shown = 0;
+    if labels.mode != LabelMode::Off {
+        // selected boids claim their cells first
+        let mut candidates: Vec<_> = boids
+            .iter()
+            .filter(|(_, _, _, selected, ..)| labels.mode == LabelMode::All || selected.is_some())
+            .collect();
+        candidates.sort_by_key(|(_, _, _, selected, ..)| selected.is_none());
+
+        for (entity, pos, vel, _, dormant, panic, forager) in candidates {
+            if shown == MAX_LABELS {
+                break;
+            }
+            let cell = (pos.0 / DECLUTTER_CELL).floor().as_ivec2();
+            if !occupied.insert(cell) {
+                continue;
+            }
+            let state = if dormant.is_some() {
+                "asleep"
+            } else if panic.is_some() {
+                "panicked"
+            } else if forager.target.is_some() {
+                "recruited"
+            } else {
+                "flying"
+            };
+            let value = format!("{entity}\n{:.0} u/s\n{state}", vel.0.length());
+            let transform = Transform::from_translation((pos.0 + LABEL_OFFSET).extend(LABEL_Z));
+
+            if let Some(&label) = labels.pool.get(shown) {
+                if let Ok((mut text, mut label_transform, mut visibility)) = texts.get_mut(label) {
+                    text.sections[0].value = value;
+                    *label_transform = transform;
+                    *visibility = Visibility::Visible;
+                }
+            } else {
+                let label = commands
+                    .spawn(Text2dBundle {
+                        text: Text::from_section(value, TextStyle {
+                            font_size: FONT_SIZE,
+                            color: Color::srgba(1., 1., 1., 0.8),
+                            ..default()
+                        }),
+                        transform,
+                        ..default()
+                    })
+                    .id();
+                labels.pool.push(label);
+            }
+            shown += 1;
+        }
+    }
+
+    for &label in labels.pool.iter().skip(shown) {
+        if let Ok((_, _, mut visibility)) = texts.get_mut(label) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
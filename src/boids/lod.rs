@@ -0,0 +1,140 @@
+use bevy::{
+    prelude::{
+        Assets,
+        Camera2d,
+        Commands,
+        DetectChanges,
+        Mesh,
+        OrthographicProjection,
+        Query,
+        Rectangle,
+        Ref,
+        Res,
+        ResMut,
+        Resource,
+        Visibility,
+        With,
+    },
+    sprite::Mesh2dHandle,
+};
+
+use super::{render::BoidMesh, Boid, Boids};
+
+// on-screen height of the full mesh in pixels below which boids become points
+const POINT_THRESHOLD_PX: f32 = 4.;
+// zooming back in must clear the threshold by this factor to switch back
+const HYSTERESIS: f32 = 1.25;
+const POINT_SIZE_PX: f32 = 2.;
+// on-screen height below which individual boids give way to the continuum
+// view, and the flock size above which they always do
+const FIELD_THRESHOLD_PX: f32 = 1.;
+const FIELD_BOID_COUNT: usize = 20_000;
+// height of the boid triangle in world units
+const BOID_SIZE: f32 = 9.;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LodLevel {
+    Full,
+    Point,
+    /// Boids hidden in favour of the density and velocity field drawn by
+    /// the continuum view
+    Field,
+}
+
+impl LodLevel {
+    fn next(self, pixels: f32, crowded: bool) -> Self {
+        let field = match self {
+            LodLevel::Field => pixels <= FIELD_THRESHOLD_PX * HYSTERESIS,
+            _ => pixels < FIELD_THRESHOLD_PX,
+        };
+        if crowded || field {
+            return LodLevel::Field;
+        }
+        match self {
+            LodLevel::Full if pixels < POINT_THRESHOLD_PX => LodLevel::Point,
+            LodLevel::Point | LodLevel::Field if pixels > POINT_THRESHOLD_PX * HYSTERESIS => LodLevel::Full,
+            LodLevel::Field => LodLevel::Point,
+            level => level,
+        }
+    }
+}
+
+/// Swaps boid meshes between the full triangle and a pixel-sized point
+/// depending on how large boids appear on screen, and hides them when
+/// zoomed out so far or with so many boids that the continuum view takes
+/// over.
+#[derive(Resource)]
+pub(super) struct BoidLod {
+    level: LodLevel,
+    point: Mesh2dHandle,
+    point_scale: f32,
+}
+
+impl BoidLod {
+    pub(super) fn level(&self) -> LodLevel {
+        self.level
+    }
+
+    fn mesh(&self, full: &BoidMesh) -> Mesh2dHandle {
+        match self.level {
+            LodLevel::Full => full.0.clone(),
+            LodLevel::Point | LodLevel::Field => self.point.clone(),
+        }
+    }
+
+    fn visibility(&self) -> Visibility {
+        match self.level {
+            LodLevel::Field => Visibility::Hidden,
+            _ => Visibility::Inherited,
+        }
+    }
+}
+
+pub(super) fn setup_lod(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(BoidLod {
+        level: LodLevel::Full,
+        point: Mesh2dHandle(meshes.add(Rectangle::from_length(POINT_SIZE_PX))),
+        point_scale: 1.,
+    });
+}
+
+pub(super) fn update_lod(
+    mut lod: ResMut<BoidLod>,
+    full: Res<BoidMesh>,
+    flock: Res<Boids>,
+    cameras: Query<&OrthographicProjection, With<Camera2d>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut boids: Query<(&mut Mesh2dHandle, &mut Visibility, Ref<Boid>)>,
+) {
+    let Ok(projection) = cameras.get_single() else {
+        return;
+    };
+    if projection.scale <= 0f32 {
+        return;
+    }
+    let pixels = BOID_SIZE / projection.scale;
+    let level = lod.level.next(pixels, flock.0.len() > FIELD_BOID_COUNT);
+
+    // keep points a constant size on screen
+    if level != LodLevel::Full && lod.point_scale != projection.scale {
+        lod.point_scale = projection.scale;
+        if let Some(mesh) = meshes.get_mut(&lod.point.0) {
+            *mesh = Rectangle::from_length(POINT_SIZE_PX * projection.scale).into();
+        }
+    }
+
+    // a replaced full mesh goes onto the boids as if switching to it
+    let switched = level != lod.level || (level == LodLevel::Full && full.is_changed());
+    lod.level = level;
+    // boids spawned since the last switch start with the full mesh
+    if switched || level != LodLevel::Full {
+        let mesh = lod.mesh(&full);
+        let visibility = lod.visibility();
+        for (mut handle, mut shown, boid) in boids.iter_mut() {
+            if switched || boid.is_added() {
+                *handle = mesh.clone();
+                *shown = visibility;
+            }
+        }
+    }
+}
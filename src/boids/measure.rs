@@ -0,0 +1,151 @@
+use bevy::{
+    input::ButtonInput,
+    prelude::{KeyCode, MouseButton, Res, ResMut, Resource, Vec2},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{
+    default,
+    Camera2d,
+    Color,
+    Commands,
+    Component,
+    Gizmos,
+    OrthographicProjection,
+    Query,
+    Text,
+    Text2dBundle,
+    TextStyle,
+    Transform,
+    Vec3,
+    Visibility,
+    With,
+    Without,
+};
+
+use crate::tools::{ActiveTool, CursorWorld};
+#[cfg(feature = "render")]
+use super::BoidsSettings;
+
+#[cfg(feature = "render")]
+const LABEL_Z: f32 = 10.;
+#[cfg(feature = "render")]
+const FONT_SIZE: f32 = 14.;
+// on-screen distance between the ruler and its label, in pixels
+#[cfg(feature = "render")]
+const LABEL_OFFSET_PX: f32 = 18.;
+#[cfg(feature = "render")]
+const RULER_COLOR: Color = Color::srgb(1., 0.9, 0.3);
+#[cfg(feature = "render")]
+const SEPARATION_COLOR: Color = Color::srgba(1., 0.4, 0.4, 0.6);
+#[cfg(feature = "render")]
+const NEIGHBOUR_COLOR: Color = Color::srgba(0.4, 0.6, 1., 0.6);
+
+/// Two points measured with the ruler tool. The end follows the cursor
+/// until it is placed.
+#[derive(Resource, Default)]
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(super) struct Ruler {
+    start: Option<Vec2>,
+    end: Option<Vec2>,
+    placed: bool,
+}
+
+/// Marks the text showing the ruler's length.
+#[cfg(feature = "render")]
+#[derive(Component)]
+pub(super) struct RulerLabel;
+
+/// M toggles the ruler. Left clicks place its start and end, a third click
+/// starts a new measurement and right click clears it.
+pub(super) fn measure_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut ruler: ResMut<Ruler>,
+) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        tool.toggle(ActiveTool::Measure);
+    }
+    if *tool != ActiveTool::Measure {
+        return;
+    }
+    if buttons.just_pressed(MouseButton::Right) {
+        *ruler = Ruler::default();
+        return;
+    }
+    let Some(cursor) = cursor.0 else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Left) {
+        if ruler.start.is_none() || ruler.placed {
+            *ruler = Ruler { start: Some(cursor), end: Some(cursor), placed: false };
+        } else {
+            ruler.end = Some(cursor);
+            ruler.placed = true;
+        }
+    } else if ruler.start.is_some() && !ruler.placed {
+        ruler.end = Some(cursor);
+    }
+}
+
+#[cfg(feature = "render")]
+pub(super) fn setup_ruler(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("", TextStyle {
+                font_size: FONT_SIZE,
+                color: RULER_COLOR,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        RulerLabel,
+    ));
+}
+
+/// Draws the ruler with its length, and circles of the separation and
+/// neighbour radii around its start so they can be compared at any zoom.
+#[cfg(feature = "render")]
+#[allow(clippy::type_complexity)]
+pub(super) fn draw_ruler(
+    mut gizmos: Gizmos,
+    ruler: Res<Ruler>,
+    tool: Res<ActiveTool>,
+    settings: Res<BoidsSettings>,
+    cameras: Query<&OrthographicProjection, With<Camera2d>>,
+    mut labels: Query<(&mut Text, &mut Transform, &mut Visibility), (With<RulerLabel>, Without<Camera2d>)>,
+) {
+    let Ok((mut text, mut transform, mut visibility)) = labels.get_single_mut() else {
+        return;
+    };
+    let (Some(start), Some(end)) = (ruler.start, ruler.end) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    if *tool != ActiveTool::Measure {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    // keep the label the same size on screen however far the camera zooms
+    let zoom = cameras.get_single().map_or(1., |projection| projection.scale);
+
+    gizmos.line_2d(start, end, RULER_COLOR);
+    let normal = (end - start).normalize_or_zero().perp() * LABEL_OFFSET_PX * zoom;
+    for point in [start, end] {
+        gizmos.line_2d(point - normal / 2., point + normal / 2., RULER_COLOR);
+    }
+    gizmos.circle_2d(start, settings.desired_separation, SEPARATION_COLOR);
+    gizmos.circle_2d(start, settings.neighbour_radius, NEIGHBOUR_COLOR);
+
+    text.sections[0].value = format!(
+        "{:.1}\nseparation {:.0}, neighbours {:.0}",
+        start.distance(end),
+        settings.desired_separation,
+        settings.neighbour_radius,
+    );
+    *transform = Transform::from_translation(((start + end) / 2. + normal).extend(LABEL_Z))
+        .with_scale(Vec3::splat(zoom));
+    *visibility = Visibility::Visible;
+}
@@ -0,0 +1,144 @@
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    Event,
+    EventReader,
+    EventWriter,
+    Query,
+    Res,
+    Resource,
+    Vec2,
+    With,
+    Without,
+};
+
+use super::{species::BodyRadius, Boid, Position, Velocity};
+
+/// Circular obstacle boids steer around and bounce off.
+#[derive(Component)]
+pub struct Obstacle {
+    pub radius: f32,
+}
+
+/// Soft obstacle that loses health every time a boid hits it, shrinking
+/// until it is despawned.
+#[derive(Component)]
+pub struct Erodible {
+    pub health: f32,
+    pub max_health: f32,
+    base_radius: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ObstacleDesc {
+    pub position: Vec2,
+    pub radius: f32,
+    /// Health of a soft obstacle, `None` for one that never erodes.
+    pub health: Option<f32>,
+}
+
+/// Sent when a boid collides with an obstacle.
+#[derive(Event)]
+pub struct ObstacleHit {
+    pub obstacle: Entity,
+    pub boid: Entity,
+}
+
+/// Sent when obstacles move, shrink or disappear so cached distance
+/// fields can be re-baked.
+#[derive(Event)]
+pub struct ObstaclesChanged;
+
+/// Damage dealt to a soft obstacle per boid collision.
+#[derive(Resource)]
+pub struct Erosion {
+    pub damage_per_hit: f32,
+}
+
+impl Default for Erosion {
+    fn default() -> Self {
+        Erosion {
+            damage_per_hit: 1.,
+        }
+    }
+}
+
+/// Obstacles spawned at startup.
+#[derive(Resource, Default)]
+pub(super) struct ObstacleLayout(pub(super) Vec<ObstacleDesc>);
+
+pub(super) fn spawn_obstacle(commands: &mut Commands, desc: ObstacleDesc) -> Entity {
+    let mut entity = commands.spawn((
+        Obstacle { radius: desc.radius },
+        Position(desc.position),
+    ));
+    if let Some(health) = desc.health {
+        entity.insert(Erodible {
+            health,
+            max_health: health,
+            base_radius: desc.radius,
+        });
+    }
+    entity.id()
+}
+
+pub(super) fn spawn_obstacles(mut commands: Commands, layout: Res<ObstacleLayout>) {
+    for desc in layout.0.iter() {
+        spawn_obstacle(&mut commands, *desc);
+    }
+}
+
+pub(super) fn collide_with_obstacles(
+    mut boids: Query<(Entity, &mut Position, &mut Velocity, &BodyRadius), With<Boid>>,
+    obstacles: Query<(Entity, &Position, &Obstacle), Without<Boid>>,
+    mut hits: EventWriter<ObstacleHit>,
+) {
+    for (boid, mut pos, mut vel, radius) in boids.iter_mut() {
+        for (obstacle, center, shape) in obstacles.iter() {
+            let offset = pos.0 - center.0;
+            let min_distance = shape.radius + radius.0;
+            let distance = offset.length();
+            if distance < min_distance {
+                let normal = if distance > 0f32 { offset / distance } else { Vec2::Y };
+                // push out to the surface and reflect the inward velocity
+                pos.0 = center.0 + normal * min_distance;
+                let inward = vel.0.dot(normal);
+                if inward < 0f32 {
+                    vel.0 -= normal * inward * 2.;
+                }
+                hits.send(ObstacleHit { obstacle, boid });
+            }
+        }
+    }
+}
+
+pub(super) fn erode_obstacles(
+    mut commands: Commands,
+    mut hits: EventReader<ObstacleHit>,
+    mut obstacles: Query<(&mut Obstacle, &mut Erodible)>,
+    erosion: Res<Erosion>,
+    mut changed: EventWriter<ObstaclesChanged>,
+) {
+    let mut any_changed = false;
+    for hit in hits.read() {
+        let Ok((mut obstacle, mut erodible)) = obstacles.get_mut(hit.obstacle) else {
+            continue;
+        };
+        if erodible.health <= 0f32 {
+            // already despawning
+            continue;
+        }
+        erodible.health -= erosion.damage_per_hit;
+        if erodible.health <= 0f32 {
+            commands.entity(hit.obstacle).despawn();
+        } else {
+            // area shrinks in proportion to the remaining health
+            obstacle.radius = erodible.base_radius * (erodible.health / erodible.max_health).sqrt();
+        }
+        any_changed = true;
+    }
+    if any_changed {
+        changed.send(ObstaclesChanged);
+    }
+}
@@ -0,0 +1,83 @@
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::{Component, Query, Res, Time, Vec2};
+
+use super::Velocity;
+
+// spring pulling the heading toward the direction of travel
+const TURN_STIFFNESS: f32 = 60.;
+const SPIN_DAMPING: f32 = 12.;
+// fastest a boid's flight and its drawn heading turn, radians per second
+const MAX_TURN_RATE: f32 = 2. * TAU;
+// how much the mesh narrows at the max turn rate
+#[cfg(feature = "render")]
+const BANK_FACTOR: f32 = 0.4;
+
+/// Facing of a boid in radians, counter-clockwise from the x-axis.
+#[derive(Component)]
+pub(super) struct Heading(pub(super) f32);
+
+#[derive(Component, Default)]
+pub(super) struct AngularVelocity(pub(super) f32);
+
+#[cfg(feature = "render")]
+impl AngularVelocity {
+    /// Width of the mesh while banking into a turn, as a fraction of its
+    /// width when flying straight.
+    pub(super) fn bank(&self) -> f32 {
+        1. - BANK_FACTOR * (self.0 / MAX_TURN_RATE).abs()
+    }
+}
+
+/// `velocity` turned back towards `previous` as far as needed to keep the
+/// turn over `dt` within the max turn rate, at the same speed. Boids that
+/// were standing still set off in any direction.
+pub(super) fn limit_turn(previous: Vec2, velocity: Vec2, dt: f32) -> Vec2 {
+    if previous == Vec2::ZERO || velocity == Vec2::ZERO {
+        return velocity;
+    }
+    let turn = wrap_angle(velocity.to_angle() - previous.to_angle());
+    let max_turn = MAX_TURN_RATE * dt;
+    if turn.abs() <= max_turn {
+        return velocity;
+    }
+    Vec2::from_angle(previous.to_angle() + max_turn.copysign(turn)) * velocity.length()
+}
+
+/// Wraps an angle into `[-PI, PI)`.
+pub(super) fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+pub(super) fn update_heading(
+    mut query: Query<(&Velocity, &mut Heading, &mut AngularVelocity)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (vel, mut heading, mut angular) in query.iter_mut() {
+        let error = if vel.0.length_squared() > 0f32 {
+            wrap_angle(vel.0.y.atan2(vel.0.x) - heading.0)
+        } else {
+            0.
+        };
+        let torque = error * TURN_STIFFNESS - angular.0 * SPIN_DAMPING;
+        angular.0 = (angular.0 + torque * dt).clamp(-MAX_TURN_RATE, MAX_TURN_RATE);
+        heading.0 = wrap_angle(heading.0 + angular.0 * dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reversing_turns_at_the_max_turn_rate() {
+        let dt = 1. / 60.;
+        let turned = limit_turn(Vec2::X * 100., Vec2::NEG_X * 50., dt);
+        assert!((turned.length() - 50.).abs() < 1e-3);
+        assert!((turned.to_angle().abs() - MAX_TURN_RATE * dt).abs() < 1e-4);
+        // gentle turns and boids setting off are left alone
+        let gentle = Vec2::from_angle(0.01) * 100.;
+        assert_eq!(limit_turn(Vec2::X * 100., gentle, dt), gentle);
+        assert_eq!(limit_turn(Vec2::ZERO, Vec2::NEG_X, dt), Vec2::NEG_X);
+    }
+}
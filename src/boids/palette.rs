@@ -0,0 +1,88 @@
+use std::{collections::HashMap, fs, io, path::Path};
+use bevy::{
+    prelude::{Assets, Color, Component, Handle, Image, Resource, Vec2},
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::TextureFormat,
+        texture::{CompressedImageFormats, ImageSampler, ImageType},
+    },
+    sprite::ColorMaterial,
+};
+
+use super::WorldBounds;
+
+// levels per colour channel sampled colours are rounded to, so boids of
+// nearly the same colour share a material and are drawn in one batch
+const LEVELS: u32 = 32;
+
+/// Picture boids take their colour from at the spot they spawn, stretched
+/// over the world bounds.
+#[derive(Resource)]
+pub(super) struct PaletteImage {
+    width: u32,
+    height: u32,
+    /// sRGB bytes, four per pixel, row by row from the top
+    pixels: Vec<u8>,
+    materials: HashMap<[u8; 3], Handle<ColorMaterial>>,
+}
+
+/// Material a boid was given from the [`PaletteImage`] when it spawned,
+/// drawn in place of its species colour for the rest of its life.
+#[derive(Component)]
+pub(super) struct PaletteMaterial(pub(super) Handle<ColorMaterial>);
+
+impl PaletteImage {
+    /// Decodes the image at `path`, in any format Bevy reads by its
+    /// extension.
+    pub(super) fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |reason: String| io::Error::new(io::ErrorKind::InvalidData, reason);
+        let bytes = fs::read(path)?;
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        let image = Image::from_buffer(
+            &bytes,
+            ImageType::Extension(extension),
+            CompressedImageFormats::NONE,
+            true,
+            ImageSampler::Default,
+            RenderAssetUsages::MAIN_WORLD,
+        )
+        .map_err(|err| invalid(err.to_string()))?;
+        let rgba = image
+            .convert(TextureFormat::Rgba8UnormSrgb)
+            .ok_or_else(|| invalid(format!("unsupported pixel format {:?}", image.texture_descriptor.format)))?;
+        if rgba.width() == 0 || rgba.height() == 0 {
+            return Err(invalid("empty image".into()));
+        }
+        Ok(PaletteImage {
+            width: rgba.width(),
+            height: rgba.height(),
+            pixels: rgba.data,
+            materials: HashMap::new(),
+        })
+    }
+
+    /// Material in the colour of the pixel under `position`, shared with
+    /// every boid of the same rounded colour. Positions outside the world
+    /// take the colour of the nearest edge.
+    pub(super) fn material_at(
+        &mut self,
+        position: Vec2,
+        bounds: &WorldBounds,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        let uv = (position / (bounds.half_extents * 2.) + 0.5).clamp(Vec2::ZERO, Vec2::ONE);
+        // image rows run down the picture, world y up the screen
+        let x = ((uv.x * self.width as f32) as u32).min(self.width - 1);
+        let y = (((1. - uv.y) * self.height as f32) as u32).min(self.height - 1);
+        let index = (y * self.width + x) as usize * 4;
+        let level = |channel: u8| ((channel as u32 * (LEVELS - 1) + 127) / 255) as u8;
+        let key = [level(self.pixels[index]), level(self.pixels[index + 1]), level(self.pixels[index + 2])];
+        self.materials
+            .entry(key)
+            .or_insert_with(|| {
+                let [r, g, b] = key.map(|level| (level as u32 * 255 / (LEVELS - 1)) as u8);
+                materials.add(Color::srgb_u8(r, g, b))
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    input::ButtonInput,
+    prelude::{KeyCode, Local, Res, ResMut},
+};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use super::{settings::SettingsProblems, BoidSpawner, BoidsSettings, MaxBoidCount};
+
+// largest flock the boid count slider goes up to
+const MAX_BOIDS: usize = 5000;
+
+/// Window (F8) with sliders for the flocking parameters and the number of
+/// boids, applied to the running simulation as they are dragged, the
+/// current frame rate and entity count, and any problems with the
+/// parameters.
+pub struct SettingsPanelPlugin;
+
+impl Plugin for SettingsPanelPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.add_systems(Update, settings_panel);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn settings_panel(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut open: Local<bool>,
+    mut settings: ResMut<BoidsSettings>,
+    mut max_boid_count: ResMut<MaxBoidCount>,
+    problems: Res<SettingsProblems>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut spawner: BoidSpawner,
+) {
+    if keys.just_pressed(KeyCode::F8) {
+        *open = !*open;
+    }
+    if !*open {
+        return;
+    }
+
+    // edit a copy so the settings are only marked changed when something was
+    let mut edited = *settings;
+    let live = spawner.boids.0.len();
+    let mut count = live;
+    let reading = |value: Option<f64>| value.map_or_else(|| "N/A".to_string(), |value| format!("{value:.0}"));
+    let fps = reading(diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed()));
+    let entities = reading(diagnostics
+        .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        .and_then(|count| count.value()));
+    egui::Window::new("Settings").open(&mut *open).show(contexts.ctx_mut(), |ui| {
+        ui.label(format!("FPS: {fps}  Entities: {entities}"));
+        ui.separator();
+        ui.add(egui::Slider::new(&mut edited.separation, 0.0..=3.).text("separation"));
+        ui.add(egui::Slider::new(&mut edited.alignment, 0.0..=3.).text("alignment"));
+        ui.add(egui::Slider::new(&mut edited.cohesion, 0.0..=3.).text("cohesion"));
+        ui.add(egui::Slider::new(&mut edited.desired_separation, 5.0..=200.).text("separation radius"));
+        ui.add(egui::Slider::new(&mut edited.neighbour_radius, 10.0..=300.).text("neighbour radius"));
+        ui.add(egui::Slider::new(&mut edited.field_of_view, 0.0..=360.).text("field of view"));
+        ui.add(egui::Slider::new(&mut edited.max_speed, 10.0..=1000.).text("max speed"));
+        ui.add(egui::Slider::new(&mut edited.max_force, 10.0..=1000.).text("max force"));
+        for problem in &problems.0 {
+            ui.colored_label(egui::Color32::LIGHT_RED, &problem.message);
+        }
+        ui.separator();
+        ui.add(egui::Slider::new(&mut count, 0..=MAX_BOIDS).text("boids"));
+    });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+    if count > live {
+        // the spawner grows the flock to the new size
+        max_boid_count.0 = count as u32;
+    } else if count < live {
+        for entity in spawner.boids.0[count..].to_vec() {
+            spawner.despawn(entity);
+        }
+        max_boid_count.0 = spawner.boids.0.len() as u32;
+    }
+}
@@ -0,0 +1,43 @@
+use bevy::prelude::{Resource, Vec2};
+
+/// How far away a boid perceives others to be, which shapes the region it
+/// flocks with: its neighbours are the boids closer than the separation
+/// and neighbour radii by this measure.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum PerceptionMetric {
+    /// Plain distance, so boids perceive a circle around them
+    #[default]
+    Circular,
+    /// Boids perceive an ellipse `aspect` times as long along their heading
+    /// as it is wide, with the same area as the circle. Forward-looking
+    /// boids tend to string out into lines, sideways-looking ones into
+    /// broad fronts.
+    Elliptical { aspect: f32 },
+}
+
+impl PerceptionMetric {
+    /// Perceived distance to something `offset` away from a boid flying
+    /// along `heading`. Boids standing still perceive a circle.
+    pub(super) fn distance(self, offset: Vec2, heading: Vec2) -> f32 {
+        let PerceptionMetric::Elliptical { aspect } = self else {
+            return offset.length();
+        };
+        let heading = heading.normalize_or_zero();
+        if heading == Vec2::ZERO || aspect.is_nan() || aspect <= 0. {
+            return offset.length();
+        }
+        // stretched along and squeezed across the heading by the same
+        // factor, keeping the area
+        let stretch = aspect.sqrt();
+        Vec2::new(offset.dot(heading) / stretch, heading.perp_dot(offset) * stretch).length()
+    }
+
+    /// Farthest away something can be and still be perceived closer than
+    /// `radius`, bounding the neighbour lookup.
+    pub(super) fn reach(self, radius: f32) -> f32 {
+        match self {
+            PerceptionMetric::Elliptical { aspect } if aspect > 0. => radius * aspect.sqrt().max(1. / aspect.sqrt()),
+            _ => radius,
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{f32::consts::TAU, ops::Mul};
+use bevy::prelude::{Commands, Component, Entity, Query, Res, ResMut, Resource, Time, Vec2, With, Without};
+
+use super::{
+    despawning::Despawning,
+    flash::{EventFlashes, Flash},
+    grid::SpatialGrid,
+    species::BodyRadius,
+    timestep::PreviousPosition,
+    Boid,
+    BoidSpawner,
+    BoidsSettings,
+    BoundaryMode,
+    Position,
+    RandomGenerator,
+    Velocity,
+    WorldBounds,
+    R,
+};
+
+/// Predators are this many times larger than boids.
+pub(super) const PREDATOR_SIZE: f32 = 2.;
+// how far predators look for prey
+const SIGHT: f32 = 400.;
+// prey closer than this to a predator is caught
+const CATCH_RADIUS: f32 = 8.;
+// longest a pursuit or evasion leads its target, in seconds
+const MAX_LEAD: f32 = 1.;
+
+/// A predator hunting the flock. Predators are not boids: they ignore the
+/// flocking rules and chase the nearest boid they can see.
+#[derive(Component)]
+pub struct Predator;
+
+/// Predators spawned at startup and whether they catch the boids they
+/// reach.
+#[derive(Resource, Clone, Copy, Debug)]
+pub(super) struct PredatorConfig {
+    pub(super) count: u32,
+    pub(super) catch_prey: bool,
+    /// Boundary mode predators are kept inside the world by, instead of
+    /// the plugin's
+    pub(super) boundary: Option<BoundaryMode>,
+}
+
+/// Predators spawned at startup as `(position, velocity)` pairs, on top of
+/// the [`PredatorConfig::count`] spawned at random.
+#[derive(Resource, Default)]
+pub(super) struct InitialPredators(pub(super) Vec<(Vec2, Vec2)>);
+
+/// Position `target` is heading for after `lead` seconds, where `lead`
+/// grows with distance so near targets are met head on.
+fn predict(from: Vec2, target: Vec2, velocity: Vec2, speed: f32) -> Vec2 {
+    let lead = if speed > 0. { (from.distance(target) / speed).min(MAX_LEAD) } else { 0. };
+    target + velocity * lead
+}
+
+/// Steering a boid away from the nearest predator within the evasion
+/// radius, aimed at where the predator is heading and stronger the closer
+/// it is.
+pub(super) fn evade(
+    boid: &Boid,
+    position: &Position,
+    velocity: &Velocity,
+    predators: &Query<(&Position, &Velocity), With<Predator>>,
+    settings: &BoidsSettings,
+) -> Vec2 {
+    let Some((threat, threat_velocity, dist)) = predators
+        .iter()
+        .map(|(pos, vel)| (pos.0, vel.0, pos.0.distance(position.0)))
+        .filter(|&(_, _, dist)| dist < settings.evasion_radius)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+    else {
+        return Vec2::ZERO;
+    };
+    let threat = predict(position.0, threat, threat_velocity, settings.predator_max_speed);
+    let direction = (position.0 - threat).normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return Vec2::ZERO;
+    }
+    let urgency = 1. - dist / settings.evasion_radius;
+    (direction * boid.max_speed - velocity.0)
+        .clamp_length_max(boid.max_force)
+        .mul(urgency)
+}
+
+pub(super) fn spawn_predators(
+    mut commands: Commands,
+    config: Res<PredatorConfig>,
+    initial: Res<InitialPredators>,
+    bounds: Res<WorldBounds>,
+    settings: Res<BoidsSettings>,
+    mut rng: ResMut<RandomGenerator>,
+) {
+    for &(position, velocity) in initial.0.iter() {
+        spawn_predator(&mut commands, position, velocity);
+    }
+    for _ in 0..config.count {
+        let position = Vec2::new(
+            rng.random_f32(-1.0..1.0) * bounds.half_extents.x,
+            rng.random_f32(-1.0..1.0) * bounds.half_extents.y,
+        );
+        let velocity = Vec2::from_angle(rng.random_f32(0.0..TAU)) * settings.predator_max_speed / 2.;
+        spawn_predator(&mut commands, position, velocity);
+    }
+}
+
+pub(super) fn spawn_predator(commands: &mut Commands, position: Vec2, velocity: Vec2) {
+    commands.spawn((
+        Predator,
+        Position(position),
+        PreviousPosition(position),
+        Velocity(velocity),
+        BodyRadius(R * PREDATOR_SIZE),
+    ));
+}
+
+/// Steers each predator towards where the nearest boid in sight is
+/// heading and moves it, keeping it inside the world.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn hunt(
+    mut predators: Query<(&mut Position, &mut Velocity, &BodyRadius), With<Predator>>,
+    prey: Query<&Velocity, Without<Predator>>,
+    grid: Res<SpatialGrid>,
+    settings: Res<BoidsSettings>,
+    bounds: Res<WorldBounds>,
+    mode: Res<BoundaryMode>,
+    config: Res<PredatorConfig>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    let mode = config.boundary.unwrap_or(*mode);
+    for (mut pos, mut vel, radius) in predators.iter_mut() {
+        let nearest = grid
+            .neighbours(pos.0, SIGHT)
+            .map(|(boid, target)| (boid, target, target.distance(pos.0)))
+            .filter(|&(_, _, dist)| dist < SIGHT)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+        if let Some((boid, target, _)) = nearest {
+            let target_velocity = prey.get(boid).map_or(Vec2::ZERO, |vel| vel.0);
+            let aim = predict(pos.0, target, target_velocity, settings.predator_max_speed);
+            let steer = ((aim - pos.0).normalize_or_zero() * settings.predator_max_speed - vel.0)
+                .clamp_length_max(settings.predator_max_force);
+            vel.0 += steer * dt;
+        }
+        vel.0 = vel.0.clamp_length_max(settings.predator_max_speed);
+        pos.0 += vel.0 * dt;
+        let (position, velocity) = mode.confine(&bounds, pos.0, vel.0, radius.0);
+        pos.0 = position;
+        vel.0 = velocity;
+    }
+}
+
+/// Despawns boids a predator has reached, if predators catch prey.
+pub(super) fn catch_prey(
+    predators: Query<&Position, With<Predator>>,
+    despawning: Query<(), With<Despawning>>,
+    grid: Res<SpatialGrid>,
+    config: Res<PredatorConfig>,
+    flashes: Option<Res<EventFlashes>>,
+    mut spawner: BoidSpawner,
+) {
+    if !config.catch_prey {
+        return;
+    }
+    let mut caught: Vec<Entity> = Vec::new();
+    for pos in predators.iter() {
+        caught.extend(
+            grid.neighbours(pos.0, CATCH_RADIUS)
+                .filter(|&(boid, target)| target.distance(pos.0) < CATCH_RADIUS && !despawning.contains(boid))
+                .map(|(boid, _)| boid),
+        );
+    }
+    caught.sort();
+    caught.dedup();
+    for boid in caught {
+        spawner.despawn(boid);
+        if flashes.is_some() {
+            spawner.commands.entity(boid).try_insert(Flash::new());
+        }
+    }
+}
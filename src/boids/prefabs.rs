@@ -0,0 +1,232 @@
+use std::{
+    f32::consts::TAU,
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+use bevy::{
+    input::ButtonInput,
+    log::{error, info},
+    prelude::{Event, EventReader, EventWriter, KeyCode, MouseButton, Query, Res, ResMut, Resource, Vec2, With, Without},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use crate::tools::{ActiveTool, CursorWorld};
+use super::{despawning::Despawning, Boid, BoidSpawner, Position, Species, Velocity};
+#[cfg(feature = "render")]
+use super::R;
+
+// how far one press of , or . turns the stamp
+const ROTATE_STEP: f32 = TAU / 24.;
+// drags shorter than this are treated as clicks and select nothing
+const MIN_SELECTION: f32 = 4.;
+#[cfg(feature = "render")]
+const SELECTION_COLOR: Color = Color::srgba(0.5, 1., 0.6, 0.8);
+#[cfg(feature = "render")]
+const PREVIEW_COLOR: Color = Color::srgba(0.5, 1., 0.6, 0.4);
+
+/// A boid saved in a prefab, relative to the centre of its group.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrefabBoid {
+    pub offset: Vec2,
+    pub velocity: Vec2,
+    /// The species sets the boid's speed, mass and size when stamped
+    pub species: Species,
+}
+
+/// A group of boids that can be stamped into the flock anywhere, at any
+/// rotation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Prefab(pub Vec<PrefabBoid>);
+
+impl Prefab {
+    /// Captures `boids` given as `(position, velocity, species)`, relative to
+    /// the centre of their positions.
+    pub fn capture(boids: &[(Vec2, Vec2, Species)]) -> Self {
+        if boids.is_empty() {
+            return Prefab::default();
+        }
+        let centre = boids.iter().map(|&(position, _, _)| position).sum::<Vec2>() / boids.len() as f32;
+        Prefab(boids
+            .iter()
+            .map(|&(position, velocity, species)| PrefabBoid { offset: position - centre, velocity, species })
+            .collect())
+    }
+
+    /// Positions and velocities of the boids with the prefab's centre at
+    /// `position`, turned by `rotation` radians.
+    pub fn place(&self, position: Vec2, rotation: f32) -> impl Iterator<Item = (Vec2, Vec2, Species)> + '_ {
+        let turn = Vec2::from_angle(rotation);
+        self.0
+            .iter()
+            .map(move |boid| (position + turn.rotate(boid.offset), turn.rotate(boid.velocity), boid.species))
+    }
+
+    /// One `x y vx vy species` line per boid.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed prefab");
+        let text = fs::read_to_string(path)?;
+        let mut boids = Vec::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let values: Vec<&str> = line.split_whitespace().collect();
+            let [x, y, vx, vy, species] = values[..] else {
+                return Err(invalid());
+            };
+            let number = |value: &str| value.parse::<f32>().map_err(|_| invalid());
+            boids.push(PrefabBoid {
+                offset: Vec2::new(number(x)?, number(y)?),
+                velocity: Vec2::new(number(vx)?, number(vy)?),
+                species: Species(species.parse().map_err(|_| invalid())?),
+            });
+        }
+        Ok(Prefab(boids))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut text = String::new();
+        for boid in &self.0 {
+            text.push_str(&format!(
+                "{} {} {} {} {}\n",
+                boid.offset.x, boid.offset.y, boid.velocity.x, boid.velocity.y, boid.species.0,
+            ));
+        }
+        fs::write(path, text)
+    }
+}
+
+/// The prefab the prefab tool stamps, and the file it is loaded from and
+/// saved to.
+#[derive(Resource)]
+pub(super) struct PrefabSlot {
+    pub(super) prefab: Prefab,
+    path: PathBuf,
+}
+
+impl PrefabSlot {
+    /// Loads the prefab saved at `path`, or starts empty.
+    pub(super) fn load_or_empty(path: PathBuf) -> Self {
+        let prefab = match Prefab::load(&path) {
+            Ok(prefab) => prefab,
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    error!("failed to load prefab {}: {err}", path.display());
+                }
+                Prefab::default()
+            }
+        };
+        PrefabSlot { prefab, path }
+    }
+}
+
+/// Stamps the current prefab into the flock, centred on `position` and
+/// turned by `rotation` radians.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StampPrefab {
+    pub position: Vec2,
+    pub rotation: f32,
+}
+
+/// State of the prefab tool between frames.
+#[derive(Resource, Default)]
+pub(super) struct PrefabTool {
+    /// Corner where the current selection drag started
+    drag_start: Option<Vec2>,
+    rotation: f32,
+}
+
+/// P toggles the prefab tool. Dragging with the left mouse selects the
+/// boids in a box and saves them as the prefab, right click stamps the
+/// prefab at the cursor and , and . turn it.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(super) fn prefab_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut state: ResMut<PrefabTool>,
+    mut slot: ResMut<PrefabSlot>,
+    boids: Query<(&Position, &Velocity, &Species), (With<Boid>, Without<Despawning>)>,
+    mut stamps: EventWriter<StampPrefab>,
+) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        tool.toggle(ActiveTool::Prefab);
+        state.drag_start = None;
+    }
+    if *tool != ActiveTool::Prefab {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Comma) {
+        state.rotation += ROTATE_STEP;
+    }
+    if keys.just_pressed(KeyCode::Period) {
+        state.rotation -= ROTATE_STEP;
+    }
+    let Some(cursor) = cursor.0 else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Right) {
+        stamps.send(StampPrefab { position: cursor, rotation: state.rotation });
+    }
+    if buttons.just_pressed(MouseButton::Left) {
+        state.drag_start = Some(cursor);
+    }
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(start) = state.drag_start.take() else {
+        return;
+    };
+    if start.distance(cursor) < MIN_SELECTION {
+        return;
+    }
+    let (min, max) = (start.min(cursor), start.max(cursor));
+    let selected: Vec<(Vec2, Vec2, Species)> = boids
+        .iter()
+        .filter(|(pos, _, _)| pos.0.cmpge(min).all() && pos.0.cmple(max).all())
+        .map(|(pos, vel, species)| (pos.0, vel.0, *species))
+        .collect();
+    if selected.is_empty() {
+        return;
+    }
+    slot.prefab = Prefab::capture(&selected);
+    state.rotation = 0.;
+    match slot.prefab.save(&slot.path) {
+        Ok(()) => info!("saved {} boids as a prefab to {}", selected.len(), slot.path.display()),
+        Err(err) => error!("failed to save prefab to {}: {err}", slot.path.display()),
+    }
+}
+
+pub(super) fn stamp_prefabs(mut stamps: EventReader<StampPrefab>, slot: Res<PrefabSlot>, mut spawner: BoidSpawner) {
+    for stamp in stamps.read() {
+        for (position, velocity, species) in slot.prefab.place(stamp.position, stamp.rotation) {
+            spawner.spawn_species(position, velocity, species);
+        }
+    }
+}
+
+/// Draws the selection box while dragging, and where the prefab would be
+/// stamped otherwise.
+#[cfg(feature = "render")]
+pub(super) fn draw_prefab_tool(
+    mut gizmos: Gizmos,
+    tool: Res<ActiveTool>,
+    state: Res<PrefabTool>,
+    slot: Res<PrefabSlot>,
+    cursor: Res<CursorWorld>,
+) {
+    let (ActiveTool::Prefab, Some(cursor)) = (*tool, cursor.0) else {
+        return;
+    };
+    if let Some(start) = state.drag_start {
+        gizmos.rect_2d((start + cursor) / 2., 0., (cursor - start).abs(), SELECTION_COLOR);
+        return;
+    }
+    for (position, velocity, _) in slot.prefab.place(cursor, state.rotation) {
+        gizmos.circle_2d(position, R, PREVIEW_COLOR);
+        gizmos.line_2d(position, position + velocity.normalize_or_zero() * 2. * R, PREVIEW_COLOR);
+    }
+}
@@ -0,0 +1,413 @@
+use std::{collections::HashSet, f32::consts::FRAC_PI_2};
+use bevy::{
+    input::ButtonInput,
+    log::info,
+    prelude::{
+        Added,
+        AssetServer,
+        Assets,
+        Camera2dBundle,
+        Capsule2d,
+        Changed,
+        Circle,
+        Color,
+        Commands,
+        DetectChanges,
+        Entity,
+        Fixed,
+        Handle,
+        Image,
+        KeyCode,
+        Mesh,
+        Quat,
+        Query,
+        Rectangle,
+        Ref,
+        Res,
+        ResMut,
+        Resource,
+        Rhombus,
+        Time,
+        Transform,
+        Triangle2d,
+        Vec2,
+        Vec3,
+        With,
+    },
+    sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use super::{
+    obstacles::Obstacle,
+    palette::{PaletteImage, PaletteMaterial},
+    predators::{Predator, PREDATOR_SIZE},
+    species::{Species, SpeciesTable},
+    timestep::PreviousPosition,
+    AngularVelocity,
+    Boid,
+    Heading,
+    Position,
+    Velocity,
+    WorldBounds,
+};
+
+// draw order: obstacles under boids, which stay below BOID_LAYER so
+// predators always draw over them
+const OBSTACLE_Z: f32 = -1.;
+const BOID_LAYER: f32 = 0.4;
+const PREDATOR_Z: f32 = 0.5;
+// distinct depths boids are spread over to break ties between them
+const TIE_STEPS: u32 = 1024;
+// side of the square sprites are drawn on, about the size of the triangle
+const SPRITE_SIZE: f32 = 12.;
+
+/// Mesh every boid is drawn with. Replacing it redraws every boid with the
+/// new mesh, unless they are zoomed out to points.
+#[derive(Resource)]
+pub struct BoidMesh(pub Mesh2dHandle);
+
+/// Material every boid is drawn with in place of its species colour, when
+/// set. Clearing it goes back to the species colours.
+#[derive(Resource, Default)]
+pub struct BoidMaterial(pub Option<Handle<ColorMaterial>>);
+
+/// How boids are drawn in place of the built-in triangle, set with
+/// [`BoidsPlugin::with_appearance`](super::BoidsPlugin::with_appearance).
+/// Meshes and sprites should point along +y, the way boids fly. Every
+/// boid is scaled by its species' size on top of `scale`, so flocks of
+/// different species can be sized apart.
+#[derive(Resource, Clone)]
+pub struct BoidAppearance {
+    /// Mesh every boid is drawn with, roughly 10 units long
+    pub mesh: Option<Mesh>,
+    /// Image in the asset folder drawn on each boid, tinted by its
+    /// material's colour, on `mesh` or else on a square
+    pub sprite: Option<String>,
+    pub scale: f32,
+    pub draw_order: DrawOrder,
+}
+
+impl Default for BoidAppearance {
+    fn default() -> Self {
+        BoidAppearance { mesh: None, sprite: None, scale: 1., draw_order: DrawOrder::default() }
+    }
+}
+
+/// Which boids are drawn over which. Whatever the order, boids are spread
+/// over a thousand depths so overlapping ones rarely share one and don't
+/// flicker as the renderer swaps them, and predators are drawn over every
+/// boid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrawOrder {
+    /// A fixed order per boid
+    #[default]
+    Fixed,
+    /// Faster boids, next to their max speed, over slower ones
+    Speed,
+    /// Species on higher [layers](super::species::SpeciesDesc::layer) over
+    /// lower ones
+    Species,
+}
+
+impl DrawOrder {
+    /// Depth of a boid, from 0 up to [`BOID_LAYER`].
+    fn z(self, entity: Entity, speed: f32, band: usize, bands: usize) -> f32 {
+        let tie = (entity.index() % TIE_STEPS) as f32 / TIE_STEPS as f32;
+        let key = match self {
+            DrawOrder::Fixed => tie,
+            DrawOrder::Speed => {
+                // ties only between boids at the same speed
+                let band = (speed.clamp(0., 1.) * (TIE_STEPS - 1) as f32).floor();
+                (band + tie) / TIE_STEPS as f32
+            }
+            DrawOrder::Species => (band as f32 + tie) / bands.max(1) as f32,
+        };
+        key * BOID_LAYER
+    }
+}
+
+/// Texture of the [`BoidAppearance`] sprite, if there is one.
+#[derive(Resource)]
+pub(super) struct BoidTexture(Option<Handle<Image>>);
+
+/// The built-in boid shapes Y cycles [`BoidMesh`] through, and which one is
+/// in use.
+#[derive(Resource)]
+pub(super) struct BoidShapes {
+    meshes: Vec<Mesh2dHandle>,
+    current: usize,
+}
+
+/// Boid material of each species, by index into the species table.
+#[derive(Resource, Default)]
+pub(super) struct SpeciesMaterials(Vec<Handle<ColorMaterial>>);
+
+impl SpeciesMaterials {
+    pub(super) fn get(&self, species: Species) -> Handle<ColorMaterial> {
+        self.0[species.0 as usize].clone()
+    }
+
+    /// The material a boid of `species` is drawn with: the custom
+    /// material if there is one, else its own colour from the palette
+    /// image, else its species colour.
+    pub(super) fn boid_material(
+        &self,
+        custom: &BoidMaterial,
+        own: Option<&PaletteMaterial>,
+        species: Species,
+    ) -> Handle<ColorMaterial> {
+        custom.0.clone()
+            .or_else(|| own.map(|own| own.0.clone()))
+            .unwrap_or_else(|| self.get(species))
+    }
+}
+
+#[derive(Resource)]
+pub(super) struct PredatorMaterial(Handle<ColorMaterial>);
+
+#[derive(Resource)]
+pub(super) struct ObstacleAssets {
+    mesh: Mesh2dHandle,
+    material: Handle<ColorMaterial>,
+}
+
+pub(super) fn setup_render(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    appearance: Res<BoidAppearance>,
+    assets: Res<AssetServer>,
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // all point along +y, the way boids fly
+    let mut shapes = vec![
+        meshes.add(Triangle2d::new(Vec2::Y * 6., Vec2::new(-3., -3.), Vec2::new(3., -3.))),
+        meshes.add(Rhombus::new(6., 10.)),
+        meshes.add(Capsule2d::new(2., 5.)),
+        meshes.add(Circle::new(3.)),
+    ];
+    // the appearance's mesh comes first, still cycled through with the rest
+    match (&appearance.mesh, &appearance.sprite) {
+        (Some(mesh), _) => shapes.insert(0, meshes.add(mesh.clone())),
+        (None, Some(_)) => shapes.insert(0, meshes.add(Rectangle::from_length(SPRITE_SIZE))),
+        (None, None) => {}
+    }
+    let shapes: Vec<Mesh2dHandle> = shapes.into_iter().map(Mesh2dHandle).collect();
+    commands.insert_resource(BoidTexture(appearance.sprite.as_ref().map(|path| assets.load(path.clone()))));
+    commands.insert_resource(BoidMesh(shapes[0].clone()));
+    commands.insert_resource(BoidShapes { meshes: shapes, current: 0 });
+
+    commands.insert_resource(PredatorMaterial(materials.add(Color::srgb(0.9, 0.25, 0.2))));
+
+    commands.insert_resource(ObstacleAssets {
+        mesh: Mesh2dHandle(meshes.add(Circle::new(1.))),
+        material: materials.add(Color::srgb(0.35, 0.35, 0.4)),
+    });
+}
+
+/// Keeps one material per species in the colour of the species, adding
+/// materials for new species and recolouring edited ones.
+pub(super) fn sync_species_materials(
+    table: Res<SpeciesTable>,
+    mut species_materials: ResMut<SpeciesMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !table.is_changed() {
+        return;
+    }
+    for (index, desc) in table.0.iter().enumerate() {
+        match species_materials.0.get(index) {
+            Some(handle) => {
+                if let Some(material) = materials.get_mut(handle) {
+                    material.color = desc.color;
+                }
+            }
+            None => {
+                let handle = materials.add(desc.color);
+                species_materials.0.push(handle);
+            }
+        }
+    }
+}
+
+/// Y switches every boid to the next built-in shape.
+pub(super) fn shape_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut shapes: ResMut<BoidShapes>,
+    mut mesh: ResMut<BoidMesh>,
+) {
+    if keys.just_pressed(KeyCode::KeyY) {
+        shapes.current = (shapes.current + 1) % shapes.meshes.len();
+        mesh.0 = shapes.meshes[shapes.current].clone();
+        info!("boid shape {}/{}", shapes.current + 1, shapes.meshes.len());
+    }
+}
+
+/// Gives newly spawned boids their mesh, and their colour from the
+/// [`PaletteImage`] under them if there is one.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn attach_boid_meshes(
+    mut commands: Commands,
+    boids: Query<(Entity, &Position, &Species), Added<Boid>>,
+    mesh: Res<BoidMesh>,
+    materials: Res<SpeciesMaterials>,
+    custom: Res<BoidMaterial>,
+    mut palette: Option<ResMut<PaletteImage>>,
+    mut assets: ResMut<Assets<ColorMaterial>>,
+    bounds: Res<WorldBounds>,
+) {
+    for (entity, pos, species) in boids.iter() {
+        let own = palette
+            .as_deref_mut()
+            .map(|palette| PaletteMaterial(palette.material_at(pos.0, &bounds, &mut assets)));
+        let material = materials.boid_material(&custom, own.as_ref(), *species);
+        let mut boid = commands.entity(entity);
+        if let Some(own) = own {
+            boid.insert(own);
+        }
+        boid.insert(MaterialMesh2dBundle {
+            mesh: mesh.0.clone(),
+            material,
+            transform: Transform::from_translation(pos.0.extend(0.)),
+            ..Default::default()
+        });
+    }
+}
+
+pub(super) fn attach_predator_meshes(
+    mut commands: Commands,
+    predators: Query<(Entity, &Position), Added<Predator>>,
+    mesh: Res<BoidMesh>,
+    material: Res<PredatorMaterial>,
+) {
+    for (entity, pos) in predators.iter() {
+        commands.entity(entity).insert(MaterialMesh2dBundle {
+            mesh: mesh.0.clone(),
+            material: material.0.clone(),
+            transform: Transform::from_translation(pos.0.extend(PREDATOR_Z))
+                .with_scale(Vec3::splat(PREDATOR_SIZE)),
+            ..Default::default()
+        });
+    }
+}
+
+pub(super) fn attach_obstacle_meshes(
+    mut commands: Commands,
+    obstacles: Query<(Entity, &Position, &Obstacle), Added<Obstacle>>,
+    assets: Res<ObstacleAssets>,
+) {
+    for (entity, pos, obstacle) in obstacles.iter() {
+        commands.entity(entity).insert(MaterialMesh2dBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
+            transform: Transform::from_translation(pos.0.extend(OBSTACLE_Z))
+                .with_scale(Vec3::splat(obstacle.radius)),
+            ..Default::default()
+        });
+    }
+}
+
+/// Puts the sprite texture on whatever material each boid is drawn with,
+/// as materials are swapped for shading, flashes and selection.
+pub(super) fn texture_boid_materials(
+    texture: Res<BoidTexture>,
+    boids: Query<&Handle<ColorMaterial>, With<Boid>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Some(texture) = &texture.0 else {
+        return;
+    };
+    let used: HashSet<&Handle<ColorMaterial>> = boids.iter().collect();
+    for handle in used {
+        // only touching the ones still missing it, so the rest stay unchanged
+        if materials.get(handle).is_some_and(|material| material.texture.as_ref() != Some(texture)) {
+            if let Some(material) = materials.get_mut(handle) {
+                material.texture = Some(texture.clone());
+            }
+        }
+    }
+}
+
+/// Swaps the material of boids that changed species, or of every boid
+/// when [`BoidMaterial`] changed.
+#[allow(clippy::type_complexity)]
+pub(super) fn sync_boid_materials(
+    mut boids: Query<(Ref<Species>, Option<&PaletteMaterial>, &mut Handle<ColorMaterial>), With<Boid>>,
+    materials: Res<SpeciesMaterials>,
+    custom: Res<BoidMaterial>,
+) {
+    let all = custom.is_changed();
+    for (species, own, mut material) in boids.iter_mut() {
+        if all || species.is_changed() {
+            *material = materials.boid_material(&custom, own, *species);
+        }
+    }
+}
+
+/// Largest move between ticks that is drawn interpolated, anything longer
+/// wrapped around the world.
+fn max_jump(bounds: &WorldBounds) -> f32 {
+    bounds.half_extents.min_element()
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn sync_boid_transforms(
+    mut boids: Query<(
+        Entity,
+        &Position,
+        &PreviousPosition,
+        &Velocity,
+        &Heading,
+        &AngularVelocity,
+        &Boid,
+        &Species,
+        &mut Transform
+    ), With<Boid>>,
+    table: Res<SpeciesTable>,
+    appearance: Res<BoidAppearance>,
+    time: Res<Time<Fixed>>,
+    bounds: Res<WorldBounds>,
+) {
+    let fraction = time.overstep_fraction();
+    // one depth band per layer in use, lowest first
+    let mut layers: Vec<i32> = table.0.iter().map(|desc| desc.layer).collect();
+    layers.sort_unstable();
+    layers.dedup();
+    for (entity, pos, previous, vel, heading, angular, boid, species, mut transform) in boids.iter_mut() {
+        let desc = table.get(*species);
+        let size = desc.size * appearance.scale;
+        let shown = previous.interpolate(pos.0, fraction, max_jump(&bounds));
+        let speed = vel.0.length() / boid.max_speed.max(f32::EPSILON);
+        let band = layers.binary_search(&desc.layer).unwrap_or_default();
+        let z = appearance.draw_order.z(entity, speed, band, layers.len());
+        transform.translation = shown.extend(z);
+        transform.rotation = Quat::from_rotation_z(heading.0 - FRAC_PI_2);
+        // bank into turns by foreshortening the wings
+        transform.scale = Vec3::new(angular.bank() * size, size, 1.);
+    }
+}
+
+pub(super) fn sync_predator_transforms(
+    mut predators: Query<(&Position, &PreviousPosition, &Velocity, &mut Transform), With<Predator>>,
+    time: Res<Time<Fixed>>,
+    bounds: Res<WorldBounds>,
+) {
+    let fraction = time.overstep_fraction();
+    for (pos, previous, vel, mut transform) in predators.iter_mut() {
+        let shown = previous.interpolate(pos.0, fraction, max_jump(&bounds));
+        transform.translation = shown.extend(PREDATOR_Z);
+        if vel.0 != Vec2::ZERO {
+            transform.rotation = Quat::from_rotation_z(vel.0.y.atan2(vel.0.x) - FRAC_PI_2);
+        }
+    }
+}
+
+pub(super) fn sync_obstacle_transforms(
+    mut obstacles: Query<(&Position, &Obstacle, &mut Transform), Changed<Obstacle>>,
+) {
+    for (pos, obstacle, mut transform) in obstacles.iter_mut() {
+        transform.translation = pos.0.extend(OBSTACLE_Z);
+        transform.scale = Vec3::splat(obstacle.radius);
+    }
+}
@@ -0,0 +1,128 @@
+use std::{f32::consts::TAU, time::Duration};
+use bevy::prelude::{
+    Commands,
+    Component,
+    Entity,
+    EventReader,
+    Query,
+    Res,
+    ResMut,
+    Resource,
+    Time,
+    Timer,
+    TimerMode,
+    Vec2,
+    With,
+    Without,
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use super::{
+    despawning::Despawning,
+    disturbance::Disturbance,
+    signals::{Inbox, SignalKind},
+    Boid,
+    Boids,
+    Position,
+    RandomGenerator,
+    Velocity,
+};
+
+// chance per second that a boid over a roost settles on it
+const PERCH_RATE: f32 = 0.3;
+// seconds a perched boid sleeps unless disturbed
+const SLEEP_SECONDS: std::ops::Range<f32> = 5.0..15.0;
+const TAKEOFF_SPEED: f32 = 150.;
+
+/// Area boids flying over may perch on and fall asleep.
+#[derive(Component)]
+pub struct Roost {
+    pub radius: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RoostDesc {
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+/// Roosts spawned at startup.
+#[derive(Resource, Default)]
+pub(super) struct RoostLayout(pub(super) Vec<RoostDesc>);
+
+/// A perched, sleeping boid. Dormant boids are taken out of the [`Boids`]
+/// list so neighbours ignore them, and skip steering and integration until
+/// their timer runs out, an alarm reaches them or they are disturbed.
+#[derive(Component)]
+pub struct Dormant {
+    pub timer: Timer,
+}
+
+pub(super) fn spawn_roosts(mut commands: Commands, layout: Res<RoostLayout>) {
+    for roost in layout.0.iter() {
+        commands.spawn((Position(roost.position), Roost { radius: roost.radius }));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn perch(
+    mut commands: Commands,
+    mut boids: Query<(Entity, &Position, &mut Velocity), (With<Boid>, Without<Dormant>, Without<Despawning>)>,
+    roosts: Query<(&Position, &Roost), Without<Boid>>,
+    mut list: ResMut<Boids>,
+    mut rng: ResMut<RandomGenerator>,
+    time: Res<Time>,
+) {
+    let chance = PERCH_RATE * time.delta_seconds();
+    for (entity, pos, mut vel) in boids.iter_mut() {
+        let over_roost = roosts
+            .iter()
+            .any(|(center, roost)| pos.0.distance(center.0) < roost.radius);
+        if !over_roost || rng.random_f32(0.0..1.0) >= chance {
+            continue;
+        }
+        vel.0 = Vec2::ZERO;
+        let seconds = rng.random_f32(SLEEP_SECONDS);
+        commands.entity(entity).insert(Dormant {
+            timer: Timer::new(Duration::from_secs_f32(seconds), TimerMode::Once),
+        });
+        list.0.retain(|&boid| boid != entity);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn wake(
+    mut commands: Commands,
+    mut boids: Query<(Entity, &Position, &Inbox, &mut Velocity, &mut Dormant), (With<Boid>, Without<Despawning>)>,
+    mut disturbances: EventReader<Disturbance>,
+    mut list: ResMut<Boids>,
+    mut rng: ResMut<RandomGenerator>,
+    time: Res<Time>,
+) {
+    let disturbances: Vec<Disturbance> = disturbances.read().copied().collect();
+    for (entity, pos, inbox, mut vel, mut dormant) in boids.iter_mut() {
+        let disturbed = disturbances
+            .iter()
+            .find(|disturbance| pos.0.distance(disturbance.position) < disturbance.radius);
+        let alarmed = inbox.0.iter().any(|signal| signal.kind == SignalKind::Alarm);
+        if !dormant.timer.tick(time.delta()).finished() && !alarmed && disturbed.is_none() {
+            continue;
+        }
+        // flushed boids burst away from the disturbance
+        let direction = disturbed
+            .map(|disturbance| (pos.0 - disturbance.position).normalize_or_zero())
+            .filter(|direction| *direction != Vec2::ZERO)
+            .unwrap_or_else(|| Vec2::from_angle(rng.random_f32(0.0..TAU)));
+        vel.0 = direction * TAKEOFF_SPEED;
+        commands.entity(entity).remove::<Dormant>();
+        list.0.push(entity);
+    }
+}
+
+#[cfg(feature = "render")]
+pub(super) fn draw_roosts(mut gizmos: Gizmos, roosts: Query<(&Position, &Roost)>) {
+    for (pos, roost) in roosts.iter() {
+        gizmos.circle_2d(pos.0, roost.radius, Color::srgb(0.6, 0.45, 0.3));
+    }
+}
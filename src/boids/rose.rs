@@ -0,0 +1,74 @@
+use std::f32::consts::TAU;
+use bevy::{
+    input::ButtonInput,
+    prelude::{Color, Gizmos, KeyCode, Query, Res, ResMut, Resource, Vec2, With},
+};
+
+use super::{orientation::Heading, Boid, WorldBounds};
+
+const SECTORS: usize = 24;
+
+const ROSE_RADIUS: f32 = 60.;
+const ROSE_MARGIN: f32 = 16.;
+
+/// Polar histogram of boid headings, refreshed every frame while visible.
+#[derive(Resource, Default)]
+pub struct HeadingRose {
+    /// Number of boids facing into each sector, counter-clockwise from the x-axis
+    pub sectors: [u32; SECTORS],
+    /// Mean heading vector. Its length is the Vicsek order parameter: 0 for
+    /// random headings, 1 when every boid faces the same way
+    pub mean: Vec2,
+    visible: bool,
+}
+
+pub(super) fn rose_input(keys: Res<ButtonInput<KeyCode>>, mut rose: ResMut<HeadingRose>) {
+    if keys.just_pressed(KeyCode::F4) {
+        rose.visible = !rose.visible;
+    }
+}
+
+pub(super) fn sample_headings(mut rose: ResMut<HeadingRose>, boids: Query<&Heading, With<Boid>>) {
+    if !rose.visible {
+        return;
+    }
+    let mut sectors = [0; SECTORS];
+    let mut sum = Vec2::ZERO;
+    let mut count = 0;
+    for heading in boids.iter() {
+        let sector = (heading.0.rem_euclid(TAU) / TAU * SECTORS as f32) as usize;
+        // rem_euclid can round up to exactly TAU
+        sectors[sector.min(SECTORS - 1)] += 1;
+        sum += Vec2::from_angle(heading.0);
+        count += 1;
+    }
+    rose.sectors = sectors;
+    rose.mean = if count > 0 { sum / count as f32 } else { Vec2::ZERO };
+}
+
+/// Draws the rose in the top-right corner, petals scaled to the fullest
+/// sector, with the mean heading as a spoke whose length is the order
+/// parameter.
+pub(super) fn draw_rose(mut gizmos: Gizmos, rose: Res<HeadingRose>, bounds: Res<WorldBounds>) {
+    if !rose.visible {
+        return;
+    }
+    let center = bounds.half_extents - Vec2::splat(ROSE_MARGIN + ROSE_RADIUS);
+    gizmos.circle_2d(center, ROSE_RADIUS, Color::srgba(1., 1., 1., 0.2));
+
+    let max = rose.sectors.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return;
+    }
+    let width = TAU / SECTORS as f32;
+    for (sector, &count) in rose.sectors.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        let radius = ROSE_RADIUS * count as f32 / max as f32;
+        let start = Vec2::from_angle(sector as f32 * width) * radius;
+        let end = Vec2::from_angle((sector + 1) as f32 * width) * radius;
+        gizmos.linestrip_2d([center, center + start, center + end, center], Color::srgb(0.4, 0.8, 1.));
+    }
+    gizmos.arrow_2d(center, center + rose.mean * ROSE_RADIUS, Color::srgb(1., 0.6, 0.2));
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::{Res, ResMut, Resource, Time};
+
+use super::{predators, BoidSpawner, SpawnArea, Species, R};
+
+/// What a [`SpawnSchedule`] spawns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spawnee {
+    /// Boids of the species at this index in the species table
+    Species(u32),
+    Predators,
+}
+
+/// When, where and how many of one species or of the predators spawn, for
+/// staging scenarios like prey settling in before the first predator shows
+/// up.
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnSchedule {
+    pub spawnee: Spawnee,
+    /// Spawned at startup
+    pub initial: u32,
+    /// Spawned per second once `start` seconds have passed
+    pub rate: f32,
+    pub start: f32,
+    /// Most ever spawned, the initial ones included
+    pub total: u32,
+    pub area: SpawnArea,
+}
+
+/// Progress of one schedule.
+struct Scheduled {
+    schedule: SpawnSchedule,
+    spawned: u32,
+    accumulator: f32,
+}
+
+/// Every schedule the plugin was given. While there are any, they do all
+/// the spawning and the flock doesn't fill up to its max boid count.
+#[derive(Resource)]
+pub(super) struct SpawnSchedules(Vec<Scheduled>);
+
+impl SpawnSchedules {
+    pub(super) fn new(schedules: &[SpawnSchedule]) -> Self {
+        SpawnSchedules(schedules
+            .iter()
+            .map(|&schedule| Scheduled {
+                schedule,
+                spawned: 0,
+                // the first one spawns right at `start`
+                accumulator: if schedule.rate > 0. { 1. } else { 0. },
+            })
+            .collect())
+    }
+}
+
+fn spawn_one(spawner: &mut BoidSpawner, schedule: &SpawnSchedule) {
+    match schedule.spawnee {
+        Spawnee::Species(species) => {
+            spawner.spawn_in(schedule.area, Some(Species(species)));
+        }
+        Spawnee::Predators => {
+            let (position, heading, _) = spawner.spot_in(schedule.area, R * predators::PREDATOR_SIZE);
+            let velocity = heading * spawner.settings.predator_max_speed / 2.;
+            predators::spawn_predator(&mut spawner.commands, position, velocity);
+        }
+    }
+}
+
+pub(super) fn spawn_scheduled_at_startup(mut schedules: ResMut<SpawnSchedules>, mut spawner: BoidSpawner) {
+    for scheduled in schedules.0.iter_mut() {
+        let count = scheduled.schedule.initial.min(scheduled.schedule.total);
+        for _ in 0..count {
+            spawn_one(&mut spawner, &scheduled.schedule);
+        }
+        scheduled.spawned = count;
+    }
+}
+
+pub(super) fn run_spawn_schedules(mut schedules: ResMut<SpawnSchedules>, mut spawner: BoidSpawner, time: Res<Time>) {
+    let elapsed = time.elapsed_seconds();
+    for scheduled in schedules.0.iter_mut() {
+        let schedule = scheduled.schedule;
+        if elapsed < schedule.start || scheduled.spawned >= schedule.total {
+            continue;
+        }
+        scheduled.accumulator += schedule.rate * time.delta_seconds();
+        while scheduled.accumulator >= 1. && scheduled.spawned < schedule.total {
+            scheduled.accumulator -= 1.;
+            scheduled.spawned += 1;
+            spawn_one(&mut spawner, &schedule);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::{
+    log::warn,
+    prelude::{DetectChanges, DetectChangesMut, Event, EventWriter, Query, Ref, Res, ResMut, Resource, Vec2},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    species::{BodyRadius, Mass, Species, SpeciesTable},
+    Boid,
+    ALIGN_MULTIPLIER,
+    COHESION_MULTIPLIER,
+    CURSOR_RADIUS,
+    CURSOR_STRENGTH,
+    DESIRED_SEPARATION,
+    EDGE_AVOIDANCE_MULTIPLIER,
+    EDGE_MARGIN,
+    EVASION_MULTIPLIER,
+    EVASION_RADIUS,
+    FIELD_OF_VIEW,
+    MAX_FORCE,
+    MAX_SPEED,
+    NEIGHBOUR_RADIUS,
+    PREDATOR_MAX_FORCE,
+    PREDATOR_MAX_SPEED,
+    R,
+    SEPARATION_MULTIPLIER,
+};
+
+// names of the parameters in `values_mut` order, as written in config files
+const NAMES: [&str; 16] = [
+    "max_force",
+    "max_speed",
+    "desired_separation",
+    "neighbour_radius",
+    "field_of_view",
+    "separation",
+    "alignment",
+    "cohesion",
+    "edge_avoidance",
+    "edge_margin",
+    "evasion",
+    "evasion_radius",
+    "predator_max_force",
+    "predator_max_speed",
+    "cursor_strength",
+    "cursor_radius",
+];
+
+// speed and steering limits, which boids freeze at or below zero
+const LIMITS: [&str; 4] = ["max_force", "max_speed", "predator_max_force", "predator_max_speed"];
+// distances, which mean nothing below zero
+const RADII: [&str; 5] = ["desired_separation", "neighbour_radius", "edge_margin", "evasion_radius", "cursor_radius"];
+// lowest a limit is raised to
+const MIN_LIMIT: f32 = 1.;
+
+/// A flocking parameter given a value the simulation can't use, or a
+/// combination of parameters that is probably a mistake. Sent whenever the
+/// settings change and one is found.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct SettingsProblem {
+    /// Field name of the parameter
+    pub parameter: &'static str,
+    pub message: String,
+}
+
+/// Problems found the last time the settings changed, shown in the
+/// settings panel.
+#[derive(Resource, Default)]
+#[cfg_attr(not(feature = "editor"), allow(dead_code))]
+pub(super) struct SettingsProblems(pub(super) Vec<SettingsProblem>);
+
+/// Flocking parameters, read by the systems every frame so they can be
+/// changed while the simulation runs.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoidsSettings {
+    /// Steering acceleration limit in units/s²
+    pub max_force: f32,
+    /// Speed limit in units/s
+    pub max_speed: f32,
+    /// Boids closer than this push each other apart
+    pub desired_separation: f32,
+    /// Boids closer than this align with and are drawn towards each other
+    pub neighbour_radius: f32,
+    /// Width in degrees of the cone around its heading a boid sees its
+    /// neighbours in, 360 to see all around
+    pub field_of_view: f32,
+    /// Weights of Reynolds' three flocking rules
+    pub separation: f32,
+    pub alignment: f32,
+    pub cohesion: f32,
+    /// Weight of steering away from the edges when boids avoid them
+    pub edge_avoidance: f32,
+    /// Boids closer than this to an edge they avoid steer away from it
+    pub edge_margin: f32,
+    /// Weight of fleeing from predators
+    pub evasion: f32,
+    /// Predators closer than this make boids flee
+    pub evasion_radius: f32,
+    /// Steering and speed limits of predators
+    pub predator_max_force: f32,
+    pub predator_max_speed: f32,
+    /// Weight of the attract tool's pull or push on boids at the cursor
+    pub cursor_strength: f32,
+    /// The attract tool's pull or push fades out to nothing at this distance
+    pub cursor_radius: f32,
+}
+
+impl Default for BoidsSettings {
+    fn default() -> Self {
+        BoidsSettings {
+            max_force: MAX_FORCE,
+            max_speed: MAX_SPEED,
+            desired_separation: DESIRED_SEPARATION,
+            neighbour_radius: NEIGHBOUR_RADIUS,
+            field_of_view: FIELD_OF_VIEW,
+            separation: SEPARATION_MULTIPLIER,
+            alignment: ALIGN_MULTIPLIER,
+            cohesion: COHESION_MULTIPLIER,
+            edge_avoidance: EDGE_AVOIDANCE_MULTIPLIER,
+            edge_margin: EDGE_MARGIN,
+            evasion: EVASION_MULTIPLIER,
+            evasion_radius: EVASION_RADIUS,
+            predator_max_force: PREDATOR_MAX_FORCE,
+            predator_max_speed: PREDATOR_MAX_SPEED,
+            cursor_strength: CURSOR_STRENGTH,
+            cursor_radius: CURSOR_RADIUS,
+        }
+    }
+}
+
+impl BoidsSettings {
+    /// Whether a boid at `position` flying along `velocity` sees a
+    /// neighbour at `other`. A boid standing still sees all around.
+    pub(super) fn in_view(&self, position: Vec2, velocity: Vec2, other: Vec2) -> bool {
+        if self.field_of_view >= 360. {
+            return true;
+        }
+        let heading = velocity.normalize_or_zero();
+        let direction = (other - position).normalize_or_zero();
+        heading == Vec2::ZERO || direction.dot(heading) >= (self.field_of_view.to_radians() / 2.).cos()
+    }
+
+    /// Every parameter in declaration order, for formats that store the
+    /// settings as a plain list of numbers.
+    pub(crate) fn values_mut(&mut self) -> [&mut f32; 16] {
+        [
+            &mut self.max_force,
+            &mut self.max_speed,
+            &mut self.desired_separation,
+            &mut self.neighbour_radius,
+            &mut self.field_of_view,
+            &mut self.separation,
+            &mut self.alignment,
+            &mut self.cohesion,
+            &mut self.edge_avoidance,
+            &mut self.edge_margin,
+            &mut self.evasion,
+            &mut self.evasion_radius,
+            &mut self.predator_max_force,
+            &mut self.predator_max_speed,
+            &mut self.cursor_strength,
+            &mut self.cursor_radius,
+        ]
+    }
+
+    /// Replaces values that would freeze the boids or turn their motion to
+    /// NaN with the nearest usable ones: non-numbers with the default,
+    /// negative distances with zero, speed and steering limits below 1 with
+    /// 1, and the field of view clamped to 0-360 degrees. Returns what was
+    /// changed, and a warning when boids keep apart from boids further away
+    /// than they flock with.
+    pub fn sanitize(&mut self) -> Vec<SettingsProblem> {
+        let mut problems = Vec::new();
+        let mut defaults = BoidsSettings::default();
+        for ((name, value), &mut default) in self.named_values_mut().zip(defaults.values_mut()) {
+            let fix = if !value.is_finite() {
+                Some((default, "is not a number"))
+            } else if LIMITS.contains(&name) && *value < MIN_LIMIT {
+                Some((MIN_LIMIT, "would stop the boids"))
+            } else if RADII.contains(&name) && *value < 0. {
+                Some((0., "is negative"))
+            } else if name == "field_of_view" && !(0. ..=360.).contains(value) {
+                Some((value.clamp(0., 360.), "is outside 0 to 360 degrees"))
+            } else {
+                None
+            };
+            if let Some((replacement, reason)) = fix {
+                problems.push(SettingsProblem {
+                    parameter: name,
+                    message: format!("{name} {value} {reason}, using {replacement}"),
+                });
+                *value = replacement;
+            }
+        }
+        if self.desired_separation > self.neighbour_radius {
+            problems.push(SettingsProblem {
+                parameter: "desired_separation",
+                message: format!(
+                    "desired_separation {} is larger than neighbour_radius {}, so boids push away boids further off than they flock with",
+                    self.desired_separation,
+                    self.neighbour_radius,
+                ),
+            });
+        }
+        problems
+    }
+
+    /// Every parameter with its field name.
+    pub(crate) fn named_values_mut(&mut self) -> impl Iterator<Item = (&'static str, &mut f32)> {
+        NAMES.into_iter().zip(self.values_mut())
+    }
+}
+
+/// Sanitizes the settings whenever they change, before they reach the
+/// boids, and reports what was wrong with them.
+pub(super) fn validate_settings(
+    mut settings: ResMut<BoidsSettings>,
+    mut problems: ResMut<SettingsProblems>,
+    mut events: EventWriter<SettingsProblem>,
+) {
+    // without marking them changed again, which would clear the problems
+    // found here on the next tick
+    let found = settings.bypass_change_detection().sanitize();
+    for problem in &found {
+        warn!("{}", problem.message);
+    }
+    events.send_batch(found.iter().cloned());
+    problems.0 = found;
+}
+
+/// Copies changed limits and species parameters onto every boid, or onto
+/// the boids that changed species.
+pub(super) fn apply_settings(
+    settings: Res<BoidsSettings>,
+    species: Res<SpeciesTable>,
+    mut boids: Query<(&mut Boid, &mut Mass, &mut BodyRadius, Ref<Species>)>,
+) {
+    let all = settings.is_changed() || species.is_changed();
+    for (mut boid, mut mass, mut radius, kind) in boids.iter_mut() {
+        if all || kind.is_changed() {
+            let desc = species.get(*kind);
+            *boid = Boid::new(&settings, desc);
+            mass.0 = desc.mass;
+            radius.0 = R * desc.size;
+        }
+    }
+}
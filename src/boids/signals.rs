@@ -0,0 +1,64 @@
+use bevy::prelude::{Component, Entity, Event, EventReader, EventWriter, Query, Vec2, With};
+
+use super::{obstacles::ObstacleHit, Boid, Position};
+
+// how far an alarm raised by a collision carries
+const ALARM_RADIUS: f32 = 80.;
+
+/// What a signal means to the boids receiving it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SignalKind {
+    /// Danger at the signal origin
+    Alarm,
+    /// Food at `location`, broadcast by boids feeding there
+    FoodFound { location: Vec2 },
+}
+
+/// A message broadcast by a boid to every boid within `radius` of `origin`.
+/// Signals sent during a tick are delivered at the start of the next one.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Signal {
+    pub kind: SignalKind,
+    pub source: Entity,
+    pub origin: Vec2,
+    pub radius: f32,
+}
+
+/// Signals a boid received this tick.
+#[derive(Component, Default)]
+pub struct Inbox(pub Vec<Signal>);
+
+/// Replaces every inbox with the signals sent last tick that reach it.
+pub(super) fn deliver_signals(
+    mut signals: EventReader<Signal>,
+    mut boids: Query<(Entity, &Position, &mut Inbox), With<Boid>>,
+) {
+    for (_, _, mut inbox) in boids.iter_mut() {
+        inbox.0.clear();
+    }
+    for signal in signals.read() {
+        for (entity, pos, mut inbox) in boids.iter_mut() {
+            if entity != signal.source && pos.0.distance(signal.origin) < signal.radius {
+                inbox.0.push(*signal);
+            }
+        }
+    }
+}
+
+/// Boids that hit an obstacle warn the boids around them.
+pub(super) fn alarm_on_collision(
+    mut hits: EventReader<ObstacleHit>,
+    positions: Query<&Position, With<Boid>>,
+    mut signals: EventWriter<Signal>,
+) {
+    for hit in hits.read() {
+        if let Ok(pos) = positions.get(hit.boid) {
+            signals.send(Signal {
+                kind: SignalKind::Alarm,
+                source: hit.boid,
+                origin: pos.0,
+                radius: ALARM_RADIUS,
+            });
+        }
+    }
+}
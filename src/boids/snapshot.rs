@@ -0,0 +1,182 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+};
+use bevy::{
+    ecs::system::SystemState,
+    input::ButtonInput,
+    log::{error, info},
+    prelude::{Entity, KeyCode, Resource, Vec2, With, Without, World},
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    despawning::Despawning,
+    predators::{spawn_predator, Predator},
+    spawning::Spawning,
+    Boid,
+    BoidSpawner,
+    Boids,
+    BoidsSettings,
+    MaxBoidCount,
+    Position,
+    RandomGenerator,
+    Species,
+    Velocity,
+};
+
+/// A boid saved in a snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotBoid {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub species: Species,
+}
+
+/// A predator saved in a snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotPredator {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Where the simulation's random generator was: its key, its stream and
+/// how many words into the stream it had drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RngState {
+    seed: [u8; 32],
+    stream: u64,
+    word_pos: u128,
+}
+
+impl RngState {
+    fn of(rng: &ChaCha12Rng) -> Self {
+        RngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos: rng.get_word_pos(),
+        }
+    }
+
+    fn restore(&self) -> ChaCha12Rng {
+        let mut rng = ChaCha12Rng::from_seed(self.seed);
+        rng.set_stream(self.stream);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+}
+
+/// The flock at one moment: every boid's position, velocity and species,
+/// the predators, the flocking parameters and the random state, so a run
+/// can be picked up from an interesting formation or a bug reproduced from
+/// it. Saved as RON.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoidsSnapshot {
+    pub settings: BoidsSettings,
+    /// The simulation's random generator, left untouched on capture, so
+    /// the run continues after applying the snapshot as it did after
+    /// capturing it
+    pub rng: RngState,
+    pub boids: Vec<SnapshotBoid>,
+    pub predators: Vec<SnapshotPredator>,
+}
+
+impl BoidsSnapshot {
+    /// Captures the flock in `world`. Boids fading out are left out.
+    pub fn capture(world: &mut World) -> Self {
+        let mut boids: Vec<(Entity, SnapshotBoid)> = world
+            .query_filtered::<(Entity, &Position, &Velocity, &Species), (With<Boid>, Without<Despawning>)>()
+            .iter(world)
+            .map(|(entity, pos, vel, species)| (entity, SnapshotBoid { position: pos.0, velocity: vel.0, species: *species }))
+            .collect();
+        // in spawn order, so applying the snapshot spawns them the same way
+        let order = &world.resource::<Boids>().0;
+        boids.sort_by_key(|(entity, _)| order.iter().position(|boid| boid == entity).unwrap_or(usize::MAX));
+        let predators = world
+            .query_filtered::<(&Position, &Velocity), With<Predator>>()
+            .iter(world)
+            .map(|(pos, vel)| SnapshotPredator { position: pos.0, velocity: vel.0 })
+            .collect();
+        BoidsSnapshot {
+            settings: *world.resource::<BoidsSettings>(),
+            rng: RngState::of(&world.resource::<RandomGenerator>().rng),
+            boids: boids.into_iter().map(|(_, boid)| boid).collect(),
+            predators,
+        }
+    }
+
+    /// Replaces the flock and predators in `world` with the snapshot's,
+    /// along with the settings and random state, and caps the flock at the
+    /// snapshot's size so it isn't topped back up. Boids spawn right where
+    /// they were saved, without fading in.
+    pub fn apply(&self, world: &mut World) {
+        let mut old: Vec<Entity> = world.query_filtered::<Entity, With<Boid>>().iter(world).collect();
+        old.extend(world.query_filtered::<Entity, With<Predator>>().iter(world));
+        for entity in old {
+            world.despawn(entity);
+        }
+        world.resource_mut::<Boids>().0.clear();
+        world.resource_mut::<MaxBoidCount>().0 = self.boids.len() as u32;
+        *world.resource_mut::<BoidsSettings>() = self.settings;
+        world.resource_mut::<RandomGenerator>().rng = self.rng.restore();
+
+        let mut state = SystemState::<BoidSpawner>::new(world);
+        let mut spawner = state.get_mut(world);
+        spawner.boid_count.0 = 0;
+        for boid in &self.boids {
+            let species = spawner.species.assign(boid.species.0);
+            let entity = spawner.insert_species(boid.position, boid.velocity, species);
+            spawner.commands.entity(entity).remove::<Spawning>();
+        }
+        for predator in &self.predators {
+            spawn_predator(&mut spawner.commands, predator.position, predator.velocity);
+        }
+        state.apply(world);
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(path, text)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// File the snapshot keys save to and load from.
+#[derive(Resource)]
+pub(super) struct SnapshotFile(pub(super) PathBuf);
+
+/// F9 saves a snapshot of the flock, F10 replaces the flock with the
+/// saved one.
+pub(super) fn snapshot_input(world: &mut World) {
+    let keys = world.resource::<ButtonInput<KeyCode>>();
+    let (save, load) = (keys.just_pressed(KeyCode::F9), keys.just_pressed(KeyCode::F10));
+    let path = world.resource::<SnapshotFile>().0.clone();
+    if save {
+        let snapshot = BoidsSnapshot::capture(world);
+        match snapshot.save(&path) {
+            Ok(()) => info!("saved {} boids to {}", snapshot.boids.len(), path.display()),
+            Err(err) => error!("failed to save snapshot to {}: {err}", path.display()),
+        }
+    }
+    if load {
+        match BoidsSnapshot::load(&path) {
+            Ok(snapshot) => {
+                snapshot.apply(world);
+                info!("loaded {} boids from {}", snapshot.boids.len(), path.display());
+            }
+            Err(err) => error!("failed to load snapshot from {}: {err}", path.display()),
+        }
+    }
+}
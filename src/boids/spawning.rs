@@ -0,0 +1,166 @@
+use std::{f32::consts::TAU, time::Duration};
+use bevy::{
+    input::ButtonInput,
+    log::info,
+    prelude::{
+        Commands,
+        Component,
+        Entity,
+        KeyCode,
+        Local,
+        MouseButton,
+        Query,
+        Res,
+        ResMut,
+        Resource,
+        Time,
+        Timer,
+        TimerMode,
+        Vec2,
+    },
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Transform, With};
+
+use crate::tools::{ActiveTool, CursorWorld};
+use super::{BoidSpawner, MaxBoidCount, SpawnArea};
+
+// boids added or removed per key press
+const BATCH_SIZE: usize = 50;
+// distance the cursor is dragged between boids spawned by the spawn brush
+const BRUSH_SPACING: f32 = 12.;
+
+/// How newly spawned boids grow in. Each boid waits a random delay of up
+/// to `stagger` seconds, so batches don't appear in lockstep, then grows
+/// over `duration` seconds.
+#[derive(Resource, Clone, Copy, Debug)]
+pub(super) struct SpawnFade {
+    pub(super) duration: f32,
+    pub(super) stagger: f32,
+}
+
+impl Default for SpawnFade {
+    fn default() -> Self {
+        SpawnFade {
+            duration: 0.4,
+            stagger: 0.4,
+        }
+    }
+}
+
+/// A boid still growing in. It is left out of the neighbour grid, so it
+/// exerts no forces on the flock until it is fully spawned.
+#[derive(Component)]
+pub(super) struct Spawning {
+    timer: Timer,
+    delay: f32,
+}
+
+impl Spawning {
+    pub(super) fn new(fade: &SpawnFade, delay: f32) -> Self {
+        Spawning {
+            timer: Timer::new(Duration::from_secs_f32(delay + fade.duration), TimerMode::Once),
+            delay,
+        }
+    }
+
+    /// Size from 0 to 1.
+    #[cfg_attr(not(feature = "render"), allow(dead_code))]
+    fn size(&self) -> f32 {
+        let grow = self.timer.duration().as_secs_f32() - self.delay;
+        if grow <= 0. {
+            return 1.;
+        }
+        ((self.timer.elapsed_secs() - self.delay) / grow).clamp(0., 1.)
+    }
+}
+
+pub(super) fn finish_spawning(
+    mut commands: Commands,
+    mut spawning: Query<(Entity, &mut Spawning)>,
+    time: Res<Time>,
+) {
+    for (entity, mut spawning) in spawning.iter_mut() {
+        if spawning.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Spawning>();
+        }
+    }
+}
+
+/// = spawns a batch of boids and - removes the newest batch, R replaces
+/// the whole flock with as many new boids. The max boid count follows
+/// along, so the spawner neither refills nor keeps growing the flock.
+pub(super) fn population_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    area: Res<SpawnArea>,
+    mut max_boid_count: ResMut<MaxBoidCount>,
+    mut spawner: BoidSpawner,
+) {
+    let live = spawner.boids.0.len();
+    if keys.just_pressed(KeyCode::Equal) {
+        for _ in 0..BATCH_SIZE {
+            spawner.spawn_in(*area, None);
+        }
+    } else if keys.just_pressed(KeyCode::Minus) {
+        let removed = spawner.boids.0[live.saturating_sub(BATCH_SIZE)..].to_vec();
+        for entity in removed {
+            spawner.despawn(entity);
+        }
+    } else if keys.just_pressed(KeyCode::KeyR) {
+        for entity in spawner.boids.0.clone() {
+            spawner.despawn(entity);
+        }
+        for _ in 0..live {
+            spawner.spawn_in(*area, None);
+        }
+    } else {
+        return;
+    }
+    max_boid_count.0 = spawner.boids.0.len() as u32;
+    info!("boids: {}", spawner.boids.0.len());
+}
+
+/// F toggles the spawn brush. Clicking spawns a boid at the cursor flying
+/// off in a random direction, dragging spawns a trail of boids flying off
+/// along the drag. Brushed boids come on top of the ones the spawner fills
+/// the flock with.
+pub(super) fn spawn_brush(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut max_boid_count: ResMut<MaxBoidCount>,
+    mut spawner: BoidSpawner,
+    mut last_spawn: Local<Option<Vec2>>,
+) {
+    if keys.just_pressed(KeyCode::KeyF) {
+        tool.toggle(ActiveTool::SpawnBrush);
+    }
+    let (Some(point), ActiveTool::SpawnBrush) = (cursor.0, *tool) else {
+        *last_spawn = None;
+        return;
+    };
+    if !buttons.pressed(MouseButton::Left) {
+        *last_spawn = None;
+        return;
+    }
+    let heading = match *last_spawn {
+        None => Vec2::from_angle(spawner.rng.random_f32(0.0..TAU)),
+        Some(last) if point.distance(last) >= BRUSH_SPACING => (point - last).normalize(),
+        Some(_) => return,
+    };
+    let velocity = heading * spawner.settings.max_speed / 2.;
+    spawner.spawn(point, velocity);
+    max_boid_count.0 += 1;
+    *last_spawn = Some(point);
+}
+
+/// Grows spawning boids from nothing to full size.
+#[cfg(feature = "render")]
+pub(super) fn grow_spawning(mut boids: Query<(&Spawning, &mut Transform), With<super::Boid>>) {
+    for (spawning, mut transform) in boids.iter_mut() {
+        let size = spawning.size();
+        transform.scale.x *= size;
+        transform.scale.y *= size;
+    }
+}
@@ -0,0 +1,328 @@
+use std::{fs, io, path::{Path, PathBuf}};
+use bevy::{
+    color::Color,
+    prelude::{Component, Resource, Vec2},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{bounds::AxisBoundary, BoundaryMode, Edge};
+
+/// How a boid combines its steering forces into a single acceleration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForceMixing {
+    /// Add up every weighted force
+    #[default]
+    WeightedSum,
+    /// Reynolds' prioritized acceleration allocation: add forces in priority
+    /// order until their magnitudes use up the max force budget, truncating
+    /// the force that exceeds it and dropping the rest
+    Prioritized,
+}
+
+impl ForceMixing {
+    /// Combines `forces`, given from highest to lowest priority.
+    pub fn mix(self, forces: &[Vec2], max_force: f32) -> Vec2 {
+        match self {
+            ForceMixing::WeightedSum => forces.iter().copied().sum(),
+            ForceMixing::Prioritized => {
+                let mut total = Vec2::ZERO;
+                let mut budget = max_force;
+                for &force in forces {
+                    let magnitude = force.length();
+                    if magnitude <= budget {
+                        total += force;
+                        budget -= magnitude;
+                    } else {
+                        total += force * (budget / magnitude);
+                        break;
+                    }
+                }
+                total
+            }
+        }
+    }
+}
+
+/// Parameters shared by every boid of a species.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeciesDesc {
+    pub mixing: ForceMixing,
+    /// Steering forces are divided by this, so heavier boids turn and
+    /// accelerate more sluggishly
+    pub mass: f32,
+    /// Multiplier on the max speed from the flock settings
+    pub speed: f32,
+    pub color: Color,
+    /// Multiplier on the size of the boid mesh
+    pub size: f32,
+    /// Multipliers on the separation, alignment and cohesion weights
+    pub weights: [f32; 3],
+    /// Row of the interaction matrix: how strongly boids of this species
+    /// align and cohere with neighbours of each species, by index. Species
+    /// past the end of the row count fully.
+    pub affinity: Vec<f32>,
+    /// What happens to boids of this species that leave the world, instead
+    /// of the plugin's [`BoundaryMode`]
+    pub boundary: Option<BoundaryMode>,
+    /// Species on higher layers are drawn over lower ones with
+    /// [`DrawOrder::Species`](super::DrawOrder::Species), species on the
+    /// same layer mixed together
+    pub layer: i32,
+}
+
+impl SpeciesDesc {
+    pub fn affinity(&self, other: Species) -> f32 {
+        self.affinity.get(other.0 as usize).copied().unwrap_or(1.)
+    }
+}
+
+impl Default for SpeciesDesc {
+    fn default() -> Self {
+        SpeciesDesc {
+            mixing: ForceMixing::default(),
+            mass: 1.,
+            speed: 1.,
+            color: Color::WHITE,
+            size: 1.,
+            weights: [1.; 3],
+            affinity: Vec::new(),
+            boundary: None,
+            layer: 0,
+        }
+    }
+}
+
+/// Inertia of a boid, acceleration is force divided by mass.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Mass(pub f32);
+
+impl Default for Mass {
+    fn default() -> Self {
+        Mass(1.)
+    }
+}
+
+/// Radius of a boid's body, the boid size scaled by its species size.
+/// Boundaries and obstacles keep boids this far away so they don't clip.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct BodyRadius(pub f32);
+
+/// Index into [`SpeciesTable`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Species(pub u32);
+
+#[derive(Resource)]
+pub struct SpeciesTable(pub Vec<SpeciesDesc>);
+
+/// File the species table is saved to from the editor.
+#[derive(Resource)]
+#[cfg_attr(not(feature = "editor"), allow(dead_code))]
+pub struct SpeciesFile(pub PathBuf);
+
+impl SpeciesTable {
+    /// Species a newly spawned boid joins, cycling through the table.
+    pub fn assign(&self, index: u32) -> Species {
+        Species(index % self.0.len() as u32)
+    }
+
+    pub fn get(&self, species: Species) -> &SpeciesDesc {
+        &self.0[species.0 as usize]
+    }
+
+    /// One species per line as `key=value` pairs, lists separated by
+    /// commas. Missing keys keep their defaults.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let species = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_species)
+            .collect::<io::Result<Vec<_>>>()?;
+        if species.is_empty() {
+            return Err(invalid());
+        }
+        Ok(SpeciesTable(species))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lines: Vec<String> = self.0.iter().map(format_species).collect();
+        fs::write(path, lines.join("\n") + "\n")
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed species table")
+}
+
+fn parse_list(value: &str) -> io::Result<Vec<f32>> {
+    if value.is_empty() {
+        return Ok(Vec::new());
+    }
+    value
+        .split(',')
+        .map(|item| item.parse().map_err(|_| invalid()))
+        .collect()
+}
+
+pub(super) fn parse_species(line: &str) -> io::Result<SpeciesDesc> {
+    let mut desc = SpeciesDesc::default();
+    for pair in line.split_whitespace() {
+        let (key, value) = pair.split_once('=').ok_or_else(invalid)?;
+        let number = || value.parse::<f32>().map_err(|_| invalid());
+        match key {
+            "mixing" => {
+                desc.mixing = match value {
+                    "sum" => ForceMixing::WeightedSum,
+                    "prioritized" => ForceMixing::Prioritized,
+                    _ => return Err(invalid()),
+                }
+            }
+            "mass" => desc.mass = number()?,
+            "speed" => desc.speed = number()?,
+            "size" => desc.size = number()?,
+            "color" => {
+                let [r, g, b] = parse_list(value)?[..] else {
+                    return Err(invalid());
+                };
+                desc.color = Color::srgb(r, g, b);
+            }
+            "weights" => desc.weights = parse_list(value)?.try_into().map_err(|_| invalid())?,
+            "affinity" => desc.affinity = parse_list(value)?,
+            "boundary" => desc.boundary = Some(parse_boundary(value).ok_or_else(invalid)?),
+            "layer" => desc.layer = value.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(desc)
+}
+
+fn parse_edge(text: &str) -> Option<Edge> {
+    match text {
+        "left" => Some(Edge::Left),
+        "right" => Some(Edge::Right),
+        "bottom" => Some(Edge::Bottom),
+        "top" => Some(Edge::Top),
+        _ => None,
+    }
+}
+
+fn parse_axis(text: &str) -> Option<AxisBoundary> {
+    match text {
+        "wrap" => Some(AxisBoundary::Wrap),
+        "bounce" => Some(AxisBoundary::Bounce),
+        _ => None,
+    }
+}
+
+/// `wrap`, `bounce`, `avoid`, `hexagonal`, `despawn` or `despawn:<edge>` to
+/// respawn at that edge, or `<x>,<y>` with `wrap` or `bounce` per axis.
+fn parse_boundary(text: &str) -> Option<BoundaryMode> {
+    match text {
+        "wrap" => Some(BoundaryMode::Wrap),
+        "bounce" => Some(BoundaryMode::Bounce),
+        "avoid" => Some(BoundaryMode::Avoid),
+        "hexagonal" => Some(BoundaryMode::Hexagonal),
+        "despawn" => Some(BoundaryMode::Despawn { respawn: None }),
+        _ => {
+            if let Some(edge) = text.strip_prefix("despawn:") {
+                return Some(BoundaryMode::Despawn { respawn: Some(parse_edge(edge)?) });
+            }
+            let (x, y) = text.split_once(',')?;
+            Some(BoundaryMode::Axes { x: parse_axis(x)?, y: parse_axis(y)? })
+        }
+    }
+}
+
+fn format_boundary(mode: BoundaryMode) -> String {
+    let edge = |edge: Edge| match edge {
+        Edge::Left => "left",
+        Edge::Right => "right",
+        Edge::Bottom => "bottom",
+        Edge::Top => "top",
+    };
+    let axis = |axis: AxisBoundary| match axis {
+        AxisBoundary::Wrap => "wrap",
+        AxisBoundary::Bounce => "bounce",
+    };
+    match mode {
+        BoundaryMode::Wrap => "wrap".to_string(),
+        BoundaryMode::Bounce => "bounce".to_string(),
+        BoundaryMode::Avoid => "avoid".to_string(),
+        BoundaryMode::Hexagonal => "hexagonal".to_string(),
+        BoundaryMode::Despawn { respawn: None } => "despawn".to_string(),
+        BoundaryMode::Despawn { respawn: Some(at) } => format!("despawn:{}", edge(at)),
+        BoundaryMode::Axes { x, y } => format!("{},{}", axis(x), axis(y)),
+    }
+}
+
+fn format_species(desc: &SpeciesDesc) -> String {
+    let list = |values: &[f32]| values.iter().map(f32::to_string).collect::<Vec<_>>().join(",");
+    let mixing = match desc.mixing {
+        ForceMixing::WeightedSum => "sum",
+        ForceMixing::Prioritized => "prioritized",
+    };
+    let color = desc.color.to_srgba();
+    let mut line = format!(
+        "mixing={mixing} mass={} speed={} size={} color={} weights={} affinity={}",
+        desc.mass,
+        desc.speed,
+        desc.size,
+        list(&[color.red, color.green, color.blue]),
+        list(&desc.weights),
+        list(&desc.affinity),
+    );
+    if let Some(mode) = desc.boundary {
+        line += &format!(" boundary={}", format_boundary(mode));
+    }
+    if desc.layer != 0 {
+        line += &format!(" layer={}", desc.layer);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundaries_round_trip() {
+        let edges = [Edge::Left, Edge::Right, Edge::Bottom, Edge::Top];
+        let axes = [AxisBoundary::Wrap, AxisBoundary::Bounce];
+        let modes = [
+            BoundaryMode::Wrap,
+            BoundaryMode::Bounce,
+            BoundaryMode::Avoid,
+            BoundaryMode::Hexagonal,
+            BoundaryMode::Despawn { respawn: None },
+        ]
+        .into_iter()
+        .chain(edges.map(|edge| BoundaryMode::Despawn { respawn: Some(edge) }))
+        .chain(axes.into_iter().flat_map(|x| axes.map(|y| BoundaryMode::Axes { x, y })));
+        for mode in modes {
+            assert_eq!(parse_boundary(&format_boundary(mode)), Some(mode));
+        }
+    }
+
+    #[test]
+    fn species_round_trip() {
+        let desc = SpeciesDesc {
+            boundary: Some(BoundaryMode::Despawn { respawn: Some(Edge::Left) }),
+            layer: -2,
+            affinity: vec![0.5, 1.],
+            // colours are written as sRGB
+            color: Color::srgb(0.25, 0.5, 0.75),
+            ..Default::default()
+        };
+        assert_eq!(parse_species(&format_species(&desc)).unwrap(), desc);
+    }
+
+    #[test]
+    fn rejects_unknown_boundaries() {
+        for text in ["", "despawn:", "despawn:middle", "wrap,", "wrap,avoid", "wrap,bounce,wrap"] {
+            assert_eq!(parse_boundary(text), None, "{text}");
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::f32::consts::PI;
+use bevy::prelude::{Entity, Query, Res, ResMut, Resource, Time, Vec2, With};
+use boids_core::steering;
+
+use super::{grid::SpatialGrid, Acceleration, Boid, BoidsSettings, Position, RandomGenerator, Velocity};
+
+/// Orientation-only mode: boids sit still on a square lattice `spacing`
+/// apart and only turn, each towards the headings of the boids within the
+/// neighbour radius plus random noise, like the spins of the XY model.
+/// Steering and movement are switched off while it's on, and the flock's
+/// polarization is the model's order parameter.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SpinLattice {
+    pub spacing: f32,
+    /// How fast spins turn towards their neighbours, per second
+    pub coupling: f32,
+    /// Strength of the random turning, the temperature of the model, in
+    /// radians per square root second
+    pub noise: f32,
+}
+
+impl SpinLattice {
+    pub fn new(spacing: f32) -> Self {
+        SpinLattice {
+            spacing,
+            coupling: 4.,
+            noise: 0.5,
+        }
+    }
+
+    /// Lattice points filling the world, not moving yet.
+    pub(super) fn lattice(&self, half_extents: Vec2) -> Vec<(Vec2, Vec2)> {
+        let spacing = self.spacing.max(1.);
+        let columns = (half_extents.x * 2. / spacing).floor() as i32;
+        let rows = (half_extents.y * 2. / spacing).floor() as i32;
+        // centred, half a step in from the edges
+        let origin = -Vec2::new(columns as f32 - 1., rows as f32 - 1.) * spacing / 2.;
+        (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| (origin + Vec2::new(column as f32, row as f32) * spacing, Vec2::ZERO))
+            .collect()
+    }
+}
+
+/// Turns every spin at once from the headings of the tick before, keeping
+/// boids at full speed so they are drawn and measured as flying. Spins
+/// without a heading yet start at a random one.
+pub(super) fn turn_spins(
+    spins: Res<SpinLattice>,
+    settings: Res<BoidsSettings>,
+    grid: Res<SpatialGrid>,
+    mut rng: ResMut<RandomGenerator>,
+    time: Res<Time>,
+    mut boids: Query<(Entity, &Position, &Boid, &mut Velocity, &mut Acceleration), With<Boid>>,
+) {
+    let dt = time.delta_seconds();
+    let radius = settings.neighbour_radius;
+    let turned: Vec<(Entity, f32)> = boids
+        .iter()
+        .map(|(entity, pos, _, vel, _)| {
+            if vel.0 == Vec2::ZERO {
+                return (entity, rng.random_f32(-PI..PI));
+            }
+            let neighbours = grid
+                .neighbours(pos.0, radius)
+                .filter(|&(other, at)| other != entity && at.distance(pos.0) < radius)
+                .filter_map(|(other, _)| boids.get(other).ok())
+                .map(|(_, _, _, other, _)| other.0.to_angle());
+            let heading = steering::align_spin(vel.0.to_angle(), neighbours, spins.coupling, dt);
+            (entity, heading + rng.random_f32(-1.0..1.0) * spins.noise * dt.sqrt())
+        })
+        .collect();
+    for (entity, heading) in turned {
+        if let Ok((_, _, boid, mut vel, mut acc)) = boids.get_mut(entity) {
+            vel.0 = Vec2::from_angle(heading) * boid.max_speed;
+            // nothing else gets to push spins around
+            acc.0 = Vec2::ZERO;
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::{
+    input::ButtonInput,
+    prelude::{Entity, KeyCode, Res, ResMut, Resource, Vec2},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos, Query, With};
+
+use super::{
+    behaviors::{Steering, SteeringBehavior},
+    grid::SpatialGrid,
+    species::SpeciesDesc,
+    BoidsSettings,
+};
+#[cfg(feature = "render")]
+use super::{Boid, Position, Velocity};
+
+const DEFAULT_STIFFNESS: f32 = 4.;
+const DEFAULT_DAMPING: f32 = 2.;
+
+/// Experimental replacement for cohesion: each boid is tied to its nearest
+/// neighbours by damped springs, which pulls flocks into a lattice. Takes
+/// the place of [`Cohesion`](super::behaviors::Cohesion) among the steering
+/// behaviours, weighted the same way.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SpringCohesion {
+    /// Number of nearest neighbours within the neighbour radius to attach to
+    pub neighbours: usize,
+    pub rest_length: f32,
+    /// Acceleration per unit of stretch
+    pub stiffness: f32,
+    /// Acceleration per unit/s of relative velocity along the spring
+    pub damping: f32,
+    visible: bool,
+}
+
+impl SpringCohesion {
+    pub fn new(neighbours: usize, rest_length: f32) -> Self {
+        SpringCohesion {
+            neighbours,
+            rest_length,
+            stiffness: DEFAULT_STIFFNESS,
+            damping: DEFAULT_DAMPING,
+            visible: false,
+        }
+    }
+
+    /// Positions and velocities of the boids within `radius` that `position`
+    /// is tied to, nearest first.
+    fn partners(
+        &self,
+        position: Vec2,
+        grid: &SpatialGrid,
+        velocity_of: &dyn Fn(Entity) -> Option<Vec2>,
+        radius: f32,
+    ) -> Vec<(Vec2, Vec2)> {
+        grid.nearest(position, self.neighbours, radius)
+            .into_iter()
+            .filter_map(|(boid, pos, _)| Some((pos, velocity_of(boid)?)))
+            .collect()
+    }
+}
+
+impl SteeringBehavior for SpringCohesion {
+    fn name(&self) -> &str {
+        "cohesion"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        let mut force = Vec2::ZERO;
+        for (pos, vel) in self.partners(boid.position, boid.grid, boid.velocity_of, boid.settings.neighbour_radius) {
+            let offset = pos - boid.position;
+            let dist = offset.length();
+            let direction = offset / dist;
+            let stretch = dist - self.rest_length;
+            let closing = (vel - boid.velocity).dot(direction);
+            force += direction * (self.stiffness * stretch + self.damping * closing);
+        }
+        force.clamp_length_max(boid.max_force)
+    }
+
+    fn weight(&self, settings: &BoidsSettings, species: &SpeciesDesc) -> f32 {
+        settings.cohesion * species.weights[2]
+    }
+}
+
+/// K toggles drawing the springs.
+pub(super) fn spring_input(keys: Res<ButtonInput<KeyCode>>, mut springs: ResMut<SpringCohesion>) {
+    if keys.just_pressed(KeyCode::KeyK) {
+        springs.visible = !springs.visible;
+    }
+}
+
+#[cfg(feature = "render")]
+pub(super) fn draw_springs(
+    mut gizmos: Gizmos,
+    springs: Res<SpringCohesion>,
+    grid: Res<SpatialGrid>,
+    settings: Res<BoidsSettings>,
+    query: Query<&Position, With<Boid>>,
+    velocities: Query<&Velocity>,
+) {
+    if !springs.visible {
+        return;
+    }
+    for pos in query.iter() {
+        let velocity_of = |boid| velocities.get(boid).ok().map(|vel| vel.0);
+        for (partner, _) in springs.partners(pos.0, &grid, &velocity_of, settings.neighbour_radius) {
+            // springs are usually mutual, so each is drawn twice and the alpha kept low
+            gizmos.line_2d(pos.0, partner, Color::srgba(0.6, 0.8, 1., 0.08));
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use bevy::{
+    input::ButtonInput,
+    prelude::{Component, KeyCode, MouseButton, Res, ResMut, Resource, Vec2},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use crate::tools::{ActiveTool, CursorWorld};
+
+const DEFAULT_GOAL_WEIGHT: f32 = 0.8;
+const GOAL_WEIGHT_STEP: f32 = 0.1;
+#[cfg(feature = "render")]
+const GOAL_MARKER_RADIUS: f32 = 12.;
+
+const SQUAD_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Squad a boid belongs to. Boids still flock with every neighbour, but
+/// additionally steer toward their squad's goal.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Squad(pub u32);
+
+#[derive(Clone, Copy, Debug)]
+pub struct SquadGoal {
+    pub target: Option<Vec2>,
+    pub weight: f32,
+}
+
+impl Default for SquadGoal {
+    fn default() -> Self {
+        SquadGoal {
+            target: None,
+            weight: DEFAULT_GOAL_WEIGHT,
+        }
+    }
+}
+
+/// Squad-level goals and weights, keyed by squad id.
+#[derive(Resource, Default)]
+pub struct Squads {
+    count: u32,
+    selected: u32,
+    goals: HashMap<u32, SquadGoal>,
+}
+
+impl Squads {
+    pub fn new(count: u32) -> Self {
+        Squads {
+            count,
+            ..Default::default()
+        }
+    }
+
+    /// Squad a newly spawned boid joins, `None` when squads are disabled.
+    pub fn assign(&self, index: u32) -> Option<Squad> {
+        (self.count > 0).then(|| Squad(index % self.count))
+    }
+
+    pub fn goal(&self, squad: Squad) -> Option<&SquadGoal> {
+        self.goals.get(&squad.0)
+    }
+
+    pub fn set_target(&mut self, squad: Squad, target: Option<Vec2>) {
+        self.goals.entry(squad.0).or_default().target = target;
+    }
+
+    pub fn set_weight(&mut self, squad: Squad, weight: f32) {
+        self.goals.entry(squad.0).or_default().weight = weight.max(0.);
+    }
+
+    #[cfg(feature = "render")]
+    fn color(&self, squad: u32) -> Color {
+        Color::hsl(360. * squad as f32 / self.count.max(1) as f32, 0.8, 0.6)
+    }
+}
+
+pub(super) fn select_squad(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut squads: ResMut<Squads>,
+    mut tool: ResMut<ActiveTool>,
+) {
+    if keys.just_pressed(KeyCode::KeyG) {
+        tool.toggle(ActiveTool::SquadGoal);
+    }
+    for (squad, key) in SQUAD_KEYS.iter().enumerate() {
+        if keys.just_pressed(*key) && (squad as u32) < squads.count {
+            squads.selected = squad as u32;
+        }
+    }
+
+    // [ and ] tune the goal weight of the selected squad
+    if *tool == ActiveTool::SquadGoal {
+        let selected = Squad(squads.selected);
+        let weight = squads.goal(selected).map_or(DEFAULT_GOAL_WEIGHT, |goal| goal.weight);
+        if keys.just_pressed(KeyCode::BracketLeft) {
+            squads.set_weight(selected, weight - GOAL_WEIGHT_STEP);
+        } else if keys.just_pressed(KeyCode::BracketRight) {
+            squads.set_weight(selected, weight + GOAL_WEIGHT_STEP);
+        }
+    }
+}
+
+pub(super) fn set_squad_goal(
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    tool: Res<ActiveTool>,
+    mut squads: ResMut<Squads>,
+) {
+    if *tool != ActiveTool::SquadGoal {
+        return;
+    }
+    let selected = Squad(squads.selected);
+    if buttons.just_pressed(MouseButton::Left) {
+        if let Some(position) = cursor.0 {
+            squads.set_target(selected, Some(position));
+        }
+    } else if buttons.just_pressed(MouseButton::Right) {
+        squads.set_target(selected, None);
+    }
+}
+
+#[cfg(feature = "render")]
+pub(super) fn draw_squad_goals(mut gizmos: Gizmos, squads: Res<Squads>) {
+    for (&squad, goal) in squads.goals.iter() {
+        if let Some(target) = goal.target {
+            let radius = if squad == squads.selected {
+                GOAL_MARKER_RADIUS * 1.5
+            } else {
+                GOAL_MARKER_RADIUS
+            };
+            gizmos.circle_2d(target, radius, squads.color(squad));
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use bevy::prelude::{Entity, Query, Res, ResMut, Resource, Vec2, With, Without};
+
+use super::{
+    despawning::Despawning,
+    grid::SpatialGrid,
+    species::BodyRadius,
+    tags::{TagStats, Tags},
+    Boid,
+    BoidsSettings,
+    Position,
+    Velocity,
+    WorldBounds,
+};
+
+/// Flock-wide summary, refreshed at the end of every frame. Boids that are
+/// despawning are left out.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct FlockStats {
+    pub boids: usize,
+    pub mean_speed: f32,
+    /// Length of the mean unit velocity: 0 for random headings, 1 when every
+    /// boid flies the same way
+    pub polarization: f32,
+    /// Mean distance from each boid to its nearest neighbour, leaving out
+    /// boids with no neighbour inside the neighbour radius
+    pub mean_nearest_distance: f32,
+    /// Mean distance of the boids from the centre of the whole flock, so a
+    /// tag's spread shows whether its boids are in the middle or at the edge
+    pub spread: f32,
+    /// Boids with a NaN or infinite position or velocity
+    pub non_finite: usize,
+    /// Boids further outside the world bounds than boundary handling allows
+    pub out_of_bounds: usize,
+}
+
+/// What one boid contributes to the statistics.
+struct Sample {
+    position: Vec2,
+    velocity: Vec2,
+    finite: bool,
+    out_of_bounds: bool,
+    nearest: Option<f32>,
+}
+
+/// Running sums the statistics are averaged from.
+#[derive(Default)]
+struct Totals {
+    stats: FlockStats,
+    speed: f32,
+    heading: Vec2,
+    spread: f32,
+    nearest: f32,
+    with_neighbours: usize,
+}
+
+impl Totals {
+    fn add(&mut self, sample: &Sample, centre: Vec2) {
+        self.stats.boids += 1;
+        if !sample.finite {
+            self.stats.non_finite += 1;
+            return;
+        }
+        if sample.out_of_bounds {
+            self.stats.out_of_bounds += 1;
+        }
+        self.speed += sample.velocity.length();
+        self.heading += sample.velocity.normalize_or_zero();
+        self.spread += sample.position.distance(centre);
+        if let Some(dist) = sample.nearest {
+            self.nearest += dist;
+            self.with_neighbours += 1;
+        }
+    }
+
+    fn finish(self) -> FlockStats {
+        let mut stats = self.stats;
+        let finite = stats.boids - stats.non_finite;
+        if finite > 0 {
+            stats.mean_speed = self.speed / finite as f32;
+            stats.polarization = self.heading.length() / finite as f32;
+            stats.spread = self.spread / finite as f32;
+        }
+        if self.with_neighbours > 0 {
+            stats.mean_nearest_distance = self.nearest / self.with_neighbours as f32;
+        }
+        stats
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn update_stats(
+    mut stats: ResMut<FlockStats>,
+    mut tag_stats: ResMut<TagStats>,
+    boids: Query<(Entity, &Position, &Velocity, &BodyRadius, Option<&Tags>), (With<Boid>, Without<Despawning>)>,
+    grid: Res<SpatialGrid>,
+    bounds: Res<WorldBounds>,
+    settings: Res<BoidsSettings>,
+) {
+    let samples: Vec<(Sample, Option<&Tags>)> = boids
+        .iter()
+        .map(|(entity, pos, vel, radius, tags)| {
+            let finite = pos.0.is_finite() && vel.0.is_finite();
+            // boundaries act once a boid is its radius past the edge, obstacle
+            // collisions may push it a little further
+            let limit = bounds.half_extents + 2. * radius.0;
+            let nearest = grid
+                .neighbours(pos.0, settings.neighbour_radius)
+                .filter(|&(other, _)| other != entity)
+                .map(|(_, other)| other.distance(pos.0))
+                .filter(|&dist| dist < settings.neighbour_radius)
+                .min_by(f32::total_cmp);
+            let sample = Sample {
+                position: pos.0,
+                velocity: vel.0,
+                finite,
+                out_of_bounds: finite && (pos.0.x.abs() > limit.x || pos.0.y.abs() > limit.y),
+                nearest: nearest.filter(|_| finite),
+            };
+            (sample, tags)
+        })
+        .collect();
+
+    let finite: Vec<Vec2> = samples.iter().filter(|(sample, _)| sample.finite).map(|(sample, _)| sample.position).collect();
+    let centre = if finite.is_empty() { Vec2::ZERO } else { finite.iter().sum::<Vec2>() / finite.len() as f32 };
+
+    let mut flock = Totals::default();
+    let mut tagged: BTreeMap<&str, Totals> = BTreeMap::new();
+    for (sample, tags) in &samples {
+        flock.add(sample, centre);
+        for tag in tags.iter().flat_map(|tags| &tags.0) {
+            tagged.entry(tag).or_default().add(sample, centre);
+        }
+    }
+    *stats = flock.finish();
+    tag_stats.0 = tagged
+        .into_iter()
+        .map(|(tag, totals)| (tag.to_string(), totals.finish()))
+        .collect();
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use bevy::{
+    input::ButtonInput,
+    log::info,
+    prelude::{
+        Added,
+        Commands,
+        Component,
+        Entity,
+        KeyCode,
+        MouseButton,
+        Query,
+        Res,
+        ResMut,
+        Resource,
+        Vec2,
+        With,
+    },
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos};
+
+use crate::tools::{ActiveTool, CursorWorld};
+use super::{Boid, FlockStats, Position};
+
+// drags shorter than this are treated as clicks and tag nothing
+const MIN_SELECTION: f32 = 4.;
+#[cfg(feature = "render")]
+const SELECTION_COLOR: Color = Color::srgba(1., 0.8, 0.4, 0.8);
+
+/// Named labels on a boid. [`TagStats`] follows each label's boids
+/// through the simulation; insert or edit the component to tag boids from
+/// your own systems.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tags(pub Vec<String>);
+
+impl Tags {
+    pub fn insert(&mut self, tag: &str) {
+        if !self.contains(tag) {
+            self.0.push(tag.to_string());
+        }
+    }
+
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.iter().any(|existing| existing == tag)
+    }
+}
+
+/// [`FlockStats`] of the boids carrying each tag, refreshed with them.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct TagStats(pub BTreeMap<String, FlockStats>);
+
+/// Boids spawned within `radius` of `position` get tagged `tag`.
+#[derive(Clone, Debug)]
+pub(super) struct TagRegion {
+    pub(super) tag: String,
+    pub(super) position: Vec2,
+    pub(super) radius: f32,
+}
+
+#[derive(Resource, Default)]
+pub(super) struct TagRegions(pub(super) Vec<TagRegion>);
+
+pub(super) fn tag_spawned(
+    mut commands: Commands,
+    regions: Res<TagRegions>,
+    mut boids: Query<(Entity, &Position, Option<&mut Tags>), Added<Boid>>,
+) {
+    for (entity, pos, tags) in boids.iter_mut() {
+        let mut added = Tags::default();
+        for region in regions.0.iter().filter(|region| region.position.distance(pos.0) < region.radius) {
+            added.insert(&region.tag);
+        }
+        if added.0.is_empty() {
+            continue;
+        }
+        match tags {
+            Some(mut tags) => added.0.iter().for_each(|tag| tags.insert(tag)),
+            None => {
+                commands.entity(entity).try_insert(added);
+            }
+        }
+    }
+}
+
+/// State of the tag tool between frames.
+#[derive(Resource, Default)]
+pub(super) struct TagTool {
+    /// Corner where the current selection drag started
+    drag_start: Option<Vec2>,
+    /// Tags handed out so far, naming the next one
+    count: u32,
+}
+
+/// J toggles the tag tool. Dragging with the left mouse tags the boids in
+/// the box with a new tag, right click removes every tag.
+pub(super) fn tag_input(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut state: ResMut<TagTool>,
+    mut boids: Query<(Entity, &Position, Option<&mut Tags>), With<Boid>>,
+) {
+    if keys.just_pressed(KeyCode::KeyJ) {
+        tool.toggle(ActiveTool::Tag);
+        state.drag_start = None;
+    }
+    if *tool != ActiveTool::Tag {
+        return;
+    }
+    if buttons.just_pressed(MouseButton::Right) {
+        for (entity, _, tags) in boids.iter() {
+            if tags.is_some() {
+                commands.entity(entity).remove::<Tags>();
+            }
+        }
+        state.count = 0;
+        info!("cleared all tags");
+        return;
+    }
+    let Some(cursor) = cursor.0 else {
+        return;
+    };
+    if buttons.just_pressed(MouseButton::Left) {
+        state.drag_start = Some(cursor);
+    }
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(start) = state.drag_start.take() else {
+        return;
+    };
+    if start.distance(cursor) < MIN_SELECTION {
+        return;
+    }
+    let (min, max) = (start.min(cursor), start.max(cursor));
+    let tag = format!("tag{}", state.count + 1);
+    let mut tagged = 0;
+    for (entity, pos, tags) in boids.iter_mut() {
+        if !(pos.0.cmpge(min).all() && pos.0.cmple(max).all()) {
+            continue;
+        }
+        match tags {
+            Some(mut tags) => tags.insert(&tag),
+            None => {
+                commands.entity(entity).try_insert(Tags(vec![tag.clone()]));
+            }
+        }
+        tagged += 1;
+    }
+    if tagged > 0 {
+        state.count += 1;
+        info!("tagged {tagged} boids `{tag}`");
+    }
+}
+
+/// Draws the selection box while dragging with the tag tool.
+#[cfg(feature = "render")]
+pub(super) fn draw_tag_selection(
+    mut gizmos: Gizmos,
+    tool: Res<ActiveTool>,
+    state: Res<TagTool>,
+    cursor: Res<CursorWorld>,
+) {
+    let (ActiveTool::Tag, Some(cursor)) = (*tool, cursor.0) else {
+        return;
+    };
+    if let Some(start) = state.drag_start {
+        gizmos.rect_2d((start + cursor) / 2., 0., (cursor - start).abs(), SELECTION_COLOR);
+    }
+}
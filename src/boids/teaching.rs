@@ -0,0 +1,107 @@
+use bevy::{
+    input::ButtonInput,
+    prelude::{KeyCode, Res, ResMut, Resource},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{
+    default,
+    Color,
+    Commands,
+    Component,
+    DetectChanges,
+    PositionType,
+    Query,
+    Style,
+    Text,
+    TextBundle,
+    TextStyle,
+    Val,
+    With,
+};
+
+use super::BoidsSettings;
+
+/// Presentation mode: every rule starts switched off and N switches on
+/// separation, alignment and cohesion in turn. Backspace goes back a step.
+#[derive(Resource)]
+pub struct TeachingMode {
+    /// Number of rules enabled so far
+    pub stage: usize,
+    /// Separation, alignment and cohesion weights once enabled
+    full: [f32; 3],
+}
+
+const STAGES: [&str; 3] = ["separation", "alignment", "cohesion"];
+
+impl TeachingMode {
+    pub(super) fn new(settings: &BoidsSettings) -> Self {
+        TeachingMode {
+            stage: 0,
+            full: [settings.separation, settings.alignment, settings.cohesion],
+        }
+    }
+
+    /// Sets the rule weights in `settings`, zero for rules not enabled yet.
+    pub(super) fn apply(&self, settings: &mut BoidsSettings) {
+        let weight = |rule: usize| if rule < self.stage { self.full[rule] } else { 0. };
+        settings.separation = weight(0);
+        settings.alignment = weight(1);
+        settings.cohesion = weight(2);
+    }
+
+    #[cfg(feature = "render")]
+    fn caption(&self) -> String {
+        if self.stage == 0 {
+            "No rules: every boid flies straight on".to_string()
+        } else {
+            format!("Rules: {}", STAGES[..self.stage].join(" + "))
+        }
+    }
+}
+
+pub(super) fn teaching_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut teaching: ResMut<TeachingMode>,
+    mut settings: ResMut<BoidsSettings>,
+) {
+    if keys.just_pressed(KeyCode::KeyN) && teaching.stage < STAGES.len() {
+        teaching.stage += 1;
+    } else if keys.just_pressed(KeyCode::Backspace) && teaching.stage > 0 {
+        teaching.stage -= 1;
+    } else {
+        return;
+    }
+    teaching.apply(&mut settings);
+}
+
+#[cfg(feature = "render")]
+#[derive(Component)]
+pub(super) struct Caption;
+
+#[cfg(feature = "render")]
+pub(super) fn setup_caption(mut commands: Commands, teaching: Res<TeachingMode>) {
+    commands.spawn((
+        Caption,
+        TextBundle::from_section(teaching.caption(), TextStyle {
+            font_size: 28.,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(4.),
+            left: Val::Percent(4.),
+            ..default()
+        }),
+    ));
+}
+
+#[cfg(feature = "render")]
+pub(super) fn update_caption(teaching: Res<TeachingMode>, mut captions: Query<&mut Text, With<Caption>>) {
+    if !teaching.is_changed() {
+        return;
+    }
+    for mut text in captions.iter_mut() {
+        text.sections[0].value = teaching.caption();
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::{
+    input::ButtonInput,
+    prelude::{KeyCode, MouseButton, Query, Res, ResMut, Resource, Time, UVec2, Vec2, With},
+};
+#[cfg(feature = "render")]
+use bevy::{
+    prelude::{
+        default,
+        Assets,
+        Commands,
+        Component,
+        Handle,
+        Image,
+        Sprite,
+        SpriteBundle,
+        Transform,
+        Visibility,
+    },
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::tools::{ActiveTool, CursorWorld};
+use super::{field::FieldGrid, Boid, Position, RandomGenerator, SpeedScale, Velocity};
+
+const GRID_SIZE: UVec2 = UVec2::new(64, 40);
+const CELL_SIZE: f32 = 25.;
+// cells between random lattice points of the generated noise
+const NOISE_PERIOD: u32 = 8;
+
+const MIN_SPEED_SCALE: f32 = 0.5;
+const MAX_SPEED_SCALE: f32 = 1.5;
+// radians per second of random heading change at temperature 1
+const MAX_HEADING_NOISE: f32 = 4.;
+
+const BRUSH_RADIUS: f32 = 80.;
+// temperature change per second at the brush centre
+const BRUSH_RATE: f32 = 1.5;
+#[cfg(feature = "render")]
+const HEATMAP_ALPHA: f32 = 0.35;
+
+/// Scalar field in `[0, 1]` where hot regions make boids faster and more erratic.
+#[derive(Resource)]
+pub struct TemperatureField {
+    pub grid: FieldGrid<f32>,
+    // the heatmap needs redrawing
+    #[cfg_attr(not(feature = "render"), allow(dead_code))]
+    dirty: bool,
+}
+
+impl TemperatureField {
+    /// Smooth value noise generated from `seed`.
+    pub fn from_noise(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let lattice = GRID_SIZE / NOISE_PERIOD + 2;
+        let values: Vec<f32> = (0..lattice.x * lattice.y).map(|_| rng.gen()).collect();
+        let at = |x: u32, y: u32| values[(y * lattice.x + x) as usize];
+        let smooth = |t: f32| t * t * (3. - 2. * t);
+
+        let grid = FieldGrid::from_fn(GRID_SIZE, CELL_SIZE, |cell| {
+            let p = cell.as_vec2() / NOISE_PERIOD as f32;
+            let (x, y) = (p.x as u32, p.y as u32);
+            let (tx, ty) = (smooth(p.x.fract()), smooth(p.y.fract()));
+            let bottom = at(x, y) + (at(x + 1, y) - at(x, y)) * tx;
+            let top = at(x, y + 1) + (at(x + 1, y + 1) - at(x, y + 1)) * tx;
+            bottom + (top - bottom) * ty
+        });
+        TemperatureField { grid, dirty: true }
+    }
+
+    pub fn temperature(&self, position: Vec2) -> f32 {
+        self.grid.sample_bilinear(position)
+    }
+
+    pub fn speed_scale(&self, position: Vec2) -> f32 {
+        MIN_SPEED_SCALE + (MAX_SPEED_SCALE - MIN_SPEED_SCALE) * self.temperature(position)
+    }
+}
+
+/// Marker for the sprite visualising the temperature field.
+#[cfg(feature = "render")]
+#[derive(Component)]
+pub(super) struct Heatmap;
+
+#[cfg(feature = "render")]
+pub(super) fn setup_heatmap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    field: Res<TemperatureField>,
+) {
+    let size = field.grid.size();
+    let image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    commands.spawn((
+        Heatmap,
+        SpriteBundle {
+            texture: images.add(image),
+            sprite: Sprite {
+                custom_size: Some(field.grid.half_extents() * 2.),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., -2.),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+pub(super) fn apply_temperature(
+    mut query: Query<(&Position, &mut Velocity, &mut SpeedScale), With<Boid>>,
+    field: Res<TemperatureField>,
+    mut rng: ResMut<RandomGenerator>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (pos, mut vel, mut speed_scale) in query.iter_mut() {
+        let temperature = field.temperature(pos.0);
+        speed_scale.0 = field.speed_scale(pos.0);
+        let jitter = rng.random_f32(-1.0..1.0) * MAX_HEADING_NOISE * temperature * dt;
+        vel.0 = Vec2::from_angle(jitter).rotate(vel.0);
+    }
+}
+
+pub(super) fn paint_temperature(
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    mut tool: ResMut<ActiveTool>,
+    mut field: ResMut<TemperatureField>,
+    time: Res<Time>,
+) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        tool.toggle(ActiveTool::TemperatureBrush);
+    }
+    if *tool != ActiveTool::TemperatureBrush {
+        return;
+    }
+    let Some(center) = cursor.0 else {
+        return;
+    };
+    // left mouse heats, right mouse cools
+    let rate = if buttons.pressed(MouseButton::Left) {
+        BRUSH_RATE
+    } else if buttons.pressed(MouseButton::Right) {
+        -BRUSH_RATE
+    } else {
+        return;
+    };
+    let delta = rate * time.delta_seconds();
+    field.grid.paint(center, BRUSH_RADIUS, |temperature, weight| {
+        *temperature = (*temperature + delta * weight).clamp(0., 1.);
+    });
+    field.dirty = true;
+}
+
+#[cfg(feature = "render")]
+pub(super) fn update_heatmap(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut field: ResMut<TemperatureField>,
+    mut heatmap: Query<(&mut Visibility, &Handle<Image>), With<Heatmap>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Ok((mut visibility, handle)) = heatmap.get_single_mut() else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::KeyH) {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+    if !field.dirty || *visibility == Visibility::Hidden {
+        return;
+    }
+    let Some(image) = images.get_mut(handle) else {
+        return;
+    };
+    let size = field.grid.size();
+    for (cell, temperature) in field.grid.iter() {
+        // image rows run top to bottom, grid rows bottom to top
+        let pixel = ((size.y - 1 - cell.y) * size.x + cell.x) as usize * 4;
+        image.data[pixel..pixel + 4].copy_from_slice(&[
+            (255. * temperature) as u8,
+            (80. * (1. - (2. * temperature - 1.).abs())) as u8,
+            (255. * (1. - temperature)) as u8,
+            (255. * HEATMAP_ALPHA) as u8,
+        ]);
+    }
+    field.dirty = false;
+}
@@ -0,0 +1,123 @@
+use std::{mem, time::Duration};
+use bevy::{
+    app::FixedMain,
+    input::ButtonInput,
+    log::info,
+    prelude::{Component, Fixed, KeyCode, Query, Res, ResMut, Resource, Time, Vec2, Virtual, World},
+};
+
+use super::Position;
+
+// most ticks a single frame catches up on; a machine too slow to keep up
+// runs the simulation slower instead of falling further behind each frame
+const MAX_CATCH_UP_TICKS: u32 = 8;
+// slowest and fastest the speed keys run the simulation at
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.;
+
+/// Position at the start of the last simulation tick. Drawing interpolates
+/// from it to the current position, so motion stays smooth when ticks and
+/// frames don't line up.
+#[derive(Component)]
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+pub(super) struct PreviousPosition(pub(super) Vec2);
+
+impl PreviousPosition {
+    /// Position a `fraction` of the way through the last tick. Jumps longer
+    /// than `max_jump`, like wrapping around the world, are not
+    /// interpolated.
+    #[cfg(feature = "render")]
+    pub(super) fn interpolate(&self, current: Vec2, fraction: f32, max_jump: f32) -> Vec2 {
+        if self.0.distance(current) > max_jump {
+            current
+        } else {
+            self.0.lerp(current, fraction)
+        }
+    }
+}
+
+/// Drives the simulation by hand, for tests, embedding and custom runners
+/// that need to control exactly when and by how much it advances.
+pub struct BoidsSimulation;
+
+impl BoidsSimulation {
+    /// Advances the simulation in `world` by exactly one tick of `dt`
+    /// seconds, outside the app's own frames. Only the fixed step schedules
+    /// run, so filling up the flock, the tools and drawing are left to the
+    /// app's updates.
+    ///
+    /// The world must come from an app with [`BoidsPlugin`](super::BoidsPlugin)
+    /// whose startup has run, e.g. after a first `App::update`.
+    ///
+    /// # Panics
+    ///
+    /// If `dt` is negative or not finite.
+    pub fn step(world: &mut World, dt: f32) {
+        let mut fixed = world.resource_mut::<Time<Fixed>>();
+        fixed.advance_by(Duration::from_secs_f32(dt));
+        let time = fixed.as_generic();
+        // systems in the fixed schedules read the fixed clock through `Time`
+        let previous = mem::replace(&mut *world.resource_mut::<Time>(), time);
+        world.run_schedule(FixedMain);
+        *world.resource_mut::<Time>() = previous;
+    }
+
+    /// [`Self::step`] by the fixed timestep, as an exclusive system for
+    /// custom schedules.
+    pub fn step_system(world: &mut World) {
+        let dt = world.resource::<Time<Fixed>>().timestep().as_secs_f32();
+        BoidsSimulation::step(world, dt);
+    }
+}
+
+/// Simulation seconds per real second.
+#[derive(Resource)]
+pub(super) struct TimeScale(pub(super) f32);
+
+pub(super) fn apply_time_scale(scale: Res<TimeScale>, mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(scale.0);
+}
+
+/// Space pauses and resumes the simulation, the right arrow advances a
+/// paused one by exactly one tick, and the up and down arrows double and
+/// halve its speed, between 0.25x and 4x. Pausing stops the virtual clock,
+/// so everything driven by it waits along with the flock.
+pub(super) fn time_input(world: &mut World) {
+    let keys = world.resource::<ButtonInput<KeyCode>>();
+    let pause = keys.just_pressed(KeyCode::Space);
+    let step = keys.just_pressed(KeyCode::ArrowRight);
+    let faster = keys.just_pressed(KeyCode::ArrowUp);
+    let slower = keys.just_pressed(KeyCode::ArrowDown);
+    if pause {
+        let mut time = world.resource_mut::<Time<Virtual>>();
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+        info!("simulation {}", if time.is_paused() { "paused" } else { "resumed" });
+    }
+    if step && world.resource::<Time<Virtual>>().is_paused() {
+        BoidsSimulation::step_system(world);
+    }
+    if faster || slower {
+        let mut scale = world.resource_mut::<TimeScale>();
+        let speed = if faster { scale.0 * 2. } else { scale.0 / 2. };
+        scale.0 = speed.clamp(MIN_SPEED, MAX_SPEED);
+        let speed = scale.0;
+        world.resource_mut::<Time<Virtual>>().set_relative_speed(speed);
+        info!("simulation speed: {speed}x");
+    }
+}
+
+/// Caps how much time a frame advances the simulation by, so a long frame
+/// runs at most `MAX_CATCH_UP_TICKS` ticks.
+pub(super) fn limit_catch_up(fixed: Res<Time<Fixed>>, mut time: ResMut<Time<Virtual>>) {
+    time.set_max_delta(fixed.timestep() * MAX_CATCH_UP_TICKS);
+}
+
+pub(super) fn remember_positions(mut query: Query<(&Position, &mut PreviousPosition)>) {
+    for (pos, mut previous) in query.iter_mut() {
+        previous.0 = pos.0;
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::{
+    app::{App, FixedUpdate, Plugin, Update},
+    input::ButtonInput,
+    log::info,
+    prelude::{
+        Added,
+        Alpha,
+        Commands,
+        Component,
+        Entity,
+        Gizmos,
+        IntoSystemConfigs,
+        KeyCode,
+        Query,
+        Res,
+        ResMut,
+        Resource,
+        Vec2,
+        With,
+    },
+};
+
+use super::{
+    orientation,
+    species::{Species, SpeciesTable},
+    Boid,
+    Position,
+    WorldBounds,
+};
+
+/// Fading trail behind every boid through the positions it flew through
+/// over its last `length` ticks, drawn in its species colour from
+/// `opacity` at the boid down to nothing at the tail. F11 hides and shows
+/// the trails.
+pub struct TrailPlugin {
+    pub length: usize,
+    pub opacity: f32,
+}
+
+impl Default for TrailPlugin {
+    fn default() -> Self {
+        TrailPlugin { length: 30, opacity: 0.5 }
+    }
+}
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TrailStyle {
+            length: self.length.max(2),
+            opacity: self.opacity.clamp(0., 1.),
+            shown: true,
+        })
+        .add_systems(FixedUpdate, record_trails.after(orientation::update_heading))
+        .add_systems(Update, (trail_input, draw_trails).chain());
+    }
+}
+
+#[derive(Resource)]
+struct TrailStyle {
+    length: usize,
+    opacity: f32,
+    shown: bool,
+}
+
+/// Ring buffer of a boid's latest positions, the oldest at `next` once
+/// it's full.
+#[derive(Component)]
+struct Trail {
+    points: Vec<Vec2>,
+    next: usize,
+}
+
+impl Trail {
+    fn push(&mut self, point: Vec2, length: usize) {
+        if self.points.len() < length {
+            self.points.push(point);
+        } else {
+            self.points[self.next] = point;
+            self.next = (self.next + 1) % length;
+        }
+    }
+
+    /// Points from the oldest to the newest.
+    fn iter(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.points[self.next..].iter().chain(&self.points[..self.next]).copied()
+    }
+}
+
+fn record_trails(
+    mut commands: Commands,
+    style: Res<TrailStyle>,
+    spawned: Query<Entity, Added<Boid>>,
+    mut trails: Query<(&Position, &mut Trail)>,
+) {
+    for entity in spawned.iter() {
+        commands.entity(entity).try_insert(Trail {
+            points: Vec::with_capacity(style.length),
+            next: 0,
+        });
+    }
+    for (pos, mut trail) in trails.iter_mut() {
+        trail.push(pos.0, style.length);
+    }
+}
+
+fn trail_input(keys: Res<ButtonInput<KeyCode>>, mut style: ResMut<TrailStyle>) {
+    if keys.just_pressed(KeyCode::F11) {
+        style.shown = !style.shown;
+        info!("trails: {}", if style.shown { "shown" } else { "hidden" });
+    }
+}
+
+fn draw_trails(
+    mut gizmos: Gizmos,
+    style: Res<TrailStyle>,
+    table: Res<SpeciesTable>,
+    bounds: Res<WorldBounds>,
+    trails: Query<(&Trail, &Species), With<Boid>>,
+) {
+    if !style.shown {
+        return;
+    }
+    // longer steps wrapped around the world and are left out
+    let max_step = bounds.half_extents.min_element();
+    for (trail, species) in trails.iter() {
+        let color = table.get(*species).color;
+        let count = trail.points.len();
+        let fade = |index: usize| color.with_alpha(style.opacity * index as f32 / (count - 1).max(1) as f32);
+        let mut previous: Option<Vec2> = None;
+        for (index, point) in trail.iter().enumerate() {
+            if let Some(from) = previous.filter(|from| from.distance(point) < max_step) {
+                gizmos.line_gradient_2d(from, point, fade(index - 1), fade(index));
+            }
+            previous = Some(point);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::f32::consts::TAU;
+use bevy::prelude::{Component, Query, Res, ResMut, Resource, Time, Vec2, With, Without};
+
+use super::{
+    behaviors::{Steering, SteeringBehavior},
+    density::Crowding,
+    despawning::Despawning,
+    roosting::Dormant,
+    species::SpeciesDesc,
+    Boid,
+    BoidsSettings,
+    RandomGenerator,
+};
+
+// distance ahead of the boid and size of the wander circle
+const DEFAULT_DISTANCE: f32 = 60.;
+const DEFAULT_RADIUS: f32 = 25.;
+// radians per second the point on the circle drifts by at most
+const DEFAULT_JITTER: f32 = 6.;
+
+/// Reynolds' wander for boids without neighbours: each steers towards a
+/// point on a circle projected ahead of it, which drifts randomly around
+/// the circle, so solitary boids meander instead of flying straight.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Wander {
+    /// Weight of the wander force
+    pub strength: f32,
+    /// How far ahead the circle is
+    pub distance: f32,
+    pub radius: f32,
+    /// Most the target drifts around the circle, in radians per second
+    pub jitter: f32,
+}
+
+impl Wander {
+    pub fn new(strength: f32) -> Self {
+        Wander {
+            strength,
+            distance: DEFAULT_DISTANCE,
+            radius: DEFAULT_RADIUS,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+impl SteeringBehavior for Wander {
+    fn name(&self) -> &str {
+        "wander"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        if boid.neighbours.iter().any(|n| n.distance < boid.settings.neighbour_radius) {
+            return Vec2::ZERO;
+        }
+        let heading = boid.velocity.try_normalize().unwrap_or(Vec2::X);
+        boid.head(heading * self.distance + heading.rotate(Vec2::from_angle(boid.wander_angle)) * self.radius)
+    }
+
+    fn weight(&self, _settings: &BoidsSettings, _species: &SpeciesDesc) -> f32 {
+        self.strength
+    }
+}
+
+/// Where on its wander circle a boid is heading, relative to its heading.
+#[derive(Component, Default)]
+pub(super) struct WanderAngle(pub(super) f32);
+
+/// Drifts the wander target of boids that had no neighbours on the last
+/// tick around its circle, before they steer. Random, so not done while
+/// steering in parallel.
+#[allow(clippy::type_complexity)]
+pub(super) fn drift_wander(
+    mut query: Query<(&Crowding, &mut WanderAngle), (With<Boid>, Without<Dormant>, Without<Despawning>)>,
+    wander: Res<Wander>,
+    mut rng: ResMut<RandomGenerator>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (crowding, mut angle) in query.iter_mut() {
+        if crowding.0 == 0 {
+            angle.0 = (angle.0 + rng.random_f32(-1.0..1.0) * wander.jitter * dt) % TAU;
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use bevy::{
+    input::ButtonInput,
+    log::info,
+    prelude::{KeyCode, Res, ResMut, Resource, Vec2},
+};
+#[cfg(feature = "render")]
+use bevy::prelude::{Color, Gizmos, Query, With, Without};
+use boids_core::sensing::{self, Whisker};
+
+use super::{
+    behaviors::{Steering, SteeringBehavior},
+    species::SpeciesDesc,
+    BoidsSettings,
+};
+#[cfg(feature = "render")]
+use super::{obstacles::Obstacle, Boid, BoundaryMode, Position, Species, SpeciesTable, Velocity, WorldBounds};
+
+const DEFAULT_LENGTH: f32 = 80.;
+// radians between the forward whisker and each side whisker
+const DEFAULT_SPREAD: f32 = 0.6;
+// side whiskers are this much shorter than the forward one
+const SIDE_LENGTH: f32 = 0.7;
+
+/// Whiskers boids feel ahead with for walls and obstacles: rays cast to the
+/// left, forward and right whose hit distances steer boids along walls and
+/// around obstacles, smoother through corridors than the avoidance push
+/// alone.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Whiskers {
+    /// Weight of the wall following force
+    pub strength: f32,
+    /// Length of the forward whisker
+    pub length: f32,
+    /// Angle between the forward whisker and each side whisker, in radians
+    pub spread: f32,
+}
+
+impl Whiskers {
+    pub fn new(strength: f32) -> Self {
+        Whiskers {
+            strength,
+            length: DEFAULT_LENGTH,
+            spread: DEFAULT_SPREAD,
+        }
+    }
+
+    /// The left, forward and right whiskers of a boid at `position` flying
+    /// along `heading`, each with how far along it touches an obstacle, given
+    /// by centre and radius, or a wall of a world of `half_extents`.
+    fn cast(
+        &self,
+        position: Vec2,
+        heading: Vec2,
+        obstacles: &[(Vec2, f32)],
+        half_extents: Vec2,
+        walls: (bool, bool),
+    ) -> [Whisker; 3] {
+        let side = self.length * SIDE_LENGTH;
+        let mut cast = [
+            Whisker { direction: Vec2::from_angle(self.spread).rotate(heading), length: side, hit: None },
+            Whisker { direction: heading, length: self.length, hit: None },
+            Whisker { direction: Vec2::from_angle(-self.spread).rotate(heading), length: side, hit: None },
+        ];
+        for whisker in cast.iter_mut() {
+            let wall = sensing::ray_walls(position, whisker.direction, half_extents, walls);
+            let obstacle = obstacles
+                .iter()
+                .filter_map(|&(center, radius)| sensing::ray_circle(position, whisker.direction, center, radius))
+                .reduce(f32::min);
+            whisker.hit = wall.into_iter().chain(obstacle).reduce(f32::min).filter(|&hit| hit < whisker.length);
+        }
+        cast
+    }
+}
+
+impl SteeringBehavior for Whiskers {
+    fn name(&self) -> &str {
+        "whiskers"
+    }
+
+    fn steer(&self, boid: &Steering) -> Vec2 {
+        let heading = boid.velocity.try_normalize().unwrap_or(Vec2::X);
+        let cast = self.cast(boid.position, heading, boid.obstacles, boid.bounds.half_extents, boid.mode.walls());
+        sensing::follow_walls(boid.velocity, &cast, boid.max_speed, boid.max_force)
+    }
+
+    fn weight(&self, _settings: &BoidsSettings, _species: &SpeciesDesc) -> f32 {
+        self.strength
+    }
+}
+
+/// Whether whiskers are drawn.
+#[derive(Resource, Default)]
+pub(super) struct ShowWhiskers(bool);
+
+/// E toggles drawing the whiskers.
+pub(super) fn whisker_input(keys: Res<ButtonInput<KeyCode>>, mut show: ResMut<ShowWhiskers>) {
+    if keys.just_pressed(KeyCode::KeyE) {
+        show.0 = !show.0;
+        info!("whiskers: {}", if show.0 { "shown" } else { "hidden" });
+    }
+}
+
+/// Draws each boid's whiskers, red up to where they touch something.
+#[cfg(feature = "render")]
+#[allow(clippy::too_many_arguments)]
+pub(super) fn draw_whiskers(
+    mut gizmos: Gizmos,
+    show: Res<ShowWhiskers>,
+    whiskers: Res<Whiskers>,
+    boids: Query<(&Position, &Velocity, &Species), With<Boid>>,
+    obstacles: Query<(&Position, &Obstacle), Without<Boid>>,
+    bounds: Res<WorldBounds>,
+    mode: Res<BoundaryMode>,
+    species: Res<SpeciesTable>,
+) {
+    if !show.0 {
+        return;
+    }
+    let obstacles: Vec<(Vec2, f32)> = obstacles.iter().map(|(pos, obstacle)| (pos.0, obstacle.radius)).collect();
+    for (pos, vel, kind) in boids.iter() {
+        let heading = vel.0.try_normalize().unwrap_or(Vec2::X);
+        let walls = species.get(*kind).boundary.unwrap_or(*mode).walls();
+        for whisker in whiskers.cast(pos.0, heading, &obstacles, bounds.half_extents, walls) {
+            match whisker.hit {
+                Some(hit) => {
+                    let touch = pos.0 + whisker.direction * hit;
+                    gizmos.line_2d(pos.0, touch, Color::srgb(1., 0.3, 0.2));
+                    gizmos.line_2d(touch, pos.0 + whisker.direction * whisker.length, Color::srgba(1., 1., 1., 0.15));
+                }
+                None => gizmos.line_2d(pos.0, pos.0 + whisker.direction * whisker.length, Color::srgba(1., 1., 1., 0.3)),
+            }
+        }
+    }
+}
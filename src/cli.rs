@@ -0,0 +1,200 @@
+use std::{path::PathBuf, process, str::FromStr};
+
+const USAGE: &str = "usage: boids [--config <path>] [--scenario <name>] [--seed <n>] [--headless] [--ticks <n>] [--spike-dump <path>] [--smoke-test] [--soak <hours>] [--idle-throttle] [--trails <ticks>] [--teach] [--warmup <ticks>] [--species <path>] [--palette <image>] [--sprite <asset>] [--prefab <path>] [--snapshot <path>] [--tick-rate <hz>] [--time-scale <x>] [--record <path>] [--record-input <path>] [--play-input <path>]
+       boids --gpu <boids> [--seed <n>]
+       boids optimize [--scenario <name>] [--seed <n>] [--ticks <n>] [--search evolution|grid] [--generations <n>] [--polarization <x>] [--spacing <d>]
+       boids diff <recording> <recording> [--tolerance <d>]";
+
+/// How the `optimize` subcommand searches parameter space.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Search {
+    /// Evolution strategy adapting its step size per parameter
+    #[default]
+    Evolution,
+    /// Every combination of a few values per parameter
+    Grid,
+}
+
+impl FromStr for Search {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "evolution" => Ok(Search::Evolution),
+            "grid" => Ok(Search::Grid),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Command line options.
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Named scenario to load instead of the default flock
+    pub scenario: Option<String>,
+    /// Start from the setup in this config file
+    pub config: Option<PathBuf>,
+    /// Seed for every random choice in the simulation, over the config
+    /// file's; 0 when neither gives one
+    pub seed: Option<u64>,
+    /// Run without a window or renderer
+    pub headless: bool,
+    /// Exit after this many frames
+    pub ticks: Option<u32>,
+    /// Write the recent frame history here when a frame time spike is detected
+    pub spike_dump: Option<PathBuf>,
+    /// Run a fixed-length headless sanity check and exit nonzero if it fails
+    pub smoke_test: bool,
+    /// Run headless for this many hours, checking invariants as it goes
+    pub soak: Option<f32>,
+    /// Lower the update rate while unfocused or once the flock has settled
+    pub idle_throttle: bool,
+    /// Draw a fading trail this many ticks long behind every boid
+    pub trails: Option<usize>,
+    /// Start with every flocking rule off and enable them one keypress at a time
+    pub teach: bool,
+    /// Simulate this many ticks before the first frame is shown
+    pub warmup: Option<u32>,
+    /// Load the species table from this file, and save edits to it
+    pub species: Option<PathBuf>,
+    /// Colour each boid from this picture at the spot it spawns
+    pub palette: Option<PathBuf>,
+    /// Draw boids with this image from the asset folder instead of triangles
+    pub sprite: Option<String>,
+    /// Load the prefab tool's prefab from this file, and save selections to it
+    pub prefab: Option<PathBuf>,
+    /// Save snapshots of the flock to this file, and load them from it
+    pub snapshot: Option<PathBuf>,
+    /// Simulation ticks per second, independent of the frame rate
+    pub tick_rate: Option<f64>,
+    /// Simulation seconds per real second
+    pub time_scale: Option<f32>,
+    /// Search flocking parameters for the objective below instead of
+    /// running the simulation; `--ticks` sets the length of each run
+    pub optimize: bool,
+    pub search: Search,
+    /// Generations the evolution strategy runs for
+    pub generations: Option<u32>,
+    /// Lowest polarization the optimized flock should reach
+    pub polarization: Option<f32>,
+    /// Mean nearest neighbour distance the optimized flock should keep
+    pub spacing: Option<f32>,
+    /// Write every boid's position after each tick to this file
+    pub record: Option<PathBuf>,
+    /// Write the keyboard, mouse and settings changes to this file
+    pub record_input: Option<PathBuf>,
+    /// Replay input recorded with `--record-input` from this file
+    pub play_input: Option<PathBuf>,
+    /// Compare these two recordings instead of running the simulation
+    pub diff: Option<(PathBuf, PathBuf)>,
+    /// Distance two recorded positions may be apart without counting as a
+    /// divergence
+    pub tolerance: Option<f32>,
+    /// Simulate this many boids in compute shaders instead of the full
+    /// simulation on the CPU
+    pub gpu: Option<u32>,
+}
+
+impl Args {
+    /// Parses the process arguments, printing usage and exiting on errors.
+    pub fn parse() -> Self {
+        Args::try_parse(std::env::args().skip(1)).unwrap_or_else(|error| {
+            eprintln!("{error}\n{USAGE}");
+            process::exit(2);
+        })
+    }
+
+    fn try_parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "optimize" => parsed.optimize = true,
+                "diff" => {
+                    let a = value(&arg, args.next())?;
+                    let b = value(&arg, args.next())?;
+                    parsed.diff = Some((a.into(), b.into()));
+                }
+                "--scenario" => parsed.scenario = Some(value(&arg, args.next())?),
+                "--config" => parsed.config = Some(value(&arg, args.next())?.into()),
+                "--seed" => parsed.seed = Some(parse_value(&arg, args.next())?),
+                "--headless" => parsed.headless = true,
+                "--smoke-test" => parsed.smoke_test = true,
+                "--soak" => parsed.soak = Some(parse_value(&arg, args.next())?),
+                "--idle-throttle" => parsed.idle_throttle = true,
+                "--trails" => parsed.trails = Some(parse_value(&arg, args.next())?),
+                "--teach" => parsed.teach = true,
+                "--ticks" => parsed.ticks = Some(parse_value(&arg, args.next())?),
+                "--warmup" => parsed.warmup = Some(parse_value(&arg, args.next())?),
+                "--spike-dump" => parsed.spike_dump = Some(value(&arg, args.next())?.into()),
+                "--tick-rate" => parsed.tick_rate = Some(parse_value(&arg, args.next())?),
+                "--time-scale" => parsed.time_scale = Some(parse_value(&arg, args.next())?),
+                "--species" => parsed.species = Some(value(&arg, args.next())?.into()),
+                "--palette" => parsed.palette = Some(value(&arg, args.next())?.into()),
+                "--sprite" => parsed.sprite = Some(value(&arg, args.next())?),
+                "--prefab" => parsed.prefab = Some(value(&arg, args.next())?.into()),
+                "--snapshot" => parsed.snapshot = Some(value(&arg, args.next())?.into()),
+                "--search" => parsed.search = parse_value(&arg, args.next())?,
+                "--generations" => parsed.generations = Some(parse_value(&arg, args.next())?),
+                "--polarization" => parsed.polarization = Some(parse_value(&arg, args.next())?),
+                "--spacing" => parsed.spacing = Some(parse_value(&arg, args.next())?),
+                "--record" => parsed.record = Some(value(&arg, args.next())?.into()),
+                "--record-input" => parsed.record_input = Some(value(&arg, args.next())?.into()),
+                "--play-input" => parsed.play_input = Some(value(&arg, args.next())?.into()),
+                "--tolerance" => parsed.tolerance = Some(parse_value(&arg, args.next())?),
+                "--gpu" => parsed.gpu = Some(parse_value(&arg, args.next())?),
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        let optimize_only = parsed.search != Search::default()
+            || parsed.generations.is_some()
+            || parsed.polarization.is_some()
+            || parsed.spacing.is_some();
+        if optimize_only && !parsed.optimize {
+            return Err("`--search`, `--generations`, `--polarization` and `--spacing` only apply to `optimize`".into());
+        }
+        if parsed.tolerance.is_some() && parsed.diff.is_none() {
+            return Err("`--tolerance` only applies to `diff`".into());
+        }
+        if parsed.sprite.is_some() && !cfg!(feature = "render") {
+            return Err("`--sprite` requires the render feature".into());
+        }
+        if parsed.smoke_test && parsed.soak.is_some() {
+            return Err("`--smoke-test` and `--soak` can't be combined".into());
+        }
+        if parsed.gpu.is_some() && (parsed.headless || parsed.smoke_test || parsed.soak.is_some() || parsed.optimize) {
+            return Err("`--gpu` needs a window".into());
+        }
+        if parsed.gpu.is_some() && parsed.config.is_some() {
+            return Err("`--config` doesn't apply to `--gpu`".into());
+        }
+        if parsed.tolerance.is_some_and(|tolerance| tolerance.is_nan() || tolerance < 0.) {
+            return Err("`--tolerance` must not be negative".into());
+        }
+        if parsed.spacing.is_some_and(|spacing| spacing.is_nan() || spacing <= 0.) {
+            return Err("`--spacing` must be positive".into());
+        }
+        if parsed.soak.is_some_and(|hours| hours.is_nan() || hours <= 0.) {
+            return Err("`--soak` must be positive".into());
+        }
+        if parsed.tick_rate.is_some_and(|hz| hz.is_nan() || hz <= 0.) {
+            return Err("`--tick-rate` must be positive".into());
+        }
+        if parsed.time_scale.is_some_and(|scale| scale.is_nan() || scale < 0.) {
+            return Err("`--time-scale` must not be negative".into());
+        }
+        Ok(parsed)
+    }
+}
+
+fn value(flag: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("`{flag}` expects a value"))
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, raw: Option<String>) -> Result<T, String> {
+    let raw = value(flag, raw)?;
+    raw.parse().map_err(|_| format!("invalid value `{raw}` for `{flag}`"))
+}
@@ -1,27 +1,263 @@
+use std::collections::VecDeque;
+use std::time::Duration;
 use bevy::diagnostic::DiagnosticsStore;
-use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::diagnostic::EntityCountDiagnosticsPlugin;
 use bevy::prelude::*;
 
 /// Marker to find the container entity so we can show/hide the FPS counter
+#[cfg(feature = "render")]
 #[derive(Component)]
 struct FpsRoot;
 
 /// Marker to find the text entity so we can update it
+#[cfg(feature = "render")]
 #[derive(Component)]
 struct FpsText;
 
-pub struct FpsPlugin;
+/// Corner of the window the overlay sits in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Where the numbers go.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FpsOutput {
+    /// UI text in a corner of the window
+    #[cfg(feature = "render")]
+    #[default]
+    Overlay,
+    /// A log line every refresh, for headless runs
+    #[cfg_attr(not(feature = "render"), default)]
+    Log,
+}
+
+/// Which numbers are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FpsMetrics {
+    /// Mean FPS over the window
+    pub fps: bool,
+    /// Lowest and highest FPS over the window
+    pub range: bool,
+    /// Mean FPS of the slowest 1% of frames
+    pub low: bool,
+    pub entities: bool,
+}
+
+impl Default for FpsMetrics {
+    fn default() -> Self {
+        FpsMetrics {
+            fps: true,
+            range: true,
+            low: true,
+            entities: true,
+        }
+    }
+}
+
+/// Frame rate readout. Shows the mean, min, max and 1% low FPS over the
+/// last `window` frames, refreshed every `refresh` seconds so the numbers
+/// hold still long enough to read and compare. `toggle` shows and hides it.
+///
+/// The plugin knows nothing about the app it runs in; app specific lines
+/// can be written to [`FpsExtraText`] and are shown below the metrics.
+pub struct FpsPlugin {
+    pub window: usize,
+    pub refresh: f32,
+    pub output: FpsOutput,
+    pub corner: Corner,
+    pub font_size: f32,
+    pub metrics: FpsMetrics,
+    pub toggle: Option<KeyCode>,
+}
+
+impl Default for FpsPlugin {
+    fn default() -> Self {
+        FpsPlugin {
+            window: 240,
+            refresh: 0.5,
+            output: FpsOutput::default(),
+            corner: Corner::default(),
+            font_size: 16.,
+            metrics: FpsMetrics::default(),
+            toggle: Some(KeyCode::F12),
+        }
+    }
+}
+
+/// Extra lines shown below the metrics.
+#[derive(Resource, Default)]
+pub struct FpsExtraText(pub String);
+
+#[derive(Resource)]
+struct FpsSettings {
+    metrics: FpsMetrics,
+    toggle: Option<KeyCode>,
+    visible: bool,
+}
 
 impl Plugin for FpsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_fps_counter)
-            .add_systems(Update, (fps_text_update_system, fps_counter_showhide));
+        if self.metrics.entities && !app.is_plugin_added::<EntityCountDiagnosticsPlugin>() {
+            app.add_plugins(EntityCountDiagnosticsPlugin);
+        }
+        app.insert_resource(FrameStats::new(self.window, self.refresh))
+            .insert_resource(FpsSettings {
+                metrics: self.metrics,
+                toggle: self.toggle,
+                visible: true,
+            })
+            .init_resource::<FpsExtraText>()
+            .add_systems(Update, (
+                fps_toggle.run_if(resource_exists::<ButtonInput<KeyCode>>),
+                record_frame_time,
+            ));
+        match self.output {
+            #[cfg(feature = "render")]
+            FpsOutput::Overlay => {
+                let (corner, font_size) = (self.corner, self.font_size);
+                app.add_systems(Startup, move |commands: Commands| setup_fps_counter(commands, corner, font_size))
+                    .add_systems(Update, (fps_text_update_system, fps_counter_showhide).after(record_frame_time));
+            }
+            FpsOutput::Log => {
+                app.add_systems(Update, log_fps.after(record_frame_time));
+            }
+        }
+    }
+}
+
+/// Frame times of the last `window` frames, in seconds.
+#[derive(Resource)]
+struct FrameStats {
+    window: usize,
+    frame_times: VecDeque<f32>,
+    refresh: Timer,
+}
+
+struct FpsSummary {
+    mean: f32,
+    min: f32,
+    max: f32,
+    /// Mean FPS of the slowest 1% of frames
+    low: f32,
+}
+
+impl FrameStats {
+    fn new(window: usize, refresh: f32) -> Self {
+        let window = window.max(1);
+        FrameStats {
+            window,
+            frame_times: VecDeque::with_capacity(window),
+            refresh: Timer::new(Duration::from_secs_f32(refresh), TimerMode::Repeating),
+        }
+    }
+
+    fn summary(&self) -> Option<FpsSummary> {
+        let total: f32 = self.frame_times.iter().sum();
+        if total <= 0. {
+            return None;
+        }
+        let mut sorted: Vec<f32> = self.frame_times.iter().copied().collect();
+        sorted.sort_unstable_by(|a, b| b.total_cmp(a));
+        let slowest = &sorted[..sorted.len().div_ceil(100)];
+        Some(FpsSummary {
+            mean: sorted.len() as f32 / total,
+            min: 1. / sorted[0],
+            max: 1. / sorted[sorted.len() - 1],
+            low: slowest.len() as f32 / slowest.iter().sum::<f32>(),
+        })
     }
 }
 
+/// Everything but the mean FPS, one metric group per line.
+fn details(
+    summary: Option<&FpsSummary>,
+    settings: &FpsSettings,
+    diagnostics: &DiagnosticsStore,
+    extra: &FpsExtraText,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(summary) = summary {
+        let mut line = Vec::new();
+        if settings.metrics.range {
+            line.push(format!("min {:.0}  max {:.0}", summary.min, summary.max));
+        }
+        if settings.metrics.low {
+            line.push(format!("1% low {:.0}", summary.low));
+        }
+        if !line.is_empty() {
+            lines.push(line.join("  "));
+        }
+    }
+    if settings.metrics.entities {
+        let entities = diagnostics
+            .get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+            .and_then(|count| count.value())
+            .map_or_else(|| "N/A".to_string(), |count| format!("{count:.0}"));
+        lines.push(format!("Entities: {entities}"));
+    }
+    lines.extend(extra.0.lines().map(str::to_string));
+    lines
+}
+
+fn record_frame_time(mut stats: ResMut<FrameStats>, time: Res<Time<Real>>) {
+    let delta = time.delta_seconds();
+    if delta <= 0. {
+        return;
+    }
+    if stats.frame_times.len() == stats.window {
+        stats.frame_times.pop_front();
+    }
+    stats.frame_times.push_back(delta);
+    stats.refresh.tick(time.delta());
+}
+
+fn fps_toggle(keys: Res<ButtonInput<KeyCode>>, mut settings: ResMut<FpsSettings>) {
+    if settings.toggle.is_some_and(|key| keys.just_pressed(key)) {
+        settings.visible = !settings.visible;
+    }
+}
+
+fn log_fps(
+    stats: Res<FrameStats>,
+    settings: Res<FpsSettings>,
+    diagnostics: Res<DiagnosticsStore>,
+    extra: Res<FpsExtraText>,
+) {
+    if !settings.visible || !stats.refresh.just_finished() {
+        return;
+    }
+    let summary = stats.summary();
+    let mut lines = details(summary.as_ref(), &settings, &diagnostics, &extra);
+    if settings.metrics.fps {
+        let fps = summary.map_or_else(|| "N/A".to_string(), |summary| format!("{:.0}", summary.mean));
+        lines.insert(0, format!("FPS: {fps}"));
+    }
+    if !lines.is_empty() {
+        info!("{}", lines.join(", "));
+    }
+}
+
+#[cfg(feature = "render")]
 fn setup_fps_counter(
     mut commands: Commands,
+    corner: Corner,
+    font_size: f32,
 ) {
+    // 1% away from the window edges, the other sides set to Auto so it can
+    // be automatically sized depending on the text
+    let (left, right) = match corner {
+        Corner::TopLeft | Corner::BottomLeft => (Val::Percent(1.), Val::Auto),
+        Corner::TopRight | Corner::BottomRight => (Val::Auto, Val::Percent(1.)),
+    };
+    let (top, bottom) = match corner {
+        Corner::TopLeft | Corner::TopRight => (Val::Percent(1.), Val::Auto),
+        Corner::BottomLeft | Corner::BottomRight => (Val::Auto, Val::Percent(1.)),
+    };
     // create our UI root node
     // this is the wrapper/container for the text
     let root = commands.spawn((
@@ -34,14 +270,10 @@ fn setup_fps_counter(
             z_index: ZIndex::Global(i32::MAX),
             style: Style {
                 position_type: PositionType::Absolute,
-                // position it at the top-right corner
-                // 1% away from the top window edge
-                right: Val::Percent(1.),
-                top: Val::Percent(1.),
-                // set bottom/left to Auto, so it can be
-                // automatically sized depending on the text
-                bottom: Val::Auto,
-                left: Val::Auto,
+                left,
+                right,
+                top,
+                bottom,
                 // give it some padding for readability
                 padding: UiRect::all(Val::Px(4.0)),
                 ..Default::default()
@@ -53,12 +285,12 @@ fn setup_fps_counter(
     let text_fps = commands.spawn((
         FpsText,
         TextBundle {
-            // use two sections, so it is easy to update just the number
+            // use separate sections, so it is easy to update just the number
             text: Text::from_sections([
                 TextSection {
-                    value: "FPS: ".into(),
+                    value: "".into(),
                     style: TextStyle {
-                        font_size: 16.0,
+                        font_size,
                         color: Color::WHITE,
                         // if you want to use your game's font asset,
                         // uncomment this and provide the handle:
@@ -67,13 +299,19 @@ fn setup_fps_counter(
                     }
                 },
                 TextSection {
-                    value: " N/A".into(),
+                    value: "".into(),
                     style: TextStyle {
-                        font_size: 16.0,
+                        font_size,
                         color: Color::WHITE,
-                        // if you want to use your game's font asset,
-                        // uncomment this and provide the handle:
-                        // font: my_font_handle
+                        ..default()
+                    }
+                },
+                // everything else, rewritten as a whole
+                TextSection {
+                    value: "".into(),
+                    style: TextStyle {
+                        font_size: font_size * 0.875,
+                        color: Color::srgb(0.8, 0.8, 0.8),
                         ..default()
                     }
                 },
@@ -84,16 +322,30 @@ fn setup_fps_counter(
     commands.entity(root).push_children(&[text_fps]);
 }
 
+#[cfg(feature = "render")]
 fn fps_text_update_system(
+    stats: Res<FrameStats>,
+    settings: Res<FpsSettings>,
     diagnostics: Res<DiagnosticsStore>,
+    extra: Res<FpsExtraText>,
     mut query: Query<&mut Text, With<FpsText>>,
 ) {
+    if !stats.refresh.just_finished() {
+        return;
+    }
+    let summary = stats.summary();
+    let details = details(summary.as_ref(), &settings, &diagnostics, &extra).join("\n");
     for mut text in &mut query {
-        // try to get a "smoothed" FPS value from Bevy
-        if let Some(value) = diagnostics
-            .get(&FrameTimeDiagnosticsPlugin::FPS)
-            .and_then(|fps| fps.smoothed())
-        {
+        if !settings.metrics.fps {
+            text.sections[0].value.clear();
+            text.sections[1].value.clear();
+            text.sections[2].value.clone_from(&details);
+            continue;
+        }
+        text.sections[0].value = "FPS: ".into();
+        text.sections[2].value = if details.is_empty() { details.clone() } else { format!("\n{details}") };
+        if let Some(summary) = &summary {
+            let value = summary.mean;
             // Format the number as to leave space for 4 digits, just in case,
             // right-aligned and rounded. This helps readability when the
             // number changes rapidly.
@@ -107,7 +359,7 @@ fn fps_text_update_system(
             } else if value >= 60.0 {
                 // Between 60-120 FPS, gradually transition from yellow to green
                 Color::srgba(
-                    (1.0 - (value - 60.0) / (120.0 - 60.0)) as f32,
+                    1.0 - (value - 60.0) / (120.0 - 60.0),
                     1.0,
                     0.0,
                     1.0,
@@ -116,7 +368,7 @@ fn fps_text_update_system(
                 // Between 30-60 FPS, gradually transition from red to yellow
                 Color::srgba(
                     1.0,
-                    ((value - 30.0) / (60.0 - 30.0)) as f32,
+                    (value - 30.0) / (60.0 - 30.0),
                     0.0,
                     1.0,
                 )
@@ -133,16 +385,17 @@ fn fps_text_update_system(
     }
 }
 
-/// Toggle the FPS counter when pressing F12
+/// Shows or hides the overlay with the toggle key
+#[cfg(feature = "render")]
 fn fps_counter_showhide(
     mut q: Query<&mut Visibility, With<FpsRoot>>,
-    kbd: Res<ButtonInput<KeyCode>>,
+    settings: Res<FpsSettings>,
 ) {
-    if kbd.just_pressed(KeyCode::F12) {
-        let mut vis = q.single_mut();
-        *vis = match *vis {
-            Visibility::Hidden => Visibility::Visible,
-            _ => Visibility::Hidden,
-        };
-    }
-}
\ No newline at end of file
+    if !settings.is_changed() {
+        return;
+    }
+    let Ok(mut vis) = q.get_single_mut() else {
+        return;
+    };
+    *vis = if settings.visible { Visibility::Visible } else { Visibility::Hidden };
+}
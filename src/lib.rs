@@ -0,0 +1,89 @@
+//! Boids flocking simulation as a Bevy plugin.
+//!
+//! Add [`BoidsPlugin`] to an app, configured through its builder methods,
+//! and the flock fills up to its max boid count. Boids can also be spawned
+//! with [`BoidBundle`] and queried through the [`Boid`], [`Position`] and
+//! [`Velocity`] components from your own systems. [`BoidsSimulation`]
+//! steps the simulation by hand, one tick at a time.
+//!
+//! [`FpsPlugin`] is a frame rate readout that knows nothing about boids and
+//! can be added to any Bevy app.
+
+mod boids;
+mod frame_counter;
+mod replay;
+mod strict;
+mod tools;
+
+pub use boids::{
+    Acceleration,
+    Alignment,
+    ApplyForce,
+    BodyRadius,
+    Boid,
+    BoidBundle,
+    BoidExited,
+    BoidMemoryUsage,
+    BoidShading,
+    BoidsPlugin,
+    BoidsSettings,
+    BoidsSimulation,
+    BoidsSnapshot,
+    BoundaryMode,
+    Cohesion,
+    Corridor,
+    Disturbance,
+    Drag,
+    Edge,
+    EdgeEmitter,
+    FlockDesc,
+    FlockId,
+    FlockStats,
+    ForceFilter,
+    ForceMixing,
+    GridStats,
+    Integrator,
+    KeyframeTrack,
+    Mass,
+    Neighbour,
+    Parameter,
+    PerceptionMetric,
+    Position,
+    Predator,
+    PredictiveSeparation,
+    Prefab,
+    PrefabBoid,
+    RngState,
+    Separation,
+    SettingsProblem,
+    SnapshotBoid,
+    SnapshotPredator,
+    SpawnArea,
+    SpawnSchedule,
+    SpawnStrategy,
+    Spawnee,
+    Species,
+    SpeciesDesc,
+    SpeciesTable,
+    SpinLattice,
+    Squad,
+    Squads,
+    SteeringBehavior,
+    StampPrefab,
+    Steering,
+    TagStats,
+    Tags,
+    Velocity,
+    Wander,
+    Whiskers,
+    WorldBounds,
+};
+#[cfg(feature = "render")]
+pub use boids::{BoidAppearance, BoidMaterial, BoidMesh, DrawOrder, GpuBoidsPlugin, TrailPlugin};
+#[cfg(feature = "debug")]
+pub use boids::{DebugLayers, DebugOverlayPlugin};
+#[cfg(feature = "editor")]
+pub use boids::{SettingsPanelPlugin, SpeciesEditorPlugin};
+pub use frame_counter::{Corner, FpsExtraText, FpsMetrics, FpsOutput, FpsPlugin};
+pub use replay::{InputPlaybackPlugin, InputRecorderPlugin};
+pub use tools::{ActiveTool, CursorWorld, ToolsPlugin};
@@ -1,19 +1,300 @@
+use std::{process, time::Duration};
 use bevy::{
     prelude::*,
     DefaultPlugins,
-    sprite::{Wireframe2dPlugin},
+    app::{PluginGroupBuilder, ScheduleRunnerPlugin},
+    time::TimeUpdateStrategy,
     diagnostic::FrameTimeDiagnosticsPlugin,
+    window::ExitCondition,
+};
+#[cfg(feature = "render")]
+use bevy::{
+    sprite::{Wireframe2dPlugin},
+    render::{settings::WgpuSettings, RenderPlugin},
+    winit::WinitPlugin,
 };
 
-use crate::boids::BoidsPlugin;
-use crate::frame_counter::FpsPlugin;
+use boids::{
+    BoidMemoryUsage,
+    BoidsPlugin,
+    FpsExtraText,
+    FpsOutput,
+    FpsPlugin,
+    GridStats,
+    InputPlaybackPlugin,
+    InputRecorderPlugin,
+    TagStats,
+};
+#[cfg(feature = "render")]
+use boids::{BoidAppearance, GpuBoidsPlugin, TrailPlugin};
+#[cfg(feature = "debug")]
+use boids::DebugOverlayPlugin;
+#[cfg(feature = "editor")]
+use boids::{SettingsPanelPlugin, SpeciesEditorPlugin};
+use crate::cli::Args;
+use crate::optimize::Objective;
+use crate::scenarios::Scenario;
+use crate::shutdown::ShutdownPlugin;
+#[cfg(feature = "render")]
+use crate::seeds::SeedPanelPlugin;
+use crate::smoke::SmokeTestPlugin;
+use crate::soak::SoakPlugin;
+#[cfg(feature = "render")]
+use crate::throttle::IdleThrottlePlugin;
+use crate::trajectory::RecorderPlugin;
+use crate::warmup::WarmupPlugin;
+use crate::watchdog::WatchdogPlugin;
+
+mod cli;
+mod optimize;
+mod scenarios;
+#[cfg(feature = "render")]
+mod seeds;
+mod shutdown;
+mod smoke;
+mod soak;
+#[cfg(feature = "render")]
+mod throttle;
+mod trajectory;
+mod warmup;
+mod watchdog;
+
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static ALLOCATOR: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
-mod boids;
-mod frame_counter;
+const SMOKE_TEST_TICKS: u32 = 600;
+// length of each optimizer run, long enough for the default flock to fill
+// up and settle
+const OPTIMIZE_TICKS: u32 = 1200;
+const OPTIMIZE_GENERATIONS: u32 = 15;
+// objective when `optimize` is given no targets
+const DEFAULT_POLARIZATION: f32 = 0.9;
+const DEFAULT_SPACING: f32 = 30.;
+// how far apart recorded positions may be before `diff` reports them, well
+// above float noise but far below anything visible
+const DEFAULT_TOLERANCE: f32 = 1e-3;
+// seconds between frame rate log lines in headless runs
+const HEADLESS_FPS_REFRESH: f32 = 5.;
 
 fn main() {
+    let args = Args::parse();
+    if let Some((a, b)) = &args.diff {
+        match trajectory::diff(a, b, args.tolerance.unwrap_or(DEFAULT_TOLERANCE)) {
+            Ok(false) => return,
+            Ok(true) => process::exit(1),
+            Err(err) => {
+                eprintln!("failed to compare recordings: {err}");
+                process::exit(2);
+            }
+        }
+    }
+    if args.optimize {
+        let objective = if args.polarization.is_none() && args.spacing.is_none() {
+            Objective { min_polarization: Some(DEFAULT_POLARIZATION), spacing: Some(DEFAULT_SPACING) }
+        } else {
+            Objective { min_polarization: args.polarization, spacing: args.spacing }
+        };
+        optimize::run(
+            || configure(&args),
+            objective,
+            args.search,
+            args.generations.unwrap_or(OPTIMIZE_GENERATIONS),
+            args.ticks.unwrap_or(OPTIMIZE_TICKS),
+        );
+        return;
+    }
+    if let Some(count) = args.gpu {
+        run_gpu(&args, count);
+        return;
+    }
+
+    let boids = configure(&args);
+    let mut app = App::new();
+    if args.headless || args.smoke_test || args.soak.is_some() || !cfg!(feature = "render") {
+        app.add_plugins((headless_plugins(), FrameTimeDiagnosticsPlugin))
+            .add_plugins(FpsPlugin {
+                output: FpsOutput::Log,
+                refresh: HEADLESS_FPS_REFRESH,
+                ..default()
+            });
+    } else {
+        add_windowed_plugins(&mut app, &args, boids.seed());
+    }
+    app.add_plugins((boids, ShutdownPlugin))
+        .add_systems(Update, show_boid_stats)
+        .add_plugins(WatchdogPlugin {
+            dump_path: args.spike_dump.clone(),
+            ..default()
+        });
+    if let Some(ticks) = args.warmup {
+        app.add_plugins(WarmupPlugin { ticks });
+    }
+    if let Some(path) = &args.record {
+        app.add_plugins(RecorderPlugin { path: path.clone() });
+    }
+    if let Some(path) = &args.record_input {
+        app.add_plugins(InputRecorderPlugin { path: path.clone() });
+    }
+    if let Some(path) = &args.play_input {
+        app.add_plugins(InputPlaybackPlugin { path: path.clone() });
+    }
+    if args.smoke_test {
+        // fixed timestep so runs are reproducible
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1. / 60.)))
+            .add_plugins(SmokeTestPlugin { ticks: args.ticks.unwrap_or(SMOKE_TEST_TICKS) });
+    } else if let Some(hours) = args.soak {
+        app.add_plugins(SoakPlugin { hours });
+    } else if let Some(ticks) = args.ticks {
+        app.add_systems(PostUpdate, move |mut frames: Local<u32>, mut exit: EventWriter<AppExit>| {
+            *frames += 1;
+            if *frames >= ticks {
+                exit.send(AppExit::Success);
+            }
+        });
+    }
+    if let AppExit::Error(code) = app.run() {
+        process::exit(code.get().into());
+    }
+}
+
+/// The flock set up by the command line options.
+fn configure(args: &Args) -> BoidsPlugin {
+    let mut boids = match &args.config {
+        Some(path) => BoidsPlugin::from_config(path),
+        None => BoidsPlugin::default(),
+    };
+    boids = boids.with_squads(4).with_teaching_mode(args.teach).with_grid_query_count(true);
+    if let Some(seed) = args.seed {
+        boids = boids.with_seed(seed);
+    }
+    if let Some(name) = &args.scenario {
+        let Some(scenario) = Scenario::from_name(name) else {
+            let names: Vec<_> = Scenario::ALL.iter().map(|scenario| scenario.name()).collect();
+            eprintln!("unknown scenario `{name}`, expected one of: {}", names.join(", "));
+            process::exit(2);
+        };
+        boids = scenario.apply(boids);
+    }
+    if let Some(path) = &args.species {
+        boids = boids.with_species_file(path);
+    }
+    if let Some(path) = &args.palette {
+        boids = boids.with_palette_image(path);
+    }
+    #[cfg(feature = "render")]
+    if let Some(path) = &args.sprite {
+        boids = boids.with_appearance(BoidAppearance { sprite: Some(path.clone()), ..default() });
+    }
+    if let Some(path) = &args.prefab {
+        boids = boids.with_prefab_file(path);
+    }
+    if let Some(path) = &args.snapshot {
+        boids = boids.with_snapshot_file(path);
+    }
+    if let Some(hz) = args.tick_rate {
+        boids = boids.with_tick_rate(hz);
+    }
+    if let Some(scale) = args.time_scale {
+        boids = boids.with_time_scale(scale);
+    }
+    boids
+}
+
+/// Adds the boid memory use and how the spatial grid is filled to the
+/// frame rate readout.
+fn show_boid_stats(
+    memory: Res<BoidMemoryUsage>,
+    grid: Res<GridStats>,
+    tags: Res<TagStats>,
+    mut extra: ResMut<FpsExtraText>,
+) {
+    extra.0 = format!(
+        "Boids: {} ({:.1} KiB)\nIndex: {:.1} KiB\nGrid: cells {:.0} wide, {} occupied, {} max / {:.1} mean per cell, {} flocking lookups",
+        memory.boids,
+        memory.component_bytes as f32 / 1024.,
+        memory.index_bytes as f32 / 1024.,
+        grid.cell_size,
+        grid.occupied_cells,
+        grid.max_per_cell,
+        grid.mean_per_cell,
+        grid.flocking_lookups,
+    );
+    for (tag, stats) in &tags.0 {
+        extra.0 += &format!(
+            "\n{tag}: {} boids, polarization {:.2}, spread {:.0}",
+            stats.boids,
+            stats.polarization,
+            stats.spread,
+        );
+    }
+}
+
+#[cfg(feature = "render")]
+fn add_windowed_plugins(app: &mut App, args: &Args, seed: u64) {
+    app.add_plugins((DefaultPlugins, Wireframe2dPlugin, FrameTimeDiagnosticsPlugin, FpsPlugin::default()))
+        .add_plugins(SeedPanelPlugin { seed });
+    if args.idle_throttle {
+        app.add_plugins(IdleThrottlePlugin);
+    }
+    if let Some(length) = args.trails {
+        app.add_plugins(TrailPlugin { length, ..default() });
+    }
+    #[cfg(feature = "debug")]
+    app.add_plugins(DebugOverlayPlugin);
+    #[cfg(feature = "editor")]
+    app.add_plugins((SpeciesEditorPlugin, SettingsPanelPlugin));
+}
+
+/// Without the render feature there is nothing to open a window with.
+#[cfg(not(feature = "render"))]
+fn add_windowed_plugins(_app: &mut App, _args: &Args, _seed: u64) {
+    unreachable!("windowed mode requires the render feature");
+}
+
+/// A window of `count` boids flocking in compute shaders. None of the CPU
+/// simulation's features or tools are available.
+#[cfg(feature = "render")]
+fn run_gpu(args: &Args, count: u32) {
     App::new()
-        .add_plugins((DefaultPlugins, Wireframe2dPlugin, FrameTimeDiagnosticsPlugin))
-        .add_plugins((BoidsPlugin::default(), FpsPlugin))
+        .add_plugins((DefaultPlugins, FrameTimeDiagnosticsPlugin, FpsPlugin::default()))
+        .add_plugins(GpuBoidsPlugin { count, seed: args.seed.unwrap_or_default(), ..default() })
         .run();
 }
+
+#[cfg(not(feature = "render"))]
+fn run_gpu(_args: &Args, _count: u32) {
+    eprintln!("`--gpu` requires the render feature");
+    process::exit(2);
+}
+
+/// Default plugins without a window, event loop or GPU backend.
+#[cfg(feature = "render")]
+fn headless_plugins() -> PluginGroupBuilder {
+    windowless_plugins()
+        .set(RenderPlugin {
+            render_creation: WgpuSettings {
+                backends: None,
+                ..default()
+            }
+            .into(),
+            ..default()
+        })
+        .disable::<WinitPlugin>()
+}
+
+/// Without the render feature the default plugins carry no renderer or event loop.
+#[cfg(not(feature = "render"))]
+fn headless_plugins() -> PluginGroupBuilder {
+    windowless_plugins()
+}
+
+fn windowless_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .add(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1. / 60.)))
+}
@@ -0,0 +1,285 @@
+use std::time::Duration;
+use bevy::{
+    app::{App, PluginsState},
+    log::LogPlugin,
+    tasks::tick_global_task_pools_on_main_thread,
+    time::TimeUpdateStrategy,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use boids::{BoidsPlugin, BoidsSettings, FlockStats};
+use crate::cli::Search;
+use crate::headless_plugins;
+
+// values tried per parameter by the grid search
+const GRID_STEPS: usize = 3;
+// candidates per generation of the evolution strategy, and how many of the
+// best the next generation is centred on
+const POPULATION: usize = 8;
+const PARENTS: usize = 4;
+// per parameter step size, as a fraction of its range
+const INITIAL_STEP: f32 = 0.25;
+const MIN_STEP: f32 = 0.02;
+// how much of a generation's spread carries over into the next step size
+const STEP_LEARNING_RATE: f32 = 0.3;
+// added to the loss of runs that blew up, lost boids or emptied out
+const FAILURE_PENALTY: f32 = 100.;
+// the flock statistics are averaged over this last fraction of each run
+const MEASURED_FRACTION: u32 = 4;
+
+/// A flocking parameter the optimizer searches over.
+#[derive(Clone, Copy, Debug)]
+enum Dimension {
+    Separation,
+    Alignment,
+    Cohesion,
+    DesiredSeparation,
+    NeighbourRadius,
+}
+
+impl Dimension {
+    const ALL: [Dimension; 5] = [
+        Dimension::Separation,
+        Dimension::Alignment,
+        Dimension::Cohesion,
+        Dimension::DesiredSeparation,
+        Dimension::NeighbourRadius,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Dimension::Separation => "separation",
+            Dimension::Alignment => "alignment",
+            Dimension::Cohesion => "cohesion",
+            Dimension::DesiredSeparation => "desired_separation",
+            Dimension::NeighbourRadius => "neighbour_radius",
+        }
+    }
+
+    /// Values the search is kept within.
+    fn range(self) -> (f32, f32) {
+        match self {
+            Dimension::Separation | Dimension::Alignment | Dimension::Cohesion => (0., 3.),
+            Dimension::DesiredSeparation => (10., 100.),
+            Dimension::NeighbourRadius => (30., 200.),
+        }
+    }
+
+    fn get(self, settings: &BoidsSettings) -> f32 {
+        match self {
+            Dimension::Separation => settings.separation,
+            Dimension::Alignment => settings.alignment,
+            Dimension::Cohesion => settings.cohesion,
+            Dimension::DesiredSeparation => settings.desired_separation,
+            Dimension::NeighbourRadius => settings.neighbour_radius,
+        }
+    }
+
+    fn set(self, settings: &mut BoidsSettings, value: f32) {
+        match self {
+            Dimension::Separation => settings.separation = value,
+            Dimension::Alignment => settings.alignment = value,
+            Dimension::Cohesion => settings.cohesion = value,
+            Dimension::DesiredSeparation => settings.desired_separation = value,
+            Dimension::NeighbourRadius => settings.neighbour_radius = value,
+        }
+    }
+}
+
+/// Point in the search space, each parameter scaled to `[0, 1]` across
+/// its range.
+type Point = [f32; 5];
+
+fn to_point(settings: &BoidsSettings) -> Point {
+    Dimension::ALL.map(|dimension| {
+        let (min, max) = dimension.range();
+        ((dimension.get(settings) - min) / (max - min)).clamp(0., 1.)
+    })
+}
+
+fn to_settings(base: BoidsSettings, point: &Point) -> BoidsSettings {
+    let mut settings = base;
+    for (dimension, value) in Dimension::ALL.iter().zip(point) {
+        let (min, max) = dimension.range();
+        dimension.set(&mut settings, min + value.clamp(0., 1.) * (max - min));
+    }
+    settings
+}
+
+/// Flock shape the optimizer aims for.
+#[derive(Clone, Copy, Debug)]
+pub struct Objective {
+    /// Lowest acceptable polarization
+    pub min_polarization: Option<f32>,
+    /// Desired mean distance between a boid and its nearest neighbour
+    pub spacing: Option<f32>,
+}
+
+impl Objective {
+    /// How far `measured` is from the targets, 0 once every one is met.
+    fn loss(&self, measured: &Measurement) -> f32 {
+        let mut loss = 0.;
+        if let Some(target) = self.min_polarization {
+            loss += (target - measured.polarization).max(0.);
+        }
+        if let Some(spacing) = self.spacing {
+            loss += (measured.spacing - spacing).abs() / spacing;
+        }
+        if measured.failed {
+            loss += FAILURE_PENALTY;
+        }
+        loss
+    }
+}
+
+/// Flock statistics averaged over the end of a run.
+#[derive(Clone, Copy, Debug, Default)]
+struct Measurement {
+    polarization: f32,
+    spacing: f32,
+    /// Some boid went non-finite or out of bounds, or no boids were left
+    failed: bool,
+}
+
+/// Runs the simulation configured by `plugin` headless for `ticks` fixed
+/// steps and measures the flock it ends up with.
+fn evaluate(plugin: BoidsPlugin, ticks: u32) -> Measurement {
+    let mut app = App::new();
+    // every run would try to install the global logger again
+    app.add_plugins(headless_plugins().disable::<LogPlugin>())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1. / 60.)))
+        .add_plugins(plugin);
+    while app.plugins_state() == PluginsState::Adding {
+        tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let measured_from = ticks - ticks / MEASURED_FRACTION;
+    let mut measurement = Measurement::default();
+    let mut samples = 0;
+    for tick in 0..ticks {
+        app.update();
+        let stats = *app.world().resource::<FlockStats>();
+        if stats.non_finite > 0 || stats.out_of_bounds > 0 {
+            measurement.failed = true;
+        }
+        if tick >= measured_from {
+            measurement.polarization += stats.polarization;
+            measurement.spacing += stats.mean_nearest_distance;
+            samples += 1;
+            if stats.boids == 0 {
+                measurement.failed = true;
+            }
+        }
+    }
+    if samples > 0 {
+        measurement.polarization /= samples as f32;
+        measurement.spacing /= samples as f32;
+    }
+    measurement
+}
+
+/// Best candidate found so far.
+struct Best {
+    settings: BoidsSettings,
+    measurement: Measurement,
+    loss: f32,
+}
+
+/// Searches separation, alignment and cohesion weights and the two
+/// flocking radii for settings that meet `objective`, running every
+/// candidate in a fresh headless simulation built by `configure`, and
+/// prints the best settings found.
+pub fn run(configure: impl Fn() -> BoidsPlugin, objective: Objective, search: Search, generations: u32, ticks: u32) {
+    let base = configure().settings();
+    let mut best: Option<Best> = None;
+    let mut runs = 0;
+    let mut try_point = |point: &Point| -> f32 {
+        let settings = to_settings(base, point);
+        let measurement = evaluate(configure().with_settings(settings), ticks);
+        let loss = objective.loss(&measurement);
+        runs += 1;
+        match &best {
+            Some(best) if best.loss <= loss => {}
+            _ => best = Some(Best { settings, measurement, loss }),
+        }
+        loss
+    };
+
+    match search {
+        Search::Grid => {
+            let total = GRID_STEPS.pow(Dimension::ALL.len() as u32);
+            for index in 0..total {
+                let mut point = [0.; 5];
+                let mut rest = index;
+                for value in point.iter_mut() {
+                    *value = (rest % GRID_STEPS) as f32 / (GRID_STEPS - 1) as f32;
+                    rest /= GRID_STEPS;
+                }
+                let loss = try_point(&point);
+                eprintln!("run {}/{total}: loss {loss:.4}", index + 1);
+            }
+        }
+        Search::Evolution => {
+            // a diagonal evolution strategy: sample around the mean, move the
+            // mean to the best candidates and adapt each parameter's step to
+            // how spread out they were
+            let mut rng = StdRng::seed_from_u64(configure().seed());
+            let mut mean = to_point(&base);
+            let mut step = [INITIAL_STEP; 5];
+            try_point(&mean);
+            for generation in 0..generations {
+                let mut candidates: Vec<(f32, Point)> = (0..POPULATION)
+                    .map(|_| {
+                        let mut point = mean;
+                        for (value, step) in point.iter_mut().zip(step) {
+                            *value = (*value + step * normal(&mut rng)).clamp(0., 1.);
+                        }
+                        (try_point(&point), point)
+                    })
+                    .collect();
+                candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let parents = &candidates[..PARENTS];
+                let previous = mean;
+                for (dimension, value) in mean.iter_mut().enumerate() {
+                    *value = parents.iter().map(|(_, point)| point[dimension]).sum::<f32>() / PARENTS as f32;
+                    let spread = (parents
+                        .iter()
+                        .map(|(_, point)| (point[dimension] - previous[dimension]).powi(2))
+                        .sum::<f32>()
+                        / PARENTS as f32)
+                        .sqrt();
+                    step[dimension] = ((1. - STEP_LEARNING_RATE) * step[dimension] + STEP_LEARNING_RATE * spread)
+                        .max(MIN_STEP);
+                }
+                eprintln!(
+                    "generation {}/{generations}: best loss this generation {:.4}",
+                    generation + 1,
+                    parents[0].0,
+                );
+            }
+        }
+    }
+
+    let Some(best) = best else {
+        return;
+    };
+    println!(
+        "best of {runs} runs: loss {:.4}, polarization {:.3}, nearest neighbour distance {:.1}{}",
+        best.loss,
+        best.measurement.polarization,
+        best.measurement.spacing,
+        if best.measurement.failed { " (flock broke down)" } else { "" },
+    );
+    for dimension in Dimension::ALL {
+        println!("{} = {:.3}", dimension.name(), dimension.get(&best.settings));
+    }
+}
+
+/// Standard normal sample by the Box-Muller transform.
+fn normal(rng: &mut StdRng) -> f32 {
+    let u: f32 = rng.gen_range(f32::EPSILON..1.);
+    let v: f32 = rng.gen();
+    (-2. * u.ln()).sqrt() * (std::f32::consts::TAU * v).cos()
+}
@@ -0,0 +1,305 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use bevy::{input::InputSystem, prelude::*, time::TimeUpdateStrategy};
+
+use crate::boids::BoidsSettings;
+use crate::tools::CursorWorld;
+
+// first line of every input recording, bumped when the format changes
+const HEADER: &str = "boids-input 2";
+
+// keys recorded and replayed, every key the simulation and its tools read
+const KEYS: [KeyCode; 61] = [
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF,
+    KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR,
+    KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX,
+    KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+    KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::BracketLeft, KeyCode::BracketRight, KeyCode::Comma, KeyCode::Period,
+    KeyCode::Equal, KeyCode::Minus,
+    KeyCode::Backspace, KeyCode::Space, KeyCode::Tab, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::ArrowUp, KeyCode::ArrowDown,
+];
+const BUTTONS: [MouseButton; 3] = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Simulation ticks completed since startup.
+#[derive(Resource, Default)]
+struct TickClock(u64);
+
+fn count_tick(mut clock: ResMut<TickClock>) {
+    clock.0 += 1;
+}
+
+/// Counts ticks and steps frames one tick at a time, for recording and
+/// playback alike, so both see the same ticks in the same frames. Added by
+/// whichever of the two plugins comes first.
+struct TickClockPlugin;
+
+impl Plugin for TickClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TickClock>()
+            .add_systems(PostStartup, step_one_tick_per_frame)
+            .add_systems(Last, step_one_tick_per_frame)
+            .add_systems(FixedPostUpdate, count_tick);
+    }
+}
+
+/// Advances time by exactly one tick every frame, so input lands on the
+/// same ticks however fast frames are drawn. Kept up to date every frame
+/// as the time scale changes.
+fn step_one_tick_per_frame(
+    mut commands: Commands,
+    fixed: Res<Time<Fixed>>,
+    time: Res<Time<Virtual>>,
+    strategy: Option<Res<TimeUpdateStrategy>>,
+) {
+    let speed = time.relative_speed();
+    let step = if speed > 0. { fixed.timestep().div_f32(speed) } else { fixed.timestep() };
+    if !matches!(strategy.as_deref(), Some(TimeUpdateStrategy::ManualDuration(current)) if *current == step) {
+        commands.insert_resource(TimeUpdateStrategy::ManualDuration(step));
+    }
+}
+
+/// Something that happened to the input between two ticks.
+#[derive(Clone, Debug, PartialEq)]
+enum InputEvent {
+    KeyPress(KeyCode),
+    KeyRelease(KeyCode),
+    ButtonPress(MouseButton),
+    ButtonRelease(MouseButton),
+    /// The cursor moved, in world coordinates, or left the window
+    Cursor(Option<Vec2>),
+    /// The flocking parameters were changed, from a panel or a key
+    Settings(BoidsSettings),
+}
+
+impl InputEvent {
+    fn write(&self, tick: u64, writer: &mut impl Write) -> io::Result<()> {
+        write!(writer, "{tick} ")?;
+        match self {
+            InputEvent::KeyPress(key) => writeln!(writer, "key press {key:?}"),
+            InputEvent::KeyRelease(key) => writeln!(writer, "key release {key:?}"),
+            InputEvent::ButtonPress(button) => writeln!(writer, "button press {button:?}"),
+            InputEvent::ButtonRelease(button) => writeln!(writer, "button release {button:?}"),
+            InputEvent::Cursor(Some(position)) => writeln!(writer, "cursor {} {}", position.x, position.y),
+            InputEvent::Cursor(None) => writeln!(writer, "cursor none"),
+            InputEvent::Settings(settings) => {
+                let mut settings = *settings;
+                let values: Vec<String> = settings.values_mut().iter().map(|value| value.to_string()).collect();
+                writeln!(writer, "settings {}", values.join(" "))
+            }
+        }
+    }
+
+    fn parse(words: &[&str]) -> Option<Self> {
+        let key = |name: &str| KEYS.into_iter().find(|key| format!("{key:?}") == name);
+        let button = |name: &str| BUTTONS.into_iter().find(|button| format!("{button:?}") == name);
+        match words {
+            ["key", "press", name] => Some(InputEvent::KeyPress(key(name)?)),
+            ["key", "release", name] => Some(InputEvent::KeyRelease(key(name)?)),
+            ["button", "press", name] => Some(InputEvent::ButtonPress(button(name)?)),
+            ["button", "release", name] => Some(InputEvent::ButtonRelease(button(name)?)),
+            ["cursor", "none"] => Some(InputEvent::Cursor(None)),
+            ["cursor", x, y] => Some(InputEvent::Cursor(Some(Vec2::new(x.parse().ok()?, y.parse().ok()?)))),
+            ["settings", values @ ..] => {
+                let mut settings = BoidsSettings::default();
+                let mut fields = settings.values_mut();
+                if values.len() != fields.len() {
+                    return None;
+                }
+                for (field, value) in fields.iter_mut().zip(values) {
+                    **field = value.parse().ok()?;
+                }
+                Some(InputEvent::Settings(settings))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// One `<tick> <event>` line per event, `<tick>` being the number of
+/// simulation ticks completed when the input was read.
+fn read_recording(path: &Path) -> io::Result<Vec<(u64, InputEvent)>> {
+    let text = fs::read_to_string(path)?;
+    let mut lines = text.lines();
+    if lines.next() != Some(HEADER) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not an input recording"));
+    }
+    lines
+        .enumerate()
+        .map(|(index, line)| {
+            let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("malformed line {}", index + 2));
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (tick, event) = words.split_first().ok_or_else(invalid)?;
+            Ok((tick.parse().map_err(|_| invalid())?, InputEvent::parse(event).ok_or_else(invalid)?))
+        })
+        .collect()
+}
+
+/// Writes the keys, mouse buttons and cursor positions the simulation
+/// reads, and every change to the [`BoidsSettings`], to `path` along with
+/// the tick they happened at, for [`InputPlaybackPlugin`] to replay. Frames
+/// are stepped one tick at a time while recording, as they are in playback,
+/// so the input of each frame is read between the same two ticks.
+pub struct InputRecorderPlugin {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+struct InputRecorder {
+    writer: BufWriter<File>,
+    cursor: Option<Vec2>,
+    settings: Option<BoidsSettings>,
+    failed: bool,
+}
+
+impl Plugin for InputRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        let recorder = File::create(&self.path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            writeln!(writer, "{HEADER}")?;
+            Ok(InputRecorder { writer, cursor: None, settings: None, failed: false })
+        });
+        match recorder {
+            Ok(recorder) => {
+                info!("recording input to {}", self.path.display());
+                if !app.is_plugin_added::<TickClockPlugin>() {
+                    app.add_plugins(TickClockPlugin);
+                }
+                app.insert_resource(recorder).add_systems(Last, record_input);
+            }
+            Err(err) => error!("failed to create input recording {}: {err}", self.path.display()),
+        }
+    }
+}
+
+/// Records the input of the frame after its ticks ran, as the tools only
+/// react to it in `Update` and so affect the tick after those.
+fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    clock: Res<TickClock>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorWorld>,
+    settings: Res<BoidsSettings>,
+) {
+    if recorder.failed {
+        return;
+    }
+    let mut events = Vec::new();
+    events.extend(keys.get_just_pressed().filter(|key| KEYS.contains(key)).map(|&key| InputEvent::KeyPress(key)));
+    events.extend(keys.get_just_released().filter(|key| KEYS.contains(key)).map(|&key| InputEvent::KeyRelease(key)));
+    events.extend(buttons.get_just_pressed().map(|&button| InputEvent::ButtonPress(button)));
+    events.extend(buttons.get_just_released().map(|&button| InputEvent::ButtonRelease(button)));
+    if recorder.cursor != cursor.0 {
+        recorder.cursor = cursor.0;
+        events.push(InputEvent::Cursor(cursor.0));
+    }
+    if recorder.settings != Some(*settings) {
+        recorder.settings = Some(*settings);
+        events.push(InputEvent::Settings(*settings));
+    }
+    if events.is_empty() {
+        return;
+    }
+    let recorder = &mut *recorder;
+    let written = events
+        .iter()
+        .try_for_each(|event| event.write(clock.0, &mut recorder.writer))
+        .and_then(|()| recorder.writer.flush());
+    if let Err(err) = written {
+        error!("failed to write input recording, stopping: {err}");
+        recorder.failed = true;
+    }
+}
+
+/// Replays an input recording made with [`InputRecorderPlugin`] in place
+/// of the real keyboard, mouse and cursor, each event at the tick it was
+/// recorded at. Frames are stepped one tick at a time, so a run with the
+/// same seed reproduces the recorded session exactly.
+pub struct InputPlaybackPlugin {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+struct InputPlayback {
+    events: Vec<(u64, InputEvent)>,
+    next: usize,
+    keys: ButtonInput<KeyCode>,
+    buttons: ButtonInput<MouseButton>,
+    cursor: Option<Vec2>,
+}
+
+impl Plugin for InputPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        let events = match read_recording(&self.path) {
+            Ok(events) => events,
+            Err(err) => {
+                error!("failed to load input recording {}: {err}", self.path.display());
+                return;
+            }
+        };
+        info!("replaying {} input events from {}", events.len(), self.path.display());
+        let playback = play_input.after(InputSystem);
+        #[cfg(feature = "render")]
+        let playback = playback.after(crate::tools::update_cursor_world);
+        app.insert_resource(InputPlayback {
+            events,
+            next: 0,
+            keys: ButtonInput::default(),
+            buttons: ButtonInput::default(),
+            cursor: None,
+        })
+        .add_systems(PreUpdate, playback);
+        if !app.is_plugin_added::<TickClockPlugin>() {
+            app.add_plugins(TickClockPlugin);
+        }
+    }
+}
+
+/// Replaces this frame's input with the recorded events due before the
+/// tick that follows it.
+fn play_input(
+    mut playback: ResMut<InputPlayback>,
+    clock: Res<TickClock>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut buttons: ResMut<ButtonInput<MouseButton>>,
+    mut cursor: ResMut<CursorWorld>,
+    mut settings: ResMut<BoidsSettings>,
+) {
+    let playback = &mut *playback;
+    playback.keys.clear();
+    playback.buttons.clear();
+    // this frame's tick runs before Update, where the tools read the input
+    while let Some((tick, event)) = playback.events.get(playback.next) {
+        if *tick > clock.0 + 1 {
+            break;
+        }
+        match event {
+            InputEvent::KeyPress(key) => playback.keys.press(*key),
+            InputEvent::KeyRelease(key) => playback.keys.release(*key),
+            InputEvent::ButtonPress(button) => playback.buttons.press(*button),
+            InputEvent::ButtonRelease(button) => playback.buttons.release(*button),
+            InputEvent::Cursor(position) => playback.cursor = *position,
+            InputEvent::Settings(recorded) => {
+                if *settings != *recorded {
+                    *settings = *recorded;
+                }
+            }
+        }
+        playback.next += 1;
+        if playback.next == playback.events.len() {
+            info!("input playback finished at tick {}", clock.0);
+        }
+    }
+    *keys = playback.keys.clone();
+    *buttons = playback.buttons.clone();
+    cursor.0 = playback.cursor;
+}
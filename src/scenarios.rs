@@ -0,0 +1,391 @@
+use std::f32::consts::TAU;
+use bevy::{color::Color, prelude::Vec2};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use boids::{
+    BoidShading,
+    BoidsPlugin,
+    BoidsSettings,
+    BoundaryMode,
+    Corridor,
+    Disturbance,
+    Drag,
+    Edge,
+    FlockDesc,
+    ForceMixing,
+    KeyframeTrack,
+    Parameter,
+    PerceptionMetric,
+    SpawnArea,
+    SpawnSchedule,
+    SpawnStrategy,
+    Spawnee,
+    SpeciesDesc,
+    SpinLattice,
+};
+
+const CRUISE_SPEED: f32 = 150.;
+
+/// Named, reproducible starting setups used for demos and benchmarking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scenario {
+    /// A large flock packed into a small disc, fading in over a couple of
+    /// seconds
+    DenseBlob,
+    /// Two flocks flying head-on into each other
+    CollidingFlocks,
+    /// A flock funnelled down a corridor lined with obstacles, with
+    /// predators lying in wait along it
+    PredatorGauntlet,
+    /// A flock scattered through a staggered grid of obstacles, half of it
+    /// mixing forces by priority instead of summing them
+    ObstacleMaze,
+    /// A stream of boids emitted from the left edge flowing past obstacles
+    WindTunnel,
+    /// A stream of queueing boids squeezing through a gap in a wall
+    Doorway,
+    /// A flock shepherded around a looping spline corridor, coasting at
+    /// cruise speed under linear drag
+    Commute,
+    /// A dense flock held together by springs to its nearest neighbours
+    Lattice,
+    /// A scattered flock recruiting each other to a few small food patches
+    Meadow,
+    /// A flock that settles onto two roosts, flushed from the larger one
+    /// after half a minute
+    Roost,
+    /// A loose swarm turning into a tight flock as alignment and cohesion
+    /// ramp up from zero and separation eases off over a minute
+    Regimes,
+    /// A flock drifting downstream in a world that wraps left to right but
+    /// is walled at the top and bottom
+    River,
+    /// A flock in a periodic hexagonal world
+    Honeycomb,
+    /// Boids drifting in from every edge of the screen, like a screensaver
+    Drift,
+    /// A flock over an empty flow field to paint with the flow brush, saved
+    /// to and loaded from `assets/flow/sculpt.txt`
+    Sculpt,
+    /// A flock hunted by three predators that catch the boids they reach,
+    /// lighting up as they spawn and are caught
+    Hunt,
+    /// Two flocks of different speeds meeting head on, passing through each
+    /// other without mixing
+    Rivals,
+    /// A loose flock whose centre is tagged `core`, to follow how the inner
+    /// boids spread through the rest
+    Core,
+    /// Prey trickling in from the edges for half a minute before the first
+    /// of three predators shows up
+    Ecology,
+    /// A few dozen boids scattered far apart, meandering on their own until
+    /// they run into each other
+    Loners,
+    /// A flock perceiving three times as far ahead as to the sides,
+    /// stringing out into lines
+    Convoy,
+    /// A flock swelling from a hundred to five thousand boids over two
+    /// minutes, spawned all over the world
+    Swell,
+    /// The whole flock spawned at once, scattered all over the world
+    Scatter,
+    /// A flock feeling its way with whiskers down a winding channel between
+    /// two walls of obstacles, in a world walled off at the edges
+    Canyon,
+    /// Prey wrapping around the world alongside leaves that blow out of it
+    /// and back in from the left, chased by predators bouncing off the
+    /// edges
+    Borders,
+    /// A fast, dense flock keeping apart by predicted closest approach,
+    /// turning early instead of brushing past each other
+    Rush,
+    /// Four flocks flying in from each side, coloured by heading so the
+    /// directions stay visible as they merge
+    Compass,
+    /// Boids fixed on a lattice, only turning towards their four nearest
+    /// neighbours' headings like spins in the XY model, coloured by heading
+    /// so vortices show up as colour wheels
+    Spins,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 28] = [
+        Scenario::DenseBlob,
+        Scenario::CollidingFlocks,
+        Scenario::PredatorGauntlet,
+        Scenario::ObstacleMaze,
+        Scenario::WindTunnel,
+        Scenario::Doorway,
+        Scenario::Commute,
+        Scenario::Lattice,
+        Scenario::Meadow,
+        Scenario::Roost,
+        Scenario::Regimes,
+        Scenario::River,
+        Scenario::Honeycomb,
+        Scenario::Drift,
+        Scenario::Sculpt,
+        Scenario::Hunt,
+        Scenario::Rivals,
+        Scenario::Core,
+        Scenario::Ecology,
+        Scenario::Loners,
+        Scenario::Convoy,
+        Scenario::Swell,
+        Scenario::Scatter,
+        Scenario::Canyon,
+        Scenario::Borders,
+        Scenario::Rush,
+        Scenario::Compass,
+        Scenario::Spins,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scenario::DenseBlob => "dense-blob",
+            Scenario::CollidingFlocks => "colliding-flocks",
+            Scenario::PredatorGauntlet => "predator-gauntlet",
+            Scenario::ObstacleMaze => "obstacle-maze",
+            Scenario::WindTunnel => "wind-tunnel",
+            Scenario::Doorway => "doorway",
+            Scenario::Commute => "commute",
+            Scenario::Lattice => "lattice",
+            Scenario::Meadow => "meadow",
+            Scenario::Roost => "roost",
+            Scenario::Regimes => "regimes",
+            Scenario::River => "river",
+            Scenario::Honeycomb => "honeycomb",
+            Scenario::Drift => "drift",
+            Scenario::Sculpt => "sculpt",
+            Scenario::Hunt => "hunt",
+            Scenario::Rivals => "rivals",
+            Scenario::Core => "core",
+            Scenario::Ecology => "ecology",
+            Scenario::Loners => "loners",
+            Scenario::Convoy => "convoy",
+            Scenario::Swell => "swell",
+            Scenario::Scatter => "scatter",
+            Scenario::Canyon => "canyon",
+            Scenario::Borders => "borders",
+            Scenario::Rush => "rush",
+            Scenario::Compass => "compass",
+            Scenario::Spins => "spins",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Scenario> {
+        Scenario::ALL.into_iter().find(|scenario| scenario.name() == name)
+    }
+
+    /// Configures `plugin` with this scenario's boids and obstacles, placed
+    /// using the plugin's seed.
+    pub fn apply(self, plugin: BoidsPlugin) -> BoidsPlugin {
+        let mut rng = StdRng::seed_from_u64(plugin.seed());
+        match self {
+            Scenario::DenseBlob => {
+                plugin
+                    .with_initial_boids(disc(&mut rng, 800, Vec2::ZERO, 120., None))
+                    .with_spawn_fade(0.6, 1.5)
+            }
+            Scenario::CollidingFlocks => {
+                let mut boids = disc(&mut rng, 300, Vec2::new(-450., 0.), 100., Some(0.));
+                boids.extend(disc(&mut rng, 300, Vec2::new(450., 0.), 100., Some(TAU / 2.)));
+                plugin.with_initial_boids(boids)
+            }
+            Scenario::PredatorGauntlet => {
+                let mut plugin = plugin
+                    .with_initial_boids(disc(&mut rng, 300, Vec2::new(-500., 0.), 80., Some(0.)));
+                for i in 0..8 {
+                    let x = -300. + i as f32 * 90.;
+                    plugin = plugin
+                        .with_obstacle(Vec2::new(x, 160.), 30.)
+                        .with_obstacle(Vec2::new(x + 45., -160.), 30.);
+                }
+                // staggered down the corridor, so the flock runs past one
+                // after another
+                let predators = (0..3)
+                    .map(|i| {
+                        let side = if i % 2 == 0 { 1. } else { -1. };
+                        (Vec2::new(-120. + i as f32 * 180., side * 80.), Vec2::ZERO)
+                    })
+                    .collect();
+                plugin.with_initial_predators(predators, true)
+            }
+            Scenario::ObstacleMaze => {
+                let mut plugin = plugin
+                    .with_initial_boids(disc(&mut rng, 400, Vec2::ZERO, 300., None))
+                    .with_species(SpeciesDesc { mixing: ForceMixing::WeightedSum, ..Default::default() })
+                    .with_species(SpeciesDesc { mixing: ForceMixing::Prioritized, ..Default::default() });
+                for row in -2..=2 {
+                    for column in -4..=4 {
+                        let offset = if row % 2 == 0 { 0. } else { 60. };
+                        let position = Vec2::new(column as f32 * 120. + offset, row as f32 * 120.);
+                        plugin = plugin.with_obstacle(position, 22.);
+                    }
+                }
+                plugin
+            }
+            Scenario::WindTunnel => plugin
+                .with_edge_emitter(Edge::Left, 40.)
+                .with_obstacle(Vec2::new(-100., 60.), 50.)
+                .with_obstacle(Vec2::new(150., -80.), 70.),
+            Scenario::Doorway => {
+                let mut plugin = plugin.with_edge_emitter(Edge::Left, 60.).with_queueing(true);
+                // a wall of overlapping obstacles with a gap in the middle
+                for i in -10..=10 {
+                    let y = i as f32 * 36.;
+                    if y.abs() > 50. {
+                        plugin = plugin.with_obstacle(Vec2::new(0., y), 20.);
+                    }
+                }
+                plugin
+            }
+            Scenario::Commute => {
+                let loop_points: Vec<Vec2> = (0..=12)
+                    .map(|i| Vec2::from_angle(i as f32 * TAU / 12.) * Vec2::new(450., 250.))
+                    .collect();
+                plugin
+                    .with_initial_boids(disc(&mut rng, 400, Vec2::ZERO, 300., None))
+                    .with_corridor(Corridor::spline(&loop_points, 120., 0.8))
+                    .with_drag(Drag::linear(1.5, CRUISE_SPEED))
+            }
+            Scenario::Lattice => plugin
+                .with_initial_boids(disc(&mut rng, 300, Vec2::ZERO, 150., Some(0.)))
+                .with_spring_cohesion(6, 40.)
+                // closer than the rest length, so separation doesn't fight the springs
+                .with_settings(BoidsSettings { desired_separation: 30., ..Default::default() }),
+            Scenario::Meadow => plugin
+                .with_initial_boids(disc(&mut rng, 400, Vec2::ZERO, 350., None))
+                .with_food_source(Vec2::new(-450., 220.), 30.)
+                .with_food_source(Vec2::new(400., -180.), 30.)
+                .with_food_source(Vec2::new(120., 260.), 20.),
+            Scenario::Roost => plugin
+                .with_initial_boids(disc(&mut rng, 600, Vec2::ZERO, 300., None))
+                .with_roost(Vec2::new(-300., 100.), 90.)
+                .with_roost(Vec2::new(250., -120.), 120.)
+                .with_disturbance_at(30., Disturbance {
+                    position: Vec2::new(250., -120.),
+                    radius: 200.,
+                    strength: 3.,
+                }),
+            Scenario::Regimes => plugin
+                .with_initial_boids(disc(&mut rng, 500, Vec2::ZERO, 350., None))
+                .with_keyframes(KeyframeTrack::new(Parameter::Separation).key(0., 2.).key(60., 1.2))
+                .with_keyframes(KeyframeTrack::new(Parameter::Alignment).key(0., 0.).key(30., 1.))
+                .with_keyframes(KeyframeTrack::new(Parameter::Cohesion).key(0., 0.).key(60., 2.)),
+            Scenario::River => plugin
+                .with_initial_boids(disc(&mut rng, 400, Vec2::ZERO, 250., None))
+                .with_boundary_mode(BoundaryMode::cylinder())
+                .with_drag(Drag::quadratic(0.01, CRUISE_SPEED))
+                .with_corridor(Corridor::new(vec![Vec2::new(-700., 0.), Vec2::new(700., 0.)], 800., 0.3)),
+            Scenario::Honeycomb => plugin
+                .with_initial_boids(disc(&mut rng, 500, Vec2::ZERO, 250., None))
+                .with_boundary_mode(BoundaryMode::Hexagonal),
+            Scenario::Drift => plugin.with_spawn_area(SpawnArea::Edges),
+            Scenario::Sculpt => plugin.with_flow_field("assets/flow/sculpt.txt"),
+            Scenario::Hunt => plugin.with_predators(3, true).with_event_flashes(true),
+            Scenario::Rivals => {
+                let mut boids = disc(&mut rng, 300, Vec2::new(-450., 0.), 100., Some(0.));
+                boids.extend(disc(&mut rng, 300, Vec2::new(450., 0.), 100., Some(TAU / 2.)));
+                plugin.with_initial_boids(boids).with_flocks(vec![
+                    FlockDesc {
+                        count: 300,
+                        species: SpeciesDesc { color: Color::srgb(0.3, 0.7, 1.), ..Default::default() },
+                    },
+                    FlockDesc {
+                        count: 300,
+                        species: SpeciesDesc { color: Color::srgb(1., 0.5, 0.3), speed: 1.2, ..Default::default() },
+                    },
+                ])
+            }
+            Scenario::Core => plugin
+                .with_initial_boids(disc(&mut rng, 500, Vec2::ZERO, 300., None))
+                .with_tag_region("core", Vec2::ZERO, 80.),
+            Scenario::Ecology => plugin
+                .with_spawn_schedule(SpawnSchedule {
+                    spawnee: Spawnee::Species(0),
+                    initial: 50,
+                    rate: 20.,
+                    start: 0.,
+                    total: 600,
+                    area: SpawnArea::Edges,
+                })
+                .with_spawn_schedule(SpawnSchedule {
+                    spawnee: Spawnee::Predators,
+                    initial: 0,
+                    rate: 0.1,
+                    start: 30.,
+                    total: 3,
+                    area: SpawnArea::Edges,
+                }),
+            Scenario::Loners => plugin
+                .with_initial_boids(disc(&mut rng, 40, Vec2::ZERO, 500., None))
+                .with_wander(1.),
+            Scenario::Convoy => plugin
+                .with_initial_boids(disc(&mut rng, 300, Vec2::ZERO, 250., None))
+                .with_perception(PerceptionMetric::Elliptical { aspect: 3. }),
+            Scenario::Swell => plugin
+                .with_spawn_area(SpawnArea::Anywhere)
+                .with_keyframes(KeyframeTrack::new(Parameter::BoidCount).key(0., 100.).key(120., 5000.)),
+            Scenario::Scatter => plugin
+                .with_spawn_area(SpawnArea::Anywhere)
+                .with_spawn_strategy(SpawnStrategy::AllAtOnce),
+            Scenario::Canyon => {
+                let mut plugin = plugin
+                    .with_initial_boids(disc(&mut rng, 250, Vec2::new(-450., 0.), 80., Some(0.)))
+                    .with_boundary_mode(BoundaryMode::Bounce)
+                    .with_whiskers(1.5);
+                for i in 0..16 {
+                    let x = -375. + i as f32 * 50.;
+                    let y = (i as f32 * 0.5).sin() * 80.;
+                    plugin = plugin
+                        .with_obstacle(Vec2::new(x, y + 130.), 28.)
+                        .with_obstacle(Vec2::new(x, y - 130.), 28.);
+                }
+                plugin
+            }
+            Scenario::Borders => plugin
+                .with_species(SpeciesDesc { color: Color::srgb(0.4, 0.8, 1.), ..Default::default() })
+                .with_species(SpeciesDesc {
+                    color: Color::srgb(0.9, 0.6, 0.2),
+                    speed: 0.7,
+                    boundary: Some(BoundaryMode::Despawn { respawn: Some(Edge::Left) }),
+                    ..Default::default()
+                })
+                .with_predators(2, false)
+                .with_predator_boundary_mode(BoundaryMode::Bounce),
+            Scenario::Rush => plugin
+                .with_initial_boids(disc(&mut rng, 800, Vec2::ZERO, 250., None))
+                .with_settings(BoidsSettings { max_speed: 450., max_force: 500., ..Default::default() })
+                .with_predictive_separation(0.5),
+            Scenario::Compass => {
+                let mut boids = Vec::new();
+                for side in 0..4 {
+                    let angle = side as f32 * TAU / 4.;
+                    let center = Vec2::from_angle(angle) * -400.;
+                    boids.extend(disc(&mut rng, 200, center, 90., Some(angle)));
+                }
+                plugin.with_initial_boids(boids).with_shading(BoidShading::Heading)
+            }
+            Scenario::Spins => plugin
+                .with_spin_lattice(SpinLattice::new(20.))
+                // reaching the four nearest lattice points but not the diagonals
+                .with_settings(BoidsSettings { neighbour_radius: 25., desired_separation: 10., ..Default::default() })
+                .with_shading(BoidShading::Heading),
+        }
+    }
+}
+
+/// `count` boids uniformly spread over a disc, all flying along `heading`
+/// or in random directions when it is `None`.
+fn disc(rng: &mut StdRng, count: u32, center: Vec2, radius: f32, heading: Option<f32>) -> Vec<(Vec2, Vec2)> {
+    (0..count)
+        .map(|_| {
+            let position = center
+                + Vec2::from_angle(rng.gen_range(0.0..TAU)) * radius * rng.gen::<f32>().sqrt();
+            let heading = heading.unwrap_or_else(|| rng.gen_range(0.0..TAU));
+            (position, Vec2::from_angle(heading) * CRUISE_SPEED)
+        })
+        .collect()
+}
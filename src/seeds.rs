@@ -0,0 +1,265 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use bevy::{
+    prelude::*,
+    render::view::screenshot::ScreenshotManager,
+    window::PrimaryWindow,
+};
+
+// relative to the working directory, inside the asset folder so the
+// thumbnails can be loaded back as images
+const SEEDS_DIR: &str = "assets/seeds";
+const RECENT_FILE: &str = "recent.txt";
+const BOOKMARKS_FILE: &str = "bookmarks.txt";
+const MAX_RECENT: usize = 12;
+// frame the thumbnail is captured at, once the flock has formed
+const THUMBNAIL_TICK: u32 = 300;
+const THUMBNAIL_SIZE: Vec2 = Vec2::new(96., 54.);
+
+/// Remembers the seeds runs were started with, captures a thumbnail of each
+/// and lists them in a panel (F5) together with bookmarked seeds (B
+/// bookmarks the current one). Clicking an entry restarts the app with that
+/// seed.
+pub struct SeedPanelPlugin {
+    pub seed: u64,
+}
+
+#[derive(Resource)]
+struct SeedLists {
+    current: u64,
+    recent: Vec<u64>,
+    bookmarks: Vec<u64>,
+    visible: bool,
+    /// The panel needs rebuilding
+    dirty: bool,
+}
+
+impl SeedLists {
+    fn load(current: u64) -> Self {
+        SeedLists {
+            current,
+            recent: read_seeds(&seeds_path(RECENT_FILE)),
+            bookmarks: read_seeds(&seeds_path(BOOKMARKS_FILE)),
+            visible: false,
+            dirty: true,
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        fs::create_dir_all(SEEDS_DIR)?;
+        write_seeds(&seeds_path(RECENT_FILE), &self.recent)?;
+        write_seeds(&seeds_path(BOOKMARKS_FILE), &self.bookmarks)
+    }
+}
+
+#[derive(Component)]
+struct SeedPanel;
+
+#[derive(Component)]
+struct SeedButton(u64);
+
+fn seeds_path(file: &str) -> PathBuf {
+    Path::new(SEEDS_DIR).join(file)
+}
+
+fn thumbnail_path(seed: u64) -> PathBuf {
+    seeds_path(&format!("{seed}.png"))
+}
+
+fn read_seeds(path: &Path) -> Vec<u64> {
+    fs::read_to_string(path)
+        .map(|text| text.lines().filter_map(|line| line.trim().parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+fn write_seeds(path: &Path, seeds: &[u64]) -> io::Result<()> {
+    let lines: Vec<String> = seeds.iter().map(u64::to_string).collect();
+    fs::write(path, lines.join("\n"))
+}
+
+impl Plugin for SeedPanelPlugin {
+    fn build(&self, app: &mut App) {
+        let mut lists = SeedLists::load(self.seed);
+        lists.recent.retain(|&seed| seed != self.seed);
+        lists.recent.insert(0, self.seed);
+        lists.recent.truncate(MAX_RECENT);
+        if let Err(err) = lists.save() {
+            error!("failed to save seed history: {err}");
+        }
+        app.insert_resource(lists)
+            .add_systems(Startup, setup_panel)
+            .add_systems(Update, (
+                capture_thumbnail,
+                seed_panel_input,
+                rebuild_panel,
+                relaunch_on_click,
+            ).chain());
+    }
+}
+
+fn setup_panel(mut commands: Commands) {
+    commands.spawn((
+        SeedPanel,
+        NodeBundle {
+            background_color: BackgroundColor(Color::BLACK.with_alpha(0.7)),
+            visibility: Visibility::Hidden,
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(1.),
+                top: Val::Percent(1.),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.),
+                padding: UiRect::all(Val::Px(6.)),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn capture_thumbnail(
+    mut frames: Local<u32>,
+    lists: Res<SeedLists>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+) {
+    *frames += 1;
+    if *frames != THUMBNAIL_TICK {
+        return;
+    }
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    if let Err(err) = fs::create_dir_all(SEEDS_DIR) {
+        error!("failed to create {SEEDS_DIR}: {err}");
+        return;
+    }
+    let path = thumbnail_path(lists.current);
+    if let Err(err) = screenshots.save_screenshot_to_disk(window, &path) {
+        error!("failed to capture thumbnail {}: {err}", path.display());
+    }
+}
+
+fn seed_panel_input(keys: Res<ButtonInput<KeyCode>>, mut lists: ResMut<SeedLists>) {
+    if keys.just_pressed(KeyCode::F5) {
+        lists.visible = !lists.visible;
+        lists.dirty = true;
+    }
+    if keys.just_pressed(KeyCode::KeyB) {
+        let current = lists.current;
+        if lists.bookmarks.contains(&current) {
+            lists.bookmarks.retain(|&seed| seed != current);
+            info!("removed bookmark for seed {current}");
+        } else {
+            lists.bookmarks.push(current);
+            info!("bookmarked seed {current}");
+        }
+        if let Err(err) = lists.save() {
+            error!("failed to save seed bookmarks: {err}");
+        }
+        lists.dirty = true;
+    }
+}
+
+fn rebuild_panel(
+    mut commands: Commands,
+    mut lists: ResMut<SeedLists>,
+    mut panel: Query<(Entity, &mut Visibility), With<SeedPanel>>,
+    assets: Res<AssetServer>,
+) {
+    if !lists.dirty {
+        return;
+    }
+    lists.dirty = false;
+    let Ok((panel, mut visibility)) = panel.get_single_mut() else {
+        return;
+    };
+    *visibility = if lists.visible { Visibility::Visible } else { Visibility::Hidden };
+    commands.entity(panel).despawn_descendants();
+    if !lists.visible {
+        return;
+    }
+
+    // bookmarks first, then recent seeds that are not bookmarked
+    let recent = lists.recent.iter().filter(|seed| !lists.bookmarks.contains(seed));
+    let seeds: Vec<u64> = lists.bookmarks.iter().chain(recent).copied().collect();
+    commands.entity(panel).with_children(|panel| {
+        for seed in seeds {
+            let mut label = seed.to_string();
+            if lists.bookmarks.contains(&seed) {
+                label.push_str(" *");
+            }
+            if seed == lists.current {
+                label.push_str(" (current)");
+            }
+            panel
+                .spawn((
+                    SeedButton(seed),
+                    ButtonBundle {
+                        background_color: BackgroundColor(Color::srgba(1., 1., 1., 0.05)),
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(6.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|row| {
+                    let size = Style {
+                        width: Val::Px(THUMBNAIL_SIZE.x),
+                        height: Val::Px(THUMBNAIL_SIZE.y),
+                        ..default()
+                    };
+                    if thumbnail_path(seed).exists() {
+                        row.spawn(ImageBundle {
+                            image: UiImage::new(assets.load(format!("seeds/{seed}.png"))),
+                            style: size,
+                            ..default()
+                        });
+                    } else {
+                        row.spawn(NodeBundle {
+                            background_color: BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                            style: size,
+                            ..default()
+                        });
+                    }
+                    row.spawn(TextBundle::from_section(label, TextStyle {
+                        font_size: 14.,
+                        color: Color::WHITE,
+                        ..default()
+                    }));
+                });
+        }
+    });
+}
+
+/// Restarts the app with the same arguments and the clicked seed.
+fn relaunch_on_click(
+    buttons: Query<(&Interaction, &SeedButton), Changed<Interaction>>,
+    lists: Res<SeedLists>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed || button.0 == lists.current {
+            continue;
+        }
+        let mut args: Vec<String> = std::env::args().skip(1).collect();
+        if let Some(index) = args.iter().position(|arg| arg == "--seed") {
+            args.drain(index..(index + 2).min(args.len()));
+        }
+        args.extend(["--seed".to_string(), button.0.to_string()]);
+        let launched = std::env::current_exe().and_then(|exe| Command::new(exe).args(&args).spawn());
+        match launched {
+            Ok(_) => {
+                info!("relaunching with seed {}", button.0);
+                exit.send(AppExit::Success);
+            }
+            Err(err) => error!("failed to relaunch with seed {}: {err}", button.0),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+/// Schedule run exactly once when the app is about to exit, whether from
+/// closing the window or an explicit [`AppExit`]. Recorders, frame capture
+/// and autosave systems add themselves here to flush pending data.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shutdown;
+
+#[derive(Resource)]
+struct ShutdownComplete;
+
+pub struct ShutdownPlugin;
+
+impl Plugin for ShutdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_schedule(Shutdown)
+            .add_systems(Last, run_shutdown_schedule);
+    }
+}
+
+fn run_shutdown_schedule(world: &mut World) {
+    let exiting = world
+        .get_resource::<Events<AppExit>>()
+        .is_some_and(|events| !events.is_empty());
+    if exiting && !world.contains_resource::<ShutdownComplete>() {
+        info!("flushing before exit");
+        world.run_schedule(Shutdown);
+        world.insert_resource(ShutdownComplete);
+    }
+}
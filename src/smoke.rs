@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use boids::FlockStats;
+
+// loose sanity bounds for a flock that is alive and moving
+const MIN_MEAN_SPEED: f32 = 10.;
+const MAX_MEAN_SPEED: f32 = 600.;
+
+/// Runs the simulation for a fixed number of ticks, checking every tick that
+/// no boid has a non-finite state or has escaped the world, and at the end
+/// that the flock statistics are in a plausible range. Exits with an error
+/// code if any check failed.
+pub struct SmokeTestPlugin {
+    pub ticks: u32,
+}
+
+#[derive(Resource)]
+struct SmokeTest {
+    ticks: u32,
+    tick: u32,
+    failures: Vec<String>,
+}
+
+impl SmokeTest {
+    fn fail(&mut self, failure: String) {
+        error!("smoke test: {failure}");
+        self.failures.push(failure);
+    }
+}
+
+impl Plugin for SmokeTestPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SmokeTest {
+            ticks: self.ticks,
+            tick: 0,
+            failures: Vec::new(),
+        })
+        // stats are written in Last, so each frame checks the one before it
+        .add_systems(First, check_tick);
+    }
+}
+
+fn check_tick(mut smoke: ResMut<SmokeTest>, stats: Res<FlockStats>, mut exit: EventWriter<AppExit>) {
+    if smoke.tick > 0 && smoke.tick <= smoke.ticks {
+        let tick = smoke.tick;
+        if stats.non_finite > 0 {
+            smoke.fail(format!("tick {tick}: {} boids with non-finite state", stats.non_finite));
+        }
+        if stats.out_of_bounds > 0 {
+            smoke.fail(format!("tick {tick}: {} boids out of bounds", stats.out_of_bounds));
+        }
+    }
+    if smoke.tick < smoke.ticks {
+        smoke.tick += 1;
+        return;
+    }
+
+    if stats.boids == 0 {
+        smoke.fail("no boids after the final tick".to_string());
+    }
+    if !(MIN_MEAN_SPEED..=MAX_MEAN_SPEED).contains(&stats.mean_speed) {
+        smoke.fail(format!(
+            "mean speed {:.1} outside {MIN_MEAN_SPEED}..={MAX_MEAN_SPEED}",
+            stats.mean_speed,
+        ));
+    }
+    if !(0f32..=1.0001).contains(&stats.polarization) {
+        smoke.fail(format!("polarization {:.3} outside 0..=1", stats.polarization));
+    }
+
+    if smoke.failures.is_empty() {
+        info!(
+            "smoke test passed after {} ticks: {} boids, mean speed {:.1}, polarization {:.3}",
+            smoke.ticks, stats.boids, stats.mean_speed, stats.polarization,
+        );
+        exit.send(AppExit::Success);
+    } else {
+        error!("smoke test failed with {} problems", smoke.failures.len());
+        exit.send(AppExit::error());
+    }
+}
@@ -0,0 +1,167 @@
+use std::time::Duration;
+use bevy::prelude::*;
+
+use boids::{BoidMemoryUsage, FlockStats};
+
+// real seconds between invariant checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// how far the memory per boid may grow past the first check's
+const MAX_MEMORY_GROWTH: f32 = 1.5;
+
+/// Runs the simulation for `hours` of real time, checking every frame that
+/// no boid has a non-finite state or has escaped the world, and every
+/// minute that the boid list and count match the world and the memory used
+/// per boid hasn't grown. Violations are logged as they are found and the run carries
+/// on; it exits with an error code at the end if there were any.
+///
+/// Memory is taken from [`BoidMemoryUsage`], so it covers boid components
+/// and the neighbour lookups. With the `jemalloc` feature the bytes
+/// allocated and resident in the whole heap mustn't grow either.
+pub struct SoakPlugin {
+    pub hours: f32,
+}
+
+#[derive(Resource)]
+struct Soak {
+    duration: Duration,
+    timer: Timer,
+    /// Frames since the last check with non-finite or escaped boids
+    non_finite_frames: u32,
+    out_of_bounds_frames: u32,
+    /// Bytes per boid at the first check
+    baseline: Option<f32>,
+    /// Bytes allocated and resident at the first check
+    #[cfg(feature = "jemalloc")]
+    heap_baseline: Option<(usize, usize)>,
+    violations: u32,
+}
+
+impl Soak {
+    fn violate(&mut self, elapsed: Duration, violation: String) {
+        error!("soak test at {}: {violation}", format_elapsed(elapsed));
+        self.violations += 1;
+    }
+}
+
+impl Plugin for SoakPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Soak {
+            duration: Duration::from_secs_f32(self.hours * 3600.),
+            timer: Timer::new(CHECK_INTERVAL, TimerMode::Repeating),
+            non_finite_frames: 0,
+            out_of_bounds_frames: 0,
+            baseline: None,
+            #[cfg(feature = "jemalloc")]
+            heap_baseline: None,
+            violations: 0,
+        })
+        // stats are written in Last, so each frame checks the one before it
+        .add_systems(First, check_invariants);
+    }
+}
+
+fn check_invariants(
+    mut soak: ResMut<Soak>,
+    time: Res<Time<Real>>,
+    stats: Res<FlockStats>,
+    memory: Res<BoidMemoryUsage>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if stats.non_finite > 0 {
+        soak.non_finite_frames += 1;
+    }
+    if stats.out_of_bounds > 0 {
+        soak.out_of_bounds_frames += 1;
+    }
+    let elapsed = time.elapsed();
+    let done = elapsed >= soak.duration;
+    if !soak.timer.tick(time.delta()).just_finished() && !done {
+        return;
+    }
+
+    if soak.non_finite_frames > 0 {
+        let frames = std::mem::take(&mut soak.non_finite_frames);
+        soak.violate(elapsed, format!("{frames} frames with non-finite boids, {} in the last", stats.non_finite));
+    }
+    if soak.out_of_bounds_frames > 0 {
+        let frames = std::mem::take(&mut soak.out_of_bounds_frames);
+        soak.violate(elapsed, format!("{frames} frames with boids out of bounds, {} in the last", stats.out_of_bounds));
+    }
+    if memory.unindexed > 0 {
+        soak.violate(elapsed, format!("{} boids missing from the boid list", memory.unindexed));
+    }
+    if memory.stale_entries > 0 {
+        soak.violate(elapsed, format!("{} stale entries in the boid list", memory.stale_entries));
+    }
+    if stats.boids > memory.boids {
+        soak.violate(elapsed, format!("{} boids in the stats but {} in the world", stats.boids, memory.boids));
+    }
+    // nothing in the soak test spawns boids around the spawner
+    if memory.counted != memory.listed + memory.perched {
+        soak.violate(
+            elapsed,
+            format!("{} boids counted but {} listed and {} perched", memory.counted, memory.listed, memory.perched),
+        );
+    }
+    if memory.boids > 0 {
+        let per_boid = (memory.component_bytes + memory.index_bytes) as f32 / memory.boids as f32;
+        match soak.baseline {
+            None => soak.baseline = Some(per_boid),
+            Some(baseline) if per_boid > baseline * MAX_MEMORY_GROWTH => {
+                soak.violate(elapsed, format!("{per_boid:.0} bytes per boid, up from {baseline:.0}"));
+            }
+            Some(_) => {}
+        }
+    }
+    #[cfg(feature = "jemalloc")]
+    check_heap(&mut soak, elapsed);
+    info!(
+        "soak test at {}: {} boids, {:.1} KiB, {} violations so far",
+        format_elapsed(elapsed),
+        memory.boids,
+        (memory.component_bytes + memory.index_bytes) as f32 / 1024.,
+        soak.violations,
+    );
+
+    if !done {
+        return;
+    }
+    if soak.violations == 0 {
+        info!("soak test passed after {}", format_elapsed(elapsed));
+        exit.send(AppExit::Success);
+    } else {
+        error!("soak test failed with {} violations", soak.violations);
+        exit.send(AppExit::error());
+    }
+}
+
+/// Checks the bytes jemalloc has allocated and resident against the first
+/// check's.
+#[cfg(feature = "jemalloc")]
+fn check_heap(soak: &mut Soak, elapsed: Duration) {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    // the stats are cached until the epoch moves on
+    let heap = epoch::advance()
+        .and_then(|_| Ok((stats::allocated::read()?, stats::resident::read()?)));
+    let Ok((allocated, resident)) = heap else {
+        soak.violate(elapsed, "jemalloc stats unavailable".into());
+        return;
+    };
+    let Some((base_allocated, base_resident)) = soak.heap_baseline else {
+        soak.heap_baseline = Some((allocated, resident));
+        return;
+    };
+    let mib = |bytes: usize| bytes as f32 / (1024. * 1024.);
+    if allocated as f32 > base_allocated as f32 * MAX_MEMORY_GROWTH {
+        soak.violate(elapsed, format!("{:.1} MiB allocated, up from {:.1}", mib(allocated), mib(base_allocated)));
+    }
+    if resident as f32 > base_resident as f32 * MAX_MEMORY_GROWTH {
+        soak.violate(elapsed, format!("{:.1} MiB resident, up from {:.1}", mib(resident), mib(base_resident)));
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
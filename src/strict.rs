@@ -0,0 +1,16 @@
+/// Checks a runtime invariant without taking the app down: logs a warning
+/// by default, and panics when built with the `strict` feature so broken
+/// invariants are caught during development.
+macro_rules! soft_assert {
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            if cfg!(feature = "strict") {
+                panic!($($arg)+);
+            } else {
+                bevy::log::warn!($($arg)+);
+            }
+        }
+    };
+}
+
+pub(crate) use soft_assert;
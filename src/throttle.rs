@@ -0,0 +1,106 @@
+use std::{collections::VecDeque, time::Duration};
+use bevy::{
+    input::mouse::MouseWheel,
+    prelude::*,
+    window::{CursorMoved, WindowFocused},
+    winit::{UpdateMode, WinitSettings},
+};
+
+use boids::FlockStats;
+
+// update rate while throttled
+const IDLE_WAIT: Duration = Duration::from_millis(100);
+// seconds of statistics compared to decide the flock has settled
+const STEADY_WINDOW: usize = 10;
+const STEADY_SPEED_TOLERANCE: f32 = 0.02;
+const STEADY_POLARIZATION_TOLERANCE: f32 = 0.02;
+
+/// Drops to a low update rate while the window is unfocused or the flock
+/// statistics have stopped changing, and returns to full rate on focus or
+/// any input.
+pub struct IdleThrottlePlugin;
+
+#[derive(Resource)]
+struct SteadyState {
+    samples: VecDeque<FlockStats>,
+    timer: Timer,
+    throttled: bool,
+}
+
+impl SteadyState {
+    fn is_steady(&self) -> bool {
+        if self.samples.len() < STEADY_WINDOW {
+            return false;
+        }
+        let range = |value: fn(&FlockStats) -> f32| {
+            let (min, max) = self.samples
+                .iter()
+                .map(value)
+                .fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v), max.max(v)));
+            max - min
+        };
+        let first = &self.samples[0];
+        let mean_speed = first.mean_speed.max(1.);
+        self.samples.iter().all(|stats| stats.boids == first.boids)
+            && range(|stats| stats.mean_speed) < mean_speed * STEADY_SPEED_TOLERANCE
+            && range(|stats| stats.polarization) < STEADY_POLARIZATION_TOLERANCE
+    }
+}
+
+impl Plugin for IdleThrottlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WinitSettings {
+            focused_mode: UpdateMode::Continuous,
+            unfocused_mode: UpdateMode::reactive_low_power(IDLE_WAIT),
+        })
+        .insert_resource(SteadyState {
+            samples: VecDeque::with_capacity(STEADY_WINDOW),
+            timer: Timer::from_seconds(1., TimerMode::Repeating),
+            throttled: false,
+        })
+        .add_systems(Update, (sample_steady_state, wake_on_input).chain());
+    }
+}
+
+fn sample_steady_state(
+    mut steady: ResMut<SteadyState>,
+    mut settings: ResMut<WinitSettings>,
+    stats: Res<FlockStats>,
+    time: Res<Time<Real>>,
+) {
+    if !steady.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if steady.samples.len() == STEADY_WINDOW {
+        steady.samples.pop_front();
+    }
+    steady.samples.push_back(*stats);
+    if !steady.throttled && steady.is_steady() {
+        info!("flock reached a steady state, throttling updates");
+        steady.throttled = true;
+        settings.focused_mode = UpdateMode::reactive_low_power(IDLE_WAIT);
+    }
+}
+
+fn wake_on_input(
+    mut steady: ResMut<SteadyState>,
+    mut settings: ResMut<WinitSettings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut cursor: EventReader<CursorMoved>,
+    mut wheel: EventReader<MouseWheel>,
+    mut focus: EventReader<WindowFocused>,
+) {
+    let input = keys.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some()
+        || cursor.read().count() > 0
+        || wheel.read().count() > 0
+        || focus.read().any(|event| event.focused);
+    if input && steady.throttled {
+        info!("input received, resuming full update rate");
+        steady.throttled = false;
+        // the flock has to settle again before throttling
+        steady.samples.clear();
+        settings.focused_mode = UpdateMode::Continuous;
+    }
+}
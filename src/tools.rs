@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+#[cfg(feature = "render")]
+use bevy::window::PrimaryWindow;
+
+/// Interactive tool currently bound to the mouse buttons.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ActiveTool {
+    #[default]
+    None,
+    /// Click to move the goal of the selected squad, right click to clear it
+    SquadGoal,
+    /// Hold left mouse to heat the temperature field, right mouse to cool it
+    TemperatureBrush,
+    /// Click a boid to highlight it and chart its state and history, right click to clear the selection
+    Inspect,
+    /// Click to startle nearby boids and flush them from roosts
+    Disturb,
+    /// Drag to paint the drag direction into the flow field, right mouse to erase it
+    FlowBrush,
+    /// Click two points to measure the distance between them, right click to clear
+    Measure,
+    /// Hold left mouse to draw boids to the cursor, right mouse to drive them away
+    Attract,
+    /// Drag a box to save the boids in it as a prefab, right click to stamp the prefab
+    Prefab,
+    /// Drag a box to tag the boids in it, right click to clear every tag
+    Tag,
+    /// Click or drag to spawn boids at the cursor, flying off along the drag
+    SpawnBrush,
+}
+
+impl ActiveTool {
+    /// Switches to `tool`, or back to no tool if it is already active.
+    pub fn toggle(&mut self, tool: ActiveTool) {
+        *self = if *self == tool { ActiveTool::None } else { tool };
+        info!("active tool: {:?}", self);
+    }
+}
+
+/// Cursor position in world coordinates, `None` while the cursor is outside the window.
+#[derive(Resource, Default)]
+pub struct CursorWorld(pub Option<Vec2>);
+
+pub struct ToolsPlugin;
+
+impl Plugin for ToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveTool>()
+            .init_resource::<CursorWorld>();
+
+        #[cfg(feature = "render")]
+        app.add_systems(PreUpdate, update_cursor_world);
+    }
+}
+
+#[cfg(feature = "render")]
+pub(crate) fn update_cursor_world(
+    mut cursor: ResMut<CursorWorld>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    cursor.0 = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|position| {
+            let (camera, transform) = cameras.get_single().ok()?;
+            camera.viewport_to_world_2d(transform, position)
+        });
+}
@@ -0,0 +1,235 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use bevy::prelude::*;
+
+use boids::{Boid, Position};
+use crate::shutdown::Shutdown;
+
+// first line of every recording, bumped when the format changes
+const HEADER: &str = "boids-trajectory 1";
+// ticks summarised per line of a diff report, a second at 60 ticks per second
+const REPORT_TICKS: u32 = 60;
+
+/// Writes every boid's position after each simulation tick to `path`, so
+/// two runs can be compared with `boids diff`. Boids are numbered in the
+/// order they first appear, which matches between runs with the same seed
+/// as long as they spawn the same way.
+pub struct RecorderPlugin {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+struct Recorder {
+    writer: BufWriter<File>,
+    tick: u32,
+    ids: HashMap<Entity, u32>,
+    failed: bool,
+}
+
+impl Recorder {
+    fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+        Ok(Recorder {
+            writer,
+            tick: 0,
+            ids: HashMap::new(),
+            failed: false,
+        })
+    }
+
+    /// One `tick <n> <count>` line followed by an `<id> <x> <y>` line per
+    /// boid, ordered by id.
+    fn write_tick(&mut self, boids: &[(u32, Vec2)]) -> io::Result<()> {
+        writeln!(self.writer, "tick {} {}", self.tick, boids.len())?;
+        for (id, position) in boids {
+            writeln!(self.writer, "{id} {} {}", position.x, position.y)?;
+        }
+        Ok(())
+    }
+}
+
+impl Plugin for RecorderPlugin {
+    fn build(&self, app: &mut App) {
+        match Recorder::create(&self.path) {
+            Ok(recorder) => {
+                info!("recording trajectories to {}", self.path.display());
+                app.insert_resource(recorder)
+                    .add_systems(FixedPostUpdate, record_tick)
+                    .add_systems(Shutdown, flush_recording);
+            }
+            Err(err) => error!("failed to create trajectory recording {}: {err}", self.path.display()),
+        }
+    }
+}
+
+fn record_tick(mut recorder: ResMut<Recorder>, boids: Query<(Entity, &Position), With<Boid>>) {
+    if recorder.failed {
+        return;
+    }
+    let mut new: Vec<Entity> = boids
+        .iter()
+        .map(|(entity, _)| entity)
+        .filter(|entity| !recorder.ids.contains_key(entity))
+        .collect();
+    new.sort();
+    for entity in new {
+        let id = recorder.ids.len() as u32;
+        recorder.ids.insert(entity, id);
+    }
+    let mut positions: Vec<(u32, Vec2)> = boids
+        .iter()
+        .map(|(entity, pos)| (recorder.ids[&entity], pos.0))
+        .collect();
+    positions.sort_by_key(|&(id, _)| id);
+    if let Err(err) = recorder.write_tick(&positions) {
+        error!("failed to write trajectory recording, stopping: {err}");
+        recorder.failed = true;
+    }
+    recorder.tick += 1;
+}
+
+fn flush_recording(mut recorder: ResMut<Recorder>) {
+    if let Err(err) = recorder.writer.flush() {
+        error!("failed to flush trajectory recording: {err}");
+    }
+}
+
+/// Boid positions recorded for one tick, by id.
+struct Tick {
+    number: u32,
+    positions: HashMap<u32, Vec2>,
+}
+
+/// Reads a recording one tick at a time.
+struct TrajectoryReader {
+    lines: io::Lines<BufReader<File>>,
+}
+
+impl TrajectoryReader {
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        if lines.next().transpose()?.as_deref() != Some(HEADER) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a trajectory recording"));
+        }
+        Ok(TrajectoryReader { lines })
+    }
+
+    fn next_tick(&mut self) -> io::Result<Option<Tick>> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed trajectory recording");
+        let Some(line) = self.lines.next().transpose()? else {
+            return Ok(None);
+        };
+        let header: Vec<&str> = line.split_whitespace().collect();
+        let ["tick", number, count] = header[..] else {
+            return Err(invalid());
+        };
+        let number = number.parse().map_err(|_| invalid())?;
+        let count: usize = count.parse().map_err(|_| invalid())?;
+        let mut positions = HashMap::with_capacity(count);
+        for _ in 0..count {
+            let line = self.lines.next().transpose()?.ok_or_else(invalid)?;
+            let values: Vec<&str> = line.split_whitespace().collect();
+            let [id, x, y] = values[..] else {
+                return Err(invalid());
+            };
+            let coordinate = |value: &str| value.parse::<f32>().map_err(|_| invalid());
+            positions.insert(id.parse().map_err(|_| invalid())?, Vec2::new(coordinate(x)?, coordinate(y)?));
+        }
+        Ok(Some(Tick { number, positions }))
+    }
+}
+
+/// Largest distance between the two recordings' positions of a boid, and
+/// which boid it was. Boids in only one of them count as infinitely far.
+fn tick_error(a: &Tick, b: &Tick) -> (f32, u32) {
+    let mut worst = (0., 0);
+    for (&id, &position) in &a.positions {
+        let error = b.positions.get(&id).map_or(f32::INFINITY, |other| position.distance(*other));
+        if error > worst.0 {
+            worst = (error, id);
+        }
+    }
+    if let Some(&id) = b.positions.keys().find(|id| !a.positions.contains_key(id)) {
+        worst = (f32::INFINITY, id);
+    }
+    worst
+}
+
+/// Worst error over an interval of ticks in a diff report.
+struct Interval {
+    start: u32,
+    error: f32,
+    id: u32,
+    tick: u32,
+}
+
+impl Interval {
+    fn print(&self, end: u32) {
+        println!("  ticks {}..={end}: {:.3} (boid {} at tick {})", self.start, self.error, self.id, self.tick);
+    }
+}
+
+/// Compares two recordings tick by tick and prints the first tick where a
+/// boid is more than `tolerance` apart between them, then the largest
+/// error over every following second. Returns whether they diverged.
+pub fn diff(a: &Path, b: &Path, tolerance: f32) -> io::Result<bool> {
+    let mut reader_a = TrajectoryReader::open(a)?;
+    let mut reader_b = TrajectoryReader::open(b)?;
+    let mut diverged = false;
+    let mut interval: Option<Interval> = None;
+    let mut ticks = 0;
+    let mut last = 0;
+    loop {
+        let (tick_a, tick_b) = match (reader_a.next_tick()?, reader_b.next_tick()?) {
+            (Some(tick_a), Some(tick_b)) => (tick_a, tick_b),
+            (None, None) => break,
+            (Some(tick), None) => {
+                println!("{} ends before tick {}", b.display(), tick.number);
+                diverged = true;
+                break;
+            }
+            (None, Some(tick)) => {
+                println!("{} ends before tick {}", a.display(), tick.number);
+                diverged = true;
+                break;
+            }
+        };
+        ticks += 1;
+        last = tick_a.number;
+        let (error, id) = tick_error(&tick_a, &tick_b);
+        if !diverged && error > tolerance {
+            diverged = true;
+            println!("first divergence at tick {}: boid {id}", tick_a.number);
+            for (path, tick) in [(a, &tick_a), (b, &tick_b)] {
+                match tick.positions.get(&id) {
+                    Some(position) => println!("  {}: ({:.3}, {:.3})", path.display(), position.x, position.y),
+                    None => println!("  {}: missing", path.display()),
+                }
+            }
+            println!("largest error per {REPORT_TICKS} ticks:");
+        }
+        if !diverged {
+            continue;
+        }
+        let current = interval.get_or_insert(Interval { start: tick_a.number, error, id, tick: tick_a.number });
+        if error > current.error {
+            *current = Interval { start: current.start, error, id, tick: tick_a.number };
+        }
+        if tick_a.number + 1 - current.start >= REPORT_TICKS {
+            current.print(tick_a.number);
+            interval = None;
+        }
+    }
+    if let Some(interval) = interval {
+        interval.print(last);
+    }
+    if !diverged {
+        println!("no divergence over {ticks} ticks (tolerance {tolerance})");
+    }
+    Ok(diverged)
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+use bevy::{app::RunFixedMainLoop, prelude::*};
+
+// length of a warm-up tick, one frame at 60 fps
+const WARMUP_STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Runs the simulation for `ticks` frames before the first frame, so
+/// the app opens on an already formed flock instead of boids bursting from
+/// their spawn points.
+///
+/// Only the schedules the simulation lives in are run: `First` would
+/// overwrite the fixed step with the wall clock and `PostUpdate` holds the
+/// `--ticks` frame limit, which should count real frames only.
+pub struct WarmupPlugin {
+    pub ticks: u32,
+}
+
+#[derive(Resource)]
+struct Warmup(u32);
+
+impl Plugin for WarmupPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Warmup(self.ticks))
+            .add_systems(PostStartup, warm_up);
+    }
+}
+
+fn warm_up(world: &mut World) {
+    let ticks = world.resource::<Warmup>().0;
+    info!("warming up for {ticks} ticks");
+    for _ in 0..ticks {
+        let mut virtual_time = world.resource_mut::<Time<Virtual>>();
+        virtual_time.advance_by(WARMUP_STEP);
+        let time = virtual_time.as_generic();
+        *world.resource_mut::<Time>() = time;
+        world.run_schedule(PreUpdate);
+        world.run_schedule(RunFixedMainLoop);
+        world.run_schedule(Update);
+        world.run_schedule(Last);
+    }
+    world.remove_resource::<Warmup>();
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use bevy::{
+    app::MainScheduleOrder,
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
+
+use boids::BoidMemoryUsage;
+
+// frames of history the median is taken over
+const MEDIAN_WINDOW: usize = 120;
+// frames to collect before spikes are reported
+const MIN_SAMPLES: usize = 30;
+// minimum time between two dumps
+const DUMP_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Runs right after the main schedule at `index` to time it.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+struct PhaseMark(usize);
+
+/// One frame as seen by the watchdog.
+struct TickRecord {
+    tick: u64,
+    frame: Duration,
+    /// Time spent in each main schedule. The first phase also includes
+    /// everything between the end of the previous frame and its start.
+    phases: Vec<Duration>,
+    boids: usize,
+}
+
+#[derive(Resource)]
+struct Watchdog {
+    threshold: f32,
+    dump_ticks: usize,
+    dump_path: Option<PathBuf>,
+    phase_names: Vec<String>,
+    /// Where the previous phase ended
+    last_mark: Option<Instant>,
+    current: Vec<Duration>,
+    history: VecDeque<TickRecord>,
+    tick: u64,
+    last_dump: Option<Instant>,
+}
+
+impl Watchdog {
+    fn median_frame(&self) -> Option<Duration> {
+        if self.history.len() < MIN_SAMPLES {
+            return None;
+        }
+        let mut frames: Vec<Duration> = self.history
+            .iter()
+            .rev()
+            .take(MEDIAN_WINDOW)
+            .map(|record| record.frame)
+            .collect();
+        frames.sort_unstable();
+        Some(frames[frames.len() / 2])
+    }
+
+    fn dump(&self, path: &Path) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        let phases: Vec<String> = self.phase_names.iter().map(|name| format!("{name}_ms")).collect();
+        writeln!(file, "tick,frame_ms,{},boids", phases.join(","))?;
+        let skip = self.history.len().saturating_sub(self.dump_ticks);
+        for record in self.history.iter().skip(skip) {
+            let phases: Vec<String> = record.phases
+                .iter()
+                .map(|phase| format!("{:.3}", phase.as_secs_f64() * 1000.))
+                .collect();
+            writeln!(
+                file,
+                "{},{:.3},{},{}",
+                record.tick,
+                record.frame.as_secs_f64() * 1000.,
+                phases.join(","),
+                record.boids,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Watches for frames taking longer than `threshold` times the recent
+/// median, logs how long each main schedule took in that frame and, if a
+/// dump path is set, writes the last `dump_ticks` frames to it as CSV.
+pub struct WatchdogPlugin {
+    pub threshold: f32,
+    pub dump_ticks: usize,
+    pub dump_path: Option<PathBuf>,
+}
+
+impl Default for WatchdogPlugin {
+    fn default() -> Self {
+        WatchdogPlugin {
+            threshold: 3.,
+            dump_ticks: 300,
+            dump_path: None,
+        }
+    }
+}
+
+impl Plugin for WatchdogPlugin {
+    fn build(&self, app: &mut App) {
+        let labels: Vec<InternedScheduleLabel> = app.world().resource::<MainScheduleOrder>().labels.clone();
+        for (index, &label) in labels.iter().enumerate() {
+            let is_last = index == labels.len() - 1;
+            app.world_mut().resource_mut::<MainScheduleOrder>().insert_after(label, PhaseMark(index));
+            app.add_systems(PhaseMark(index), move |watchdog: ResMut<Watchdog>, memory: Option<Res<BoidMemoryUsage>>| {
+                mark_phase(watchdog, memory, is_last);
+            });
+        }
+        app.insert_resource(Watchdog {
+            threshold: self.threshold,
+            dump_ticks: self.dump_ticks,
+            dump_path: self.dump_path.clone(),
+            phase_names: labels.iter().map(|label| format!("{label:?}")).collect(),
+            last_mark: None,
+            current: Vec::with_capacity(labels.len()),
+            history: VecDeque::with_capacity(self.dump_ticks.max(MEDIAN_WINDOW)),
+            tick: 0,
+            last_dump: None,
+        });
+    }
+}
+
+fn mark_phase(mut watchdog: ResMut<Watchdog>, memory: Option<Res<BoidMemoryUsage>>, is_last: bool) {
+    let now = Instant::now();
+    let Some(last_mark) = watchdog.last_mark.replace(now) else {
+        // nothing to measure against on the very first mark
+        return;
+    };
+    watchdog.current.push(now - last_mark);
+    if !is_last {
+        return;
+    }
+
+    let phases = std::mem::take(&mut watchdog.current);
+    if phases.len() < watchdog.phase_names.len() {
+        // first frame, started partway through
+        return;
+    }
+    let record = TickRecord {
+        tick: watchdog.tick,
+        frame: phases.iter().sum(),
+        phases,
+        boids: memory.map_or(0, |memory| memory.boids),
+    };
+    watchdog.tick += 1;
+
+    let median = watchdog.median_frame();
+    let frame = record.frame;
+    watchdog.history.push_back(record);
+    let keep = watchdog.dump_ticks.max(MEDIAN_WINDOW);
+    while watchdog.history.len() > keep {
+        watchdog.history.pop_front();
+    }
+
+    let Some(median) = median else {
+        return;
+    };
+    if frame.as_secs_f32() <= median.as_secs_f32() * watchdog.threshold {
+        return;
+    }
+    report_spike(&watchdog, median);
+    let Some(path) = watchdog.dump_path.clone() else {
+        return;
+    };
+    if watchdog.last_dump.is_some_and(|last| now - last < DUMP_COOLDOWN) {
+        return;
+    }
+    match watchdog.dump(&path) {
+        Ok(()) => {
+            let ticks = watchdog.history.len().min(watchdog.dump_ticks);
+            info!("dumped the last {ticks} ticks to {}", path.display());
+        }
+        Err(err) => error!("failed to dump ticks to {}: {err}", path.display()),
+    }
+    watchdog.last_dump = Some(now);
+}
+
+/// Logs the latest frame in the history.
+fn report_spike(watchdog: &Watchdog, median: Duration) {
+    let Some(record) = watchdog.history.back() else {
+        return;
+    };
+    let phases: Vec<String> = watchdog.phase_names
+        .iter()
+        .zip(record.phases.iter())
+        .map(|(name, phase)| format!("{name} {:.2} ms", phase.as_secs_f64() * 1000.))
+        .collect();
+    warn!(
+        "frame {} took {:.2} ms ({:.1}x the median {:.2} ms): {}",
+        record.tick,
+        record.frame.as_secs_f64() * 1000.,
+        record.frame.as_secs_f64() / median.as_secs_f64(),
+        median.as_secs_f64() * 1000.,
+        phases.join(", "),
+    );
+}